@@ -0,0 +1,119 @@
+use crate::board::Board;
+use crate::color::Color;
+use crate::game::Game;
+use crate::moves::Move;
+use crate::name::Name;
+use crate::variant::{Outcome, Variant};
+
+// capturing is compulsory and losing all your pieces wins,
+// the king is just another piece
+pub struct Antichess;
+
+impl Variant for Antichess {
+	fn name(&self) -> &str {
+		"antichess"
+	}
+
+	fn standard_legality(&self) -> bool {
+		true
+	}
+
+	fn is_symmetric(&self) -> bool {
+		true
+	}
+
+	fn starting_fen(&self) -> &str {
+		"rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w - - 0 1"
+	}
+
+	fn promotions(&self) -> Vec<Name> {
+		let mut names = Name::promotions();
+		names.push(Name::King);
+		names
+	}
+
+	// there is no castling
+	fn pseudo_legal_moves(&self, game: &Game) -> Vec<Move> {
+		game.piece_moves()
+	}
+
+	fn is_in_check(&self, _board: &Board, _color: Color) -> bool {
+		false
+	}
+
+	fn filter_moves(&self, game: &Game, moves: Vec<Move>) -> Vec<Move> {
+		if moves.iter().any(|mv| game.is_capture(*mv)) {
+			return moves.into_iter().filter(|mv| game.is_capture(*mv)).collect();
+		}
+
+		moves
+	}
+
+	fn outcome(&self, game: &Game) -> Option<Outcome> {
+		[Color::White, Color::Black]
+			.iter()
+			.find(|color| !game.board().squares().any(|square| square.piece.is_some_and(|piece| piece.color == **color)))
+			.map(|color| Outcome::Win(*color))
+	}
+
+	// running out of moves wins too
+	fn no_moves_outcome(&self, game: &Game) -> Outcome {
+		Outcome::Win(game.turn())
+	}
+
+	// any side can still give all its pieces away
+	fn has_mating_material(&self, _game: &Game, _color: Color) -> bool {
+		true
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::square::Square;
+
+	#[test]
+	fn test_antichess() {
+		let game = Game::with_variant(Antichess);
+		assert_eq!(game.perft(1), 20);
+		assert_eq!(game.perft(2), 400);
+		assert_eq!(game.perft(3), 8067);
+
+		let game = Game::from_variant_fen(Antichess, "8/1p6/8/8/8/8/P7/8 w - - 0 1").unwrap();
+		let nodes: Vec<usize> = (1..=6).map(|depth| game.perft(depth)).collect();
+		assert_eq!(nodes, vec![2, 4, 4, 3, 1, 0]);
+	}
+
+	#[test]
+	fn test_antichess_captures() {
+		let mut game = Game::with_variant(Antichess);
+
+		game.make_move(game.parse_san("e4").unwrap()).unwrap();
+		game.make_move(game.parse_san("d5").unwrap()).unwrap();
+
+		// the capture is forced
+		assert_eq!(game.legal_moves().len(), 1);
+		assert_eq!(
+			game.move_piece(Square::from_str("d2").unwrap(), Square::from_str("d4").unwrap()),
+			Err("That move isn't allowed in this variant".to_string())
+		);
+
+		// the king can be captured, and pawns can promote to kings
+		let game = Game::from_variant_fen(Antichess, "4k3/1P6/8/8/8/8/8/4R3 w - - 0 1").unwrap();
+		assert!(!game.is_check());
+		assert_eq!(game.legal_moves().len(), 1);
+
+		let game = Game::from_variant_fen(Antichess, "8/1P6/8/8/8/8/8/k7 w - - 0 1").unwrap();
+		assert!(game.parse_uci("b7b8k").is_ok());
+
+		// losing every piece wins
+		let mut game = Game::from_variant_fen(Antichess, "8/8/8/8/8/8/8/R1r5 w - - 0 1").unwrap();
+		assert_eq!(game.outcome(), None);
+		game.make_move(game.parse_uci("a1c1").unwrap()).unwrap();
+		assert_eq!(game.outcome(), Some(Outcome::Win(Color::Black)));
+
+		// and so does running out of moves
+		let game = Game::from_variant_fen(Antichess, "8/8/8/8/8/8/1p6/1R6 b - - 0 1").unwrap();
+		assert_eq!(game.outcome(), Some(Outcome::Win(Color::Black)));
+	}
+}
@@ -0,0 +1,794 @@
+use crate::clock::{Clock, TimeControl, TimeSource, WallTime};
+use crate::color::Color;
+use crate::engine::{Engine, Limits};
+use crate::game::Game;
+use crate::moves::Move;
+use crate::syzygy::{Tablebase, Wdl};
+use crate::tree::GameTree;
+use crate::variant::Outcome;
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+// the results of the first engine of a match
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct Tally {
+	wins: usize,
+	draws: usize,
+	losses: usize,
+}
+
+// the expected score against an opponent rated `elo` points lower
+fn expected_score(elo: f64) -> f64 {
+	1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+fn elo_difference(score: f64) -> f64 {
+	-400.0 * (1.0 / score - 1.0).log10()
+}
+
+impl Tally {
+	pub fn new(wins: usize, draws: usize, losses: usize) -> Tally {
+		Tally {
+			wins,
+			draws,
+			losses,
+		}
+	}
+
+	pub fn wins(&self) -> usize {
+		self.wins
+	}
+
+	pub fn draws(&self) -> usize {
+		self.draws
+	}
+
+	pub fn losses(&self) -> usize {
+		self.losses
+	}
+
+	pub fn games(&self) -> usize {
+		self.wins + self.draws + self.losses
+	}
+
+	pub fn add(&mut self, outcome: Outcome, color: Color) {
+		match outcome {
+			Outcome::Win(winner) if winner == color => self.wins += 1,
+			Outcome::Win(_) => self.losses += 1,
+			Outcome::Draw => self.draws += 1,
+		}
+	}
+
+	// the points per game, a draw is half a point
+	pub fn score(&self) -> f64 {
+		if self.games() == 0 {
+			return 0.5;
+		}
+
+		(self.wins as f64 + self.draws as f64 / 2.0) / self.games() as f64
+	}
+
+	// the variance of the points of a single game
+	fn variance(&self) -> f64 {
+		let score = self.score();
+		let squares = self.wins as f64 * (1.0 - score).powi(2)
+			+ self.draws as f64 * (0.5 - score).powi(2)
+			+ self.losses as f64 * score.powi(2);
+
+		squares / self.games().max(1) as f64
+	}
+
+	// the difference in rating the score suggests, infinite after only wins or losses
+	pub fn elo(&self) -> f64 {
+		elo_difference(self.score())
+	}
+
+	// half the width of the 95% confidence interval of the elo difference,
+	// there is none when the interval reaches a score of 0 or 1
+	pub fn elo_margin(&self) -> Option<f64> {
+		let deviation = (self.variance() / self.games().max(1) as f64).sqrt();
+		let low = self.score() - 1.96 * deviation;
+		let high = self.score() + 1.96 * deviation;
+
+		if low <= 0.0 || high >= 1.0 {
+			return None;
+		}
+
+		Some((elo_difference(high) - elo_difference(low)) / 2.0)
+	}
+}
+
+impl fmt::Display for Tally {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "+{} ={} -{}, elo {:.1}", self.wins, self.draws, self.losses, self.elo())?;
+
+		match self.elo_margin() {
+			Some(margin) => write!(f, " +/- {:.1}", margin),
+			None => Ok(()),
+		}
+	}
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SprtStatus {
+	Continue,
+	// the first engine is at least `elo1` stronger
+	Pass,
+	// the first engine is at most `elo0` stronger
+	Fail,
+}
+
+// a sequential probability ratio test of whether the first engine is `elo1`
+// rather than `elo0` points stronger, which stops as soon as the games tell
+// with error rates `alpha` (passing wrongly) and `beta` (failing wrongly)
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Sprt {
+	elo0: f64,
+	elo1: f64,
+	alpha: f64,
+	beta: f64,
+}
+
+impl Sprt {
+	pub fn new(elo0: f64, elo1: f64, alpha: f64, beta: f64) -> Result<Sprt, String> {
+		if elo0.partial_cmp(&elo1) != Some(std::cmp::Ordering::Less) {
+			return Err("elo0 has to be less than elo1".to_string());
+		}
+
+		if !(alpha > 0.0 && alpha < 1.0 && beta > 0.0 && beta < 1.0) {
+			return Err("The error rates have to be between 0 and 1".to_string());
+		}
+
+		Ok(Sprt {
+			elo0,
+			elo1,
+			alpha,
+			beta,
+		})
+	}
+
+	// the log-likelihood ratio of the two hypotheses, with the points of
+	// a game taken as normally distributed. It stays 0 until the results vary
+	pub fn llr(&self, tally: &Tally) -> f64 {
+		let variance = tally.variance();
+		if variance == 0.0 {
+			return 0.0;
+		}
+
+		let score0 = expected_score(self.elo0);
+		let score1 = expected_score(self.elo1);
+
+		tally.games() as f64 * (score1 - score0) * (2.0 * tally.score() - score0 - score1) / (2.0 * variance)
+	}
+
+	// the ratios below the first fail the test and those above the second pass it
+	pub fn bounds(&self) -> (f64, f64) {
+		((self.beta / (1.0 - self.alpha)).ln(), ((1.0 - self.beta) / self.alpha).ln())
+	}
+
+	pub fn status(&self, tally: &Tally) -> SprtStatus {
+		let llr = self.llr(tally);
+		let (lower, upper) = self.bounds();
+
+		if llr >= upper {
+			SprtStatus::Pass
+		} else if llr <= lower {
+			SprtStatus::Fail
+		} else {
+			SprtStatus::Continue
+		}
+	}
+}
+
+// how a game of a match ended
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Termination {
+	// mate, stalemate or the end of a variant
+	Rules,
+	Repetition,
+	FiftyMoves,
+	InsufficientMaterial,
+	TimeForfeit,
+	IllegalMove,
+	// the engine didn't answer, e.g. because it crashed
+	EngineFailure,
+	// both engines agreed that one side was winning
+	Resignation,
+	// both engines agreed that the game was a draw
+	DrawAdjudication,
+	Tablebase,
+	MoveLimit,
+}
+
+impl Termination {
+	// the value of the PGN Termination tag
+	pub fn pgn_tag(self) -> &'static str {
+		match self {
+			Termination::Rules | Termination::Repetition | Termination::FiftyMoves | Termination::InsufficientMaterial => {
+				"normal"
+			},
+			Termination::TimeForfeit => "time forfeit",
+			Termination::IllegalMove => "rules infraction",
+			Termination::EngineFailure => "abandoned",
+			Termination::Resignation | Termination::DrawAdjudication | Termination::Tablebase | Termination::MoveLimit => {
+				"adjudication"
+			},
+		}
+	}
+}
+
+fn result_tag(outcome: Outcome) -> &'static str {
+	match outcome {
+		Outcome::Win(Color::White) => "1-0",
+		Outcome::Win(Color::Black) => "0-1",
+		Outcome::Draw => "1/2-1/2",
+	}
+}
+
+// a finished game of a match
+#[derive(Clone)]
+pub struct MatchGame {
+	tree: GameTree,
+	outcome: Outcome,
+	termination: Termination,
+	// the color of the first engine
+	color: Color,
+}
+
+impl MatchGame {
+	pub fn tree(&self) -> &GameTree {
+		&self.tree
+	}
+
+	pub fn outcome(&self) -> Outcome {
+		self.outcome
+	}
+
+	pub fn termination(&self) -> Termination {
+		self.termination
+	}
+
+	pub fn first_engine_color(&self) -> Color {
+		self.color
+	}
+}
+
+// where a game of the match starts, the moves of the opening are played
+// for the engines so they only see the position after them
+#[derive(Clone)]
+struct Opening {
+	start: Game,
+	moves: Vec<Move>,
+}
+
+// games between two engines. Every opening is played twice with the
+// colors swapped, and the games are arbitrated with `Game` so that
+// illegal moves lose
+pub struct Match {
+	engines: [Box<dyn Engine>; 2],
+	control: TimeControl,
+	source: Arc<dyn TimeSource>,
+	games: usize,
+	openings: Vec<Opening>,
+	event: String,
+	// the score and the moves of each side it has to last for
+	resign: Option<(i32, usize)>,
+	// the move from which on, the score and the moves of each side
+	draw: Option<(usize, i32, usize)>,
+	tablebase: Option<Tablebase>,
+	max_plies: Option<usize>,
+	sprt: Option<Sprt>,
+	played: Vec<MatchGame>,
+	tally: Tally,
+}
+
+impl Match {
+	pub fn new(first: Box<dyn Engine>, second: Box<dyn Engine>, control: TimeControl) -> Match {
+		Match {
+			engines: [first, second],
+			control,
+			source: Arc::new(WallTime::new()),
+			games: 2,
+			openings: vec![Opening {
+				start: Game::new(),
+				moves: Vec::new(),
+			}],
+			event: "Engine match".to_string(),
+			resign: None,
+			draw: None,
+			tablebase: None,
+			max_plies: None,
+			sprt: None,
+			played: Vec::new(),
+			tally: Tally::default(),
+		}
+	}
+
+	// the most games that are played, fewer when the SPRT ends the match
+	pub fn games(mut self, games: usize) -> Match {
+		self.games = games;
+		self
+	}
+
+	// the main lines of the trees, which are used in turn
+	pub fn openings(mut self, trees: &[GameTree]) -> Result<Match, String> {
+		if trees.is_empty() {
+			return Err("A match needs at least one opening".to_string());
+		}
+
+		self.openings = trees
+			.iter()
+			.map(|tree| Opening {
+				start: tree.node(tree.root()).game().clone(),
+				moves: tree.mainline().into_iter().filter_map(|id| tree.node(id).mv()).collect(),
+			})
+			.collect();
+
+		Ok(self)
+	}
+
+	pub fn time_source(mut self, source: Arc<dyn TimeSource>) -> Match {
+		self.source = source;
+		self
+	}
+
+	pub fn event(mut self, event: &str) -> Match {
+		self.event = event.to_string();
+		self
+	}
+
+	// a side wins once both engines have given it at least `score`
+	// centipawns for `moves` moves each
+	pub fn resign(mut self, score: i32, moves: usize) -> Match {
+		self.resign = Some((score, moves));
+		self
+	}
+
+	// the game is drawn once both engines have given a score of at most
+	// `score` centipawns either way for `moves` moves each, after move `after`
+	pub fn draw(mut self, after: usize, score: i32, moves: usize) -> Match {
+		self.draw = Some((after, score, moves));
+		self
+	}
+
+	// the games are decided by the tables once they are reached
+	pub fn tablebase(mut self, tablebase: Tablebase) -> Match {
+		self.tablebase = Some(tablebase);
+		self
+	}
+
+	// the game is drawn after this many moves of both sides together
+	pub fn max_plies(mut self, plies: usize) -> Match {
+		self.max_plies = Some(plies);
+		self
+	}
+
+	pub fn sprt(mut self, sprt: Sprt) -> Match {
+		self.sprt = Some(sprt);
+		self
+	}
+
+	pub fn tally(&self) -> Tally {
+		self.tally
+	}
+
+	pub fn played(&self) -> &[MatchGame] {
+		&self.played
+	}
+
+	pub fn sprt_status(&self) -> Option<SprtStatus> {
+		self.sprt.map(|sprt| sprt.status(&self.tally))
+	}
+
+	pub fn is_finished(&self) -> bool {
+		self.played.len() >= self.games || self.sprt_status().is_some_and(|status| status != SprtStatus::Continue)
+	}
+
+	// every game played so far
+	pub fn to_pgn(&self) -> String {
+		self.played.iter().map(|game| game.tree.to_pgn()).collect::<Vec<_>>().join("\n")
+	}
+
+	// plays the games that are left, `report` is called after each one
+	// with the results so far
+	pub fn run<F: FnMut(&MatchGame, &Tally)>(&mut self, mut report: F) -> Tally {
+		while !self.is_finished() {
+			let game = self.play_game(self.played.len());
+
+			self.tally.add(game.outcome, game.color);
+			report(&game, &self.tally);
+			self.played.push(game);
+		}
+
+		self.tally
+	}
+
+	fn play_game(&mut self, round: usize) -> MatchGame {
+		let opening = self.openings[(round / 2) % self.openings.len()].clone();
+		// the first engine is white in the even rounds
+		let color = if round.is_multiple_of(2) { Color::White } else { Color::Black };
+		let engine = |side: Color| if side == color { 0 } else { 1 };
+
+		let mut tree = GameTree::new(opening.start.clone());
+		for mv in opening.moves.iter() {
+			tree.play(*mv).unwrap();
+		}
+
+		tree.set_header("Event", &self.event);
+		tree.set_header("Round", &(round + 1).to_string());
+		tree.set_header("White", &self.engines[engine(Color::White)].name());
+		tree.set_header("Black", &self.engines[engine(Color::Black)].name());
+
+		let (outcome, termination, note) = self.arbitrate(&mut tree, &opening, engine);
+
+		tree.set_header("Result", result_tag(outcome));
+		tree.set_header("Termination", termination.pgn_tag());
+		tree.set_comment(tree.current(), &note).unwrap();
+
+		MatchGame {
+			tree,
+			outcome,
+			termination,
+			color,
+		}
+	}
+
+	// plays the game out, the note says how it ended
+	fn arbitrate<E: Fn(Color) -> usize>(
+		&mut self,
+		tree: &mut GameTree,
+		opening: &Opening,
+		engine: E,
+	) -> (Outcome, Termination, String) {
+		let mut game = tree.game().clone();
+		let mut moves = opening.moves.clone();
+
+		let mut positions: HashMap<u64, usize> = HashMap::new();
+		let mut replay = opening.start.clone();
+		*positions.entry(replay.position_key()).or_default() += 1;
+		for mv in opening.moves.iter() {
+			replay.make_move(*mv).unwrap();
+			*positions.entry(replay.position_key()).or_default() += 1;
+		}
+
+		// the scores of the engines from white's point of view, one for every move
+		let mut scores: Vec<Option<i32>> = Vec::new();
+
+		for index in 0..2 {
+			if let Err(error) = self.engines[index].new_game() {
+				let side = if engine(Color::White) == index { Color::White } else { Color::Black };
+				return (Outcome::Win(side.opposite()), Termination::EngineFailure, format!("{:?} failed: {}", side, error));
+			}
+		}
+
+		let mut clock = Clock::new(self.control.clone(), self.source.clone());
+		clock.start(game.turn());
+
+		loop {
+			if let Some(end) = self.adjudicate(&game, &positions, &scores, moves.len() - opening.moves.len()) {
+				return end;
+			}
+
+			let side = game.turn();
+			let limits = Limits::from_clock(&clock);
+			let reply = self.engines[engine(side)].go(&game, &opening.start, &moves, &limits);
+
+			if clock.press(side).is_err() {
+				let outcome = game.timeout_outcome(side);
+				return (outcome, Termination::TimeForfeit, format!("{:?} lost on time", side));
+			}
+
+			let reply = match reply {
+				Ok(reply) => reply,
+				Err(error) => {
+					return (Outcome::Win(side.opposite()), Termination::EngineFailure, format!("{:?} failed: {}", side, error));
+				},
+			};
+
+			let mv = match game.parse_uci(reply.uci()) {
+				Ok(mv) if game.legal_moves().contains(&mv) => mv,
+				_ => {
+					let note = format!("{:?} made the illegal move {}", side, reply.uci());
+					return (Outcome::Win(side.opposite()), Termination::IllegalMove, note);
+				},
+			};
+
+			game.make_move(mv).unwrap();
+			tree.play(mv).unwrap();
+			moves.push(mv);
+			scores.push(reply.score().map(|score| score.for_white(side)));
+			*positions.entry(game.position_key()).or_default() += 1;
+		}
+	}
+
+	// the rules of the game come first, then the tables and the engines
+	fn adjudicate(
+		&self,
+		game: &Game,
+		positions: &HashMap<u64, usize>,
+		scores: &[Option<i32>],
+		plies: usize,
+	) -> Option<(Outcome, Termination, String)> {
+		if let Some(outcome) = game.outcome() {
+			let note = match outcome {
+				Outcome::Win(color) if game.is_checkmate() => format!("{:?} mates", color),
+				Outcome::Draw if game.legal_moves().is_empty() => "Stalemate".to_string(),
+				_ => "The game ended by the rules of the variant".to_string(),
+			};
+
+			return Some((outcome, Termination::Rules, note));
+		}
+
+		let draw = |termination: Termination, note: &str| Some((Outcome::Draw, termination, note.to_string()));
+
+		if positions.get(&game.position_key()).is_some_and(|count| *count >= 3) {
+			return draw(Termination::Repetition, "Threefold repetition");
+		}
+
+		if game.halfmove_clock() >= 100 {
+			return draw(Termination::FiftyMoves, "Fifty moves without a capture or a pawn move");
+		}
+
+		if !game.has_mating_material(Color::White) && !game.has_mating_material(Color::Black) {
+			return draw(Termination::InsufficientMaterial, "Neither side can mate");
+		}
+
+		// a probe is only exact about the 50 move rule right after
+		// a capture or a pawn move, which is how the tables are reached
+		if let Some(tablebase) = &self.tablebase {
+			if game.halfmove_clock() == 0 {
+				if let Ok(wdl) = tablebase.probe_wdl(game) {
+					let outcome = match wdl {
+						Wdl::Win => Outcome::Win(game.turn()),
+						Wdl::Loss => Outcome::Win(game.turn().opposite()),
+						_ => Outcome::Draw,
+					};
+
+					return Some((outcome, Termination::Tablebase, format!("The tablebase gives {:?}", wdl)));
+				}
+			}
+		}
+
+		// the last moves of both engines, which all have to agree
+		let last = |moves: usize| -> Option<Vec<i32>> {
+			if moves == 0 || scores.len() < 2 * moves {
+				return None;
+			}
+
+			scores[scores.len() - 2 * moves..].iter().copied().collect()
+		};
+
+		if let Some((score, moves)) = self.resign {
+			if let Some(last) = last(moves) {
+				for color in [Color::White, Color::Black] {
+					let sign = if color == Color::White { 1 } else { -1 };
+					if last.iter().all(|value| value * sign >= score) {
+						let note = format!("Both engines agree that {:?} is winning", color);
+						return Some((Outcome::Win(color), Termination::Resignation, note));
+					}
+				}
+			}
+		}
+
+		if let Some((after, score, moves)) = self.draw {
+			if let Some(last) = last(moves) {
+				if game.fullmove_number() > after && last.iter().all(|value| value.abs() <= score) {
+					return draw(Termination::DrawAdjudication, "Both engines agree that the game is drawn");
+				}
+			}
+		}
+
+		if self.max_plies.is_some_and(|max| plies >= max) {
+			return draw(Termination::MoveLimit, "The move limit was reached");
+		}
+
+		None
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::clock::ManualTime;
+	use crate::engine::{Reply, Score};
+	use crate::variant::{Crazyhouse, KingOfTheHill};
+
+	use std::time::Duration;
+
+	// plays the first legal move, or the scripted one while there are any,
+	// and claims the same score every time
+	struct Scripted {
+		name: &'static str,
+		moves: Vec<&'static str>,
+		score: Option<Score>,
+		color: Option<Color>,
+		// how long every move takes on the manual clock
+		think: Option<(Arc<ManualTime>, Duration)>,
+	}
+
+	impl Scripted {
+		fn new(name: &'static str, score: Option<Score>) -> Box<Scripted> {
+			Box::new(Scripted {
+				name,
+				moves: Vec::new(),
+				score,
+				color: None,
+				think: None,
+			})
+		}
+	}
+
+	impl Engine for Scripted {
+		fn name(&self) -> String {
+			self.name.to_string()
+		}
+
+		fn go(&mut self, game: &Game, _start: &Game, _moves: &[Move], _limits: &Limits) -> Result<Reply, String> {
+			if let Some((time, duration)) = &self.think {
+				time.advance(*duration);
+			}
+
+			let uci = if self.moves.is_empty() {
+				game.to_uci(game.legal_moves()[0])
+			} else {
+				self.moves.remove(0).to_string()
+			};
+
+			// the score is kept to itself when playing the other color
+			let score = self.score.filter(|_| self.color.is_none_or(|color| color == game.turn()));
+			Ok(Reply::new(&uci, score))
+		}
+	}
+
+	fn minutes(minutes: u64) -> TimeControl {
+		TimeControl::sudden_death(Duration::from_secs(minutes * 60))
+	}
+
+	#[test]
+	fn test_elo() {
+		let tally = Tally::new(60, 20, 20);
+
+		assert_eq!(tally.games(), 100);
+		assert!((tally.score() - 0.7).abs() < 1e-9);
+		assert!((tally.elo() - 147.2).abs() < 0.1);
+		let margin = tally.elo_margin().unwrap();
+		assert!(margin > 50.0 && margin < 100.0);
+
+		assert_eq!(Tally::new(5, 10, 5).elo(), 0.0);
+		assert_eq!(Tally::new(3, 0, 0).elo(), f64::INFINITY);
+		assert!(Tally::new(30, 40, 30).elo_margin() < Tally::new(3, 4, 3).elo_margin());
+
+		// the interval of a few won games reaches a score of 1
+		assert_eq!(Tally::new(3, 0, 0).elo_margin(), None);
+		assert_eq!(Tally::new(9, 1, 0).elo_margin(), None);
+		assert_eq!(Tally::new(9, 1, 0).to_string(), "+9 =1 -0, elo 511.5");
+	}
+
+	#[test]
+	fn test_sprt() {
+		let sprt = Sprt::new(0.0, 10.0, 0.05, 0.05).unwrap();
+		let (lower, upper) = sprt.bounds();
+		assert!((upper - 2.944).abs() < 0.001);
+		assert!((lower + 2.944).abs() < 0.001);
+
+		assert_eq!(sprt.status(&Tally::new(12, 10, 10)), SprtStatus::Continue);
+		assert_eq!(sprt.status(&Tally::new(1300, 2000, 1000)), SprtStatus::Pass);
+		assert_eq!(sprt.status(&Tally::new(1000, 2000, 1100)), SprtStatus::Fail);
+		assert_eq!(sprt.llr(&Tally::new(0, 40, 0)), 0.0);
+
+		assert!(Sprt::new(10.0, 0.0, 0.05, 0.05).is_err());
+		assert!(Sprt::new(0.0, 10.0, 0.0, 0.05).is_err());
+		assert!(Sprt::new(0.0, 10.0, 0.05, f64::NAN).is_err());
+	}
+
+	#[test]
+	fn test_illegal_move() {
+		let mut cheat = Scripted::new("Cheat", None);
+		cheat.moves = vec!["e2e5"];
+
+		let mut arena = Match::new(cheat, Scripted::new("Honest", None), minutes(5)).games(1);
+		let tally = arena.run(|_, _| ());
+
+		assert_eq!(tally, Tally::new(0, 0, 1));
+
+		let game = &arena.played()[0];
+		assert_eq!(game.termination(), Termination::IllegalMove);
+		assert_eq!(game.tree().header("White"), Some("Cheat"));
+		assert!(arena.to_pgn().contains("[Termination \"rules infraction\"]"));
+		assert!(arena.to_pgn().contains("{White made the illegal move e2e5} 0-1"));
+	}
+
+	#[test]
+	fn test_adjudication() {
+		let winning = Some(Score::Centipawns(800));
+		let mut arena = Match::new(Scripted::new("Optimist", winning), Scripted::new("Optimist too", winning), minutes(5))
+			.resign(500, 3)
+			.games(2);
+
+		// both engines think the side to move is winning, so they never agree
+		arena = arena.max_plies(10);
+		assert_eq!(arena.run(|_, _| ()), Tally::new(0, 2, 0));
+		assert_eq!(arena.played()[1].termination(), Termination::MoveLimit);
+
+		let losing = Some(Score::Centipawns(-800));
+		let mut arena = Match::new(Scripted::new("Strong", winning), Scripted::new("Weak", losing), minutes(5))
+			.resign(500, 3)
+			.games(2);
+
+		let mut reports = 0;
+		let tally = arena.run(|game, tally| {
+			reports += 1;
+			assert_eq!(tally.games(), reports);
+			assert_eq!(game.tree().mainline().len(), 6);
+		});
+
+		assert_eq!(tally, Tally::new(2, 0, 0));
+		assert_eq!(arena.played()[0].outcome(), Outcome::Win(Color::White));
+		assert_eq!(arena.played()[1].first_engine_color(), Color::Black);
+		assert_eq!(arena.played()[1].outcome(), Outcome::Win(Color::Black));
+
+		let even = Some(Score::Centipawns(0));
+		let mut arena =
+			Match::new(Scripted::new("Drawish", even), Scripted::new("Drawish", even), minutes(5)).draw(4, 10, 2).games(1);
+
+		arena.run(|_, _| ());
+		assert_eq!(arena.played()[0].termination(), Termination::DrawAdjudication);
+		assert_eq!(arena.played()[0].tree().game().fullmove_number(), 5);
+
+		// a lone king can still win King of the Hill or with a piece in the pocket
+		let ended = |game: &Game| {
+			arena
+				.adjudicate(game, &HashMap::new(), &[], 0)
+				.map(|(_, termination, _)| termination)
+		};
+
+		let kings = "4k3/8/8/8/8/8/8/4K3 w - - 0 1";
+		assert_eq!(ended(&Game::from_fen(kings).unwrap()), Some(Termination::InsufficientMaterial));
+		assert_eq!(ended(&Game::from_variant_fen(KingOfTheHill, kings).unwrap()), None);
+		assert_eq!(ended(&Game::from_variant_fen(Crazyhouse, "4k3/8/8/8/8/8/8/4K3[n] w - - 0 1").unwrap()), None);
+	}
+
+	#[test]
+	fn test_time_forfeit() {
+		let time = Arc::new(ManualTime::new());
+		let mut slow = Scripted::new("Slow", None);
+		slow.think = Some((time.clone(), Duration::from_secs(130)));
+
+		let mut arena = Match::new(slow, Scripted::new("Fast", None), minutes(5)).time_source(time).games(1);
+		arena.run(|_, _| ());
+
+		let game = &arena.played()[0];
+		assert_eq!(game.termination(), Termination::TimeForfeit);
+		assert_eq!(game.outcome(), Outcome::Win(Color::Black));
+		assert_eq!(game.tree().mainline().len(), 4);
+	}
+
+	#[test]
+	fn test_openings_and_sprt() {
+		let openings = GameTree::read_pgns("1. e4 e5 *\n\n1. d4 d5 *").unwrap();
+		// the engines only agree when the first one is white, the other games
+		// are drawn, which is enough for the test to pass early
+		let mut strong = Scripted::new("Strong", Some(Score::Mate(5)));
+		strong.color = Some(Color::White);
+		let mut weak = Scripted::new("Weak", Some(Score::Mate(-5)));
+		weak.color = Some(Color::Black);
+
+		let mut arena = Match::new(strong, weak, minutes(5))
+			.openings(&openings)
+			.unwrap()
+			.resign(1_000, 2)
+			.max_plies(40)
+			.sprt(Sprt::new(0.0, 20.0, 0.05, 0.05).unwrap())
+			.games(1_000);
+
+		let tally = arena.run(|_, _| ());
+		assert!(tally.games() < 1_000);
+		assert_eq!(arena.sprt_status(), Some(SprtStatus::Pass));
+
+		let third = arena.played()[2].tree();
+		let first_move = third.node(third.mainline()[0]).mv().unwrap();
+		assert_eq!(Game::new().to_san(first_move), "d4");
+	}
+}
@@ -0,0 +1,125 @@
+use crate::board::Board;
+use crate::color::Color;
+use crate::game::Game;
+use crate::moves::Move;
+use crate::name::Name;
+use crate::square::Square;
+use crate::variant::{Outcome, Variant};
+
+// captures blow up every piece around them apart from pawns
+pub struct Atomic;
+
+impl Variant for Atomic {
+	fn name(&self) -> &str {
+		"atomic"
+	}
+
+	fn is_symmetric(&self) -> bool {
+		true
+	}
+
+	// kings can't capture, they would blow themselves up
+	fn pseudo_legal_moves(&self, game: &Game) -> Vec<Move> {
+		let mut moves: Vec<Move> = game
+			.piece_moves()
+			.into_iter()
+			.filter(|mv| {
+				let piece = game.board().get(mv.from).piece.unwrap();
+				piece.name != Name::King || game.board().get(mv.to).is_empty()
+			})
+			.collect();
+
+		moves.extend(game.castling_moves());
+		moves
+	}
+
+	// nothing can attack a square next to the other king,
+	// since capturing there would blow up that king as well
+	fn is_attacked(&self, board: &Board, square: Square, color: Color) -> bool {
+		let next_to_king = match board.find_king(color.opposite()) {
+			Some(king) => board
+				.neighbours(square)
+				.iter()
+				.any(|neighbour| neighbour.rank == king.rank && neighbour.file == king.file),
+			None => true,
+		};
+
+		!next_to_king && board.is_attacked(square, color)
+	}
+
+	// blowing up your own king is never allowed,
+	// blowing up the other king wins straight away
+	fn is_legal(&self, game: &Game, _mv: Move, after: &Board) -> bool {
+		if after.find_king(game.turn()).is_none() {
+			return false;
+		}
+
+		if after.find_king(game.turn().opposite()).is_none() {
+			return true;
+		}
+
+		!self.is_in_check(after, game.turn())
+	}
+
+	fn after_apply(&self, board: &mut Board, mv: Move, _color: Color, capture: bool) {
+		if capture {
+			board.explode(mv.to);
+		}
+	}
+
+	fn outcome(&self, game: &Game) -> Option<Outcome> {
+		[Color::White, Color::Black]
+			.iter()
+			.find(|color| game.board().find_king(**color).is_none())
+			.map(|color| Outcome::Win(color.opposite()))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_perft_atomic() {
+		let game =
+			Game::from_variant_fen(Atomic, "rn2kb1r/1pp1p2p/p2q1pp1/3P4/2P3b1/4PN2/PP3PPP/R2QKB1R b KQkq -")
+				.unwrap();
+		assert_eq!(game.perft(1), 40);
+		assert_eq!(game.perft(2), 1238);
+
+		// castling next to the other king, where the squares
+		// the king passes can't be attacked
+		let game = Game::from_variant_fen(Atomic, "r3k1rR/5K2/8/8/8/8/8/8 b kq -").unwrap();
+		assert_eq!(game.perft(1), 25);
+		assert_eq!(game.perft(2), 282);
+		assert_eq!(game.perft(3), 6753);
+	}
+
+	#[test]
+	fn test_atomic_explosion() {
+		let mut game = Game::from_variant_fen(Atomic, "4k3/8/2pnp3/3r4/8/8/8/3RK3 w - - 0 1").unwrap();
+
+		game.make_move(game.parse_uci("d1d5").unwrap()).unwrap();
+		assert_eq!(game.to_fen(), "4k3/8/2p1p3/8/8/8/8/4K3 b - - 0 1");
+		assert_eq!(game.winner(), None);
+
+		let mut game = Game::from_variant_fen(Atomic, "3qk3/3p4/8/8/8/8/8/3QK3 w - - 0 1").unwrap();
+
+		game.make_move(game.parse_uci("d1d7").unwrap()).unwrap();
+		assert_eq!(game.to_fen(), "8/8/8/8/8/8/8/4K3 b - - 0 1");
+		assert_eq!(game.winner(), Some(Color::White));
+		assert!(game.legal_moves().is_empty());
+	}
+
+	#[test]
+	fn test_atomic_kings() {
+		let fen = "4k3/8/8/8/8/8/4p3/4K3 w - - 0 1";
+		assert_eq!(Game::from_fen(fen).unwrap().legal_moves().len(), 3);
+		assert_eq!(Game::from_variant_fen(Atomic, fen).unwrap().legal_moves().len(), 2);
+
+		// a king next to the other king can't be in check
+		let fen = "4r3/8/8/8/8/8/3kK3/8 w - - 0 1";
+		assert!(Game::from_fen(fen).unwrap().is_check());
+		assert!(!Game::from_variant_fen(Atomic, fen).unwrap().is_check());
+	}
+}
@@ -0,0 +1,237 @@
+use crate::board::Board;
+use crate::color::Color;
+use crate::file::File;
+use crate::movement::Movement;
+use crate::name::Name;
+use crate::rank::Rank;
+use crate::square::Square;
+
+// the pieces of each color that attack every square of a board,
+// a piece attacking a square taken by its own side defends it
+#[derive(Clone)]
+pub struct AttackMap {
+	width: usize,
+	squares: Vec<Square>,
+	pieces: Vec<[Vec<Square>; 2]>,
+}
+
+impl AttackMap {
+	// the pieces of the color that could capture on the square,
+	// with the squares they stand on
+	pub fn pieces(&self, square: Square, color: Color) -> &[Square] {
+		&self.pieces[self.index(square)][color.index()]
+	}
+
+	pub fn count(&self, square: Square, color: Color) -> usize {
+		self.pieces(square, color).len()
+	}
+
+	// the pieces of the other side than the one standing on the square
+	pub fn attackers(&self, square: Square) -> &[Square] {
+		match self.squares[self.index(square)].piece {
+			Some(piece) => self.pieces(square, piece.color.opposite()),
+			None => &[],
+		}
+	}
+
+	// the pieces of the same side as the one standing on the square
+	pub fn defenders(&self, square: Square) -> &[Square] {
+		match self.squares[self.index(square)].piece {
+			Some(piece) => self.pieces(square, piece.color),
+			None => &[],
+		}
+	}
+
+	// the side with more pieces on the square, if any
+	pub fn controlled_by(&self, square: Square) -> Option<Color> {
+		let white = self.count(square, Color::White);
+		let black = self.count(square, Color::Black);
+
+		if white > black {
+			Some(Color::White)
+		} else if black > white {
+			Some(Color::Black)
+		} else {
+			None
+		}
+	}
+
+	// the pieces of the color that are attacked and not defended, kings left out
+	pub fn hanging(&self, color: Color) -> Vec<Square> {
+		self.squares
+			.iter()
+			.filter(|square| {
+				square.piece.is_some_and(|piece| piece.color == color && piece.name != Name::King)
+			})
+			.filter(|square| !self.attackers(**square).is_empty() && self.defenders(**square).is_empty())
+			.copied()
+			.collect()
+	}
+
+	fn index(&self, square: Square) -> usize {
+		usize::from(square.rank) * self.width + usize::from(square.file)
+	}
+}
+
+impl Board {
+	// walks the capturing moves of every piece once
+	pub fn attack_map(&self) -> AttackMap {
+		let mut map = AttackMap {
+			width: self.width(),
+			squares: self.squares().copied().collect(),
+			pieces: vec![[Vec::new(), Vec::new()]; self.width() * self.height()],
+		};
+
+		for square in self.squares() {
+			let piece = match square.piece {
+				Some(piece) => piece,
+				None => continue,
+			};
+
+			let mut targets: Vec<Square> = Vec::new();
+			for movement in piece.movements().iter().filter(|movement| movement.can_capture()) {
+				for target in self.attacked_squares(*square, movement) {
+					// a custom piece might reach the same square in more than one way
+					if !targets.iter().any(|found| found.rank == target.rank && found.file == target.file) {
+						targets.push(target);
+					}
+				}
+			}
+
+			for target in targets {
+				let index = map.index(target);
+				map.pieces[index][piece.color.index()].push(*square);
+			}
+		}
+
+		map
+	}
+
+	// the squares the movement reaches up to and including the first piece in the way
+	fn attacked_squares(&self, square: Square, movement: &Movement) -> Vec<Square> {
+		let mut squares = Vec::new();
+		let direction_change = match square.piece.unwrap().color {
+			Color::White => 1,
+			Color::Black => -1,
+		};
+
+		let mut curr_rank: isize = square.rank.into();
+		let mut curr_file: isize = square.file.into();
+		let mut hurdle = false;
+
+		'repetetive: loop {
+			for (j, direction) in movement.steps.iter().enumerate() {
+				curr_rank += direction.delta_y() * direction_change;
+				curr_file += direction.delta_x() * direction_change;
+
+				if !self.contains(curr_rank, curr_file) {
+					break 'repetetive;
+				}
+
+				let target = *self.get(Square::new(Rank(curr_rank as usize), File(curr_file as usize)));
+
+				if j != movement.steps.len() - 1 {
+					if target.is_empty() || movement.jump || movement.hop {
+						continue;
+					} else {
+						break 'repetetive;
+					}
+				}
+
+				// a hopper only attacks the square behind the first piece it meets
+				if movement.hop && !hurdle {
+					hurdle = !target.is_empty();
+					continue;
+				}
+
+				squares.push(target);
+
+				if !target.is_empty() || movement.hop {
+					break 'repetetive;
+				}
+			}
+
+			if !movement.repeat {
+				break;
+			}
+		}
+
+		squares
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::fairy::PieceKind;
+	use crate::game::Game;
+	use crate::variant::Variant;
+
+	use std::sync::Arc;
+
+	// the riders are known by their letters in the variant, registering
+	// them would change what the other tests see
+	struct Riders;
+
+	impl Variant for Riders {
+		fn name(&self) -> &str {
+			"riders"
+		}
+
+		fn pieces(&self) -> Vec<PieceKind> {
+			vec![PieceKind::nightrider(), PieceKind::grasshopper()]
+		}
+	}
+
+	fn riders(fen: &str) -> Game {
+		Game::from_fen_with_variant(fen, Arc::new(Riders)).unwrap()
+	}
+
+	fn square(name: &str) -> Square {
+		Square::from_str(name).unwrap()
+	}
+
+	fn names(squares: &[Square]) -> Vec<String> {
+		let mut names: Vec<String> = squares.iter().map(|square| square.to_string()).collect();
+		names.sort();
+		names
+	}
+
+	#[test]
+	fn test_same_as_can_capture() {
+		for fen in &[
+			"rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+			"r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+			"4k3/2g5/8/3P1h2/8/1G3p2/8/4K3 w - - 0 1",
+		] {
+			let board = riders(fen).board().clone();
+			let map = board.attack_map();
+
+			for square in board.squares() {
+				for color in &[Color::White, Color::Black] {
+					let expected = board.can_capture(*square, color.opposite());
+					assert_eq!(names(map.pieces(*square, *color)), names(&expected), "{} {:?}", square, color);
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn test_attackers_and_defenders() {
+		let game = Game::from_fen("4k3/8/4p3/3p4/4P3/5N2/8/4K3 w - - 0 1").unwrap();
+		let map = game.board().attack_map();
+
+		assert_eq!(names(map.attackers(square("d5"))), vec!["e4"]);
+		assert_eq!(names(map.defenders(square("d5"))), vec!["e6"]);
+		assert_eq!(names(map.attackers(square("e4"))), vec!["d5"]);
+		assert!(map.defenders(square("e4")).is_empty());
+		assert_eq!(map.count(square("d4"), Color::White), 1);
+		assert_eq!(map.count(square("d4"), Color::Black), 0);
+		assert_eq!(map.controlled_by(square("f5")), None);
+		assert_eq!(map.controlled_by(square("e5")), Some(Color::White));
+		assert!(map.attackers(square("a1")).is_empty());
+
+		assert_eq!(names(&map.hanging(Color::White)), vec!["e4"]);
+		assert!(map.hanging(Color::Black).is_empty());
+	}
+}
@@ -4,19 +4,45 @@ use crate::name::Name;
 use crate::square::Square;
 use crate::file::File;
 use crate::rank::Rank;
+use crate::movement::Movement;
 use crate::direction::Direction;
-use std::convert::TryFrom;
 
+// the largest width or height a board can have
+pub const MAX_SIZE: usize = 32;
+
+#[derive(Clone)]
 pub struct Board {
 	height: usize,
 	width: usize,
 	board: Vec<Vec<Square>>,
 }
 
+impl Default for Board {
+	fn default() -> Self {
+		Board::new()
+	}
+}
+
 impl Board {
 	pub fn new() -> Board {
-		let width = 8;
-		let height = 8;
+		Board::with_back_rank(&[
+			Name::Rook,
+			Name::Knight,
+			Name::Bishop,
+			Name::Queen,
+			Name::King,
+			Name::Bishop,
+			Name::Knight,
+			Name::Rook,
+		])
+	}
+
+	pub fn empty() -> Board {
+		Board::with_size(8, 8)
+	}
+
+	pub fn with_size(width: usize, height: usize) -> Board {
+		assert!(width <= MAX_SIZE && height <= MAX_SIZE, "the board is too large");
 
 		let mut board = Vec::with_capacity(height);
 		for i in 0..height {
@@ -26,36 +52,46 @@ impl Board {
 			}
 		}
 
-		Board::initialize(&mut board, height, width);
-
 		Board {
-			width: width,
-			height: height,
-			board: board,
+			width,
+			height,
+			board,
 		}
 	}
 
-	fn initialize(board: &mut Vec<Vec<Square>>, height: usize, width: usize) {
-		let names = vec![
-			Name::Rook,
-			Name::Knight,
-			Name::Bishop,
-			Name::Queen,
-			Name::King,
-			Name::Bishop,
-			Name::Knight,
-			Name::Rook,
-		];
+	// sets up the pawns and the given back rank, mirrored for black,
+	// on a board that is as wide as the back rank
+	pub fn with_back_rank(names: &[Name]) -> Board {
+		let mut board = Board::with_size(names.len(), 8);
+		let (height, width) = (board.height, board.width);
+
+		Board::initialize(&mut board.board, height, width, names);
 
+		board
+	}
+
+	fn initialize(board: &mut [Vec<Square>], height: usize, width: usize, names: &[Name]) {
 		for i in 0..width {
 			board[1][i].piece = Some(Piece::new(Color::White, Name::Pawn));
-			board[6][i].piece = Some(Piece::new(Color::Black, Name::Pawn));
+			board[height - 2][i].piece = Some(Piece::new(Color::Black, Name::Pawn));
 
 			board[0][i].piece = Some(Piece::new(Color::White, names[i]));
-			board[7][i].piece = Some(Piece::new(Color::Black, names[i]));
+			board[height - 1][i].piece = Some(Piece::new(Color::Black, names[i]));
 		}
 	}
 
+	pub fn width(&self) -> usize {
+		self.width
+	}
+
+	pub fn height(&self) -> usize {
+		self.height
+	}
+
+	pub fn contains(&self, rank: isize, file: isize) -> bool {
+		rank >= 0 && file >= 0 && (rank as usize) < self.height && (file as usize) < self.width
+	}
+
 	pub fn get(&self, square: Square) -> &Square {
 		&self.board[usize::from(square.rank)][usize::from(square.file)]
 	}
@@ -66,21 +102,80 @@ impl Board {
 
 	pub fn move_piece(&mut self, from_square: Square, to_square: Square) {
 		let from_square = &mut self.get_mut(from_square);
-		let piece = from_square.piece.take();
+		let mut piece = from_square.piece.take();
 		let to_square = &mut self.get_mut(to_square);
 
+		if let Some(piece) = piece.as_mut() {
+			piece.moved = true;
+		}
+
 		to_square.piece = piece;
 	}
 
+	pub fn squares(&self) -> impl Iterator<Item = &Square> {
+		self.board.iter().flat_map(|rank| rank.iter())
+	}
+
+	pub fn find_king(&self, color: Color) -> Option<Square> {
+		self.squares()
+			.find(|square| match square.piece {
+				Some(piece) => piece.color == color && piece.name == Name::King,
+				None => false,
+			})
+			.copied()
+	}
+
+	// whether a piece of the opposite color of `color` attacks the square
+	pub fn is_attacked(&self, square: Square, color: Color) -> bool {
+		!self.can_capture(square, color).is_empty()
+	}
+
 	pub fn capture_piece(&mut self, square: Square) {
 		let square = self.get_mut(square);
 		square.piece = None;
 	}
 
+	// the squares around the given one, leaving out those off the board
+	pub fn neighbours(&self, square: Square) -> Vec<Square> {
+		Direction::all()
+			.into_iter()
+			.filter_map(|direction| {
+				let rank = isize::from(square.rank) + direction.delta_y();
+				let file = isize::from(square.file) + direction.delta_x();
+
+				if self.contains(rank, file) {
+					Some(*self.get(Square::new(Rank(rank as usize), File(file as usize))))
+				} else {
+					None
+				}
+			})
+			.collect()
+	}
+
+	// a capture in atomic chess removes the piece on the square
+	// and every piece around it apart from pawns
+	pub fn explode(&mut self, square: Square) {
+		self.capture_piece(square);
+
+		for neighbour in self.neighbours(square) {
+			if matches!(neighbour.piece, Some(piece) if piece.name != Name::Pawn) {
+				self.capture_piece(neighbour);
+			}
+		}
+	}
+
 	pub fn print(&self) {
-		println!(" ABCDEFGH");
+		// ranks can have more than one digit on large boards
+		let margin = self.height.to_string().len();
+
+		print!("{:margin$}", "", margin = margin);
+		for j in 0..self.width {
+			print!("{}", File(j));
+		}
+		println!();
+
 		for i in (0..self.height).rev() {
-			print!("{}", i + 1);
+			print!("{:>margin$}", i + 1, margin = margin);
 			for j in 0..self.width {
 				let square = self.get(Square::new(Rank(i), File(j)));
 				if !square.is_empty() {
@@ -95,41 +190,23 @@ impl Board {
 
 	pub fn legal_moves(&self, square: Square) -> Vec<Square> {
 		let square = self.get(square);
-		let mut legal_moves = Vec::new();
+		let mut legal_moves: Vec<Square> = Vec::new();
 
 		let piece = square.piece.unwrap();
 
-		if piece.special_capture_move() {
-			legal_moves.extend_from_slice(&self.calculate_legal_moves(
-				square,
-				piece.moves(),
-				false,
-			));
-
-			legal_moves.extend_from_slice(&self.calculate_legal_moves(
-				square,
-				piece.capture_moves(),
-				true,
-			));
-		} else {
-			legal_moves.extend_from_slice(&self.calculate_legal_moves(
-				square,
-				piece.moves(),
-				false,
-			));
+		for movement in piece.movements() {
+			for target in self.calculate_legal_moves(square, &movement) {
+				// a custom piece might reach the same square in more than one way
+				if !legal_moves.iter().any(|found| found.rank == target.rank && found.file == target.file) {
+					legal_moves.push(target);
+				}
+			}
 		}
 
-		return legal_moves;
+		legal_moves
 	}
 
-	// if the piece chosen piece doesn't have special capture moves,
-	// capture_moves should be set to false
-	fn calculate_legal_moves(
-		&self,
-		square: &Square,
-		moves: Vec<Vec<Direction>>,
-		capture_moves: bool,
-	) -> Vec<Square> {
+	fn calculate_legal_moves(&self, square: &Square, movement: &Movement) -> Vec<Square> {
 		let mut legal_moves = Vec::new();
 		let piece = square.piece.unwrap();
 		let direction_change = match piece.color {
@@ -137,69 +214,61 @@ impl Board {
 			Color::Black => -1,
 		};
 
-		for i in 0..moves.len() {
-			let mut curr_rank: isize = square.rank.into();
-			let mut curr_file: isize = square.file.into();
+		let mut curr_rank: isize = square.rank.into();
+		let mut curr_file: isize = square.file.into();
+		let mut hurdle = false;
 
-			'repetetive: loop {
-				for j in 0..moves[i].len() {
-					curr_rank += moves[i][j].delta_y() * direction_change;
-					curr_file += moves[i][j].delta_x() * direction_change;
+		'repetetive: loop {
+			for (j, direction) in movement.steps.iter().enumerate() {
+				curr_rank += direction.delta_y() * direction_change;
+				curr_file += direction.delta_x() * direction_change;
 
-					let file = match File::try_from(curr_file) {
-						Ok(f) => f,
-						Err(_) => break 'repetetive,
-					};
-
-					let rank = match Rank::try_from(curr_rank) {
-						Ok(r) => r,
-						Err(_) => break 'repetetive,
-					};
+				if !self.contains(curr_rank, curr_file) {
+					break 'repetetive;
+				}
 
-					let square = self.get(Square::new(rank, file));
+				let square = self.get(Square::new(Rank(curr_rank as usize), File(curr_file as usize)));
 
-					if j != moves[i].len() - 1 {
-						if square.is_empty() || piece.can_jump() {
-							continue;
-						} else {
-							break;
-						}
+				if j != movement.steps.len() - 1 {
+					if square.is_empty() || movement.jump || movement.hop {
+						continue;
 					} else {
-						let mut capture = false;
-
-						if square.is_empty() {
-							if capture_moves {
-								break 'repetetive;
-							}
-						} else {
-							if !capture_moves && piece.special_capture_move() {
-								break;
-							}
-
-							let captured_piece = square.piece.unwrap();
+						break 'repetetive;
+					}
+				}
 
-							if captured_piece.color == piece.color {
-								break 'repetetive;
-							} else {
-								capture = true;
-							}
-						}
+				// a hopper passes the empty squares until it finds something
+				// to hop over, and can only land on the square behind it
+				if movement.hop && !hurdle {
+					hurdle = !square.is_empty();
+					continue;
+				}
 
+				if square.is_empty() {
+					if movement.can_move() {
 						legal_moves.push(*square);
+					}
+				} else {
+					let captured_piece = square.piece.unwrap();
 
-						if capture {
-							break 'repetetive;
-						}
+					if captured_piece.color != piece.color && movement.can_capture() {
+						legal_moves.push(*square);
 					}
+
+					break 'repetetive;
 				}
 
-				if !piece.repetetive_moves() {
-					break;
+				if movement.hop {
+					break 'repetetive;
 				}
 			}
+
+			if !movement.repeat {
+				break;
+			}
 		}
 
-		return legal_moves;
+		legal_moves
 	}
 
 	pub fn can_capture(&self, square: Square, color: Color) -> Vec<Square> {
@@ -212,61 +281,77 @@ impl Board {
 			Color::Black => 1,
 		};
 
-		for name in &Name::all() {
+		// only the kinds of piece the other side has on the board can capture
+		let mut names: Vec<Name> = Vec::new();
+		for piece in self.squares().filter_map(|square| square.piece) {
+			if piece.color != color && !names.contains(&piece.name) {
+				names.push(piece.name);
+			}
+		}
+
+		for name in &names {
 			let piece = Piece::simple(*name);
-			let capture_moves = piece.capture_moves();
 
-			for i in 0..capture_moves.len() {
+			for movement in piece.movements() {
+				if !movement.can_capture() {
+					continue;
+				}
+
+				// walk the movement from the square back to the capturing piece
+				let movement = movement.backwards();
+
 				let mut curr_rank: isize = square.rank.into();
 				let mut curr_file: isize = square.file.into();
+				let mut hurdle = false;
 
 				'repetetive: loop {
-					for j in 0..capture_moves[i].len() {
-						curr_rank +=
-							capture_moves[i][j].backwards().delta_y() * direction_change;
-						curr_file +=
-							capture_moves[i][j].backwards().delta_x() * direction_change;
-
-						let file = match File::try_from(curr_file) {
-							Ok(f) => f,
-							Err(_) => break 'repetetive,
-						};
-
-						let rank = match Rank::try_from(curr_rank) {
-							Ok(r) => r,
-							Err(_) => break 'repetetive,
-						};
-
-						let square = self.get(Square::new(rank, file));
-
-						if j != capture_moves[i].len() - 1 {
-							if piece.can_jump() || square.is_empty() {
+					for (j, direction) in movement.steps.iter().enumerate() {
+						curr_rank += direction.delta_y() * direction_change;
+						curr_file += direction.delta_x() * direction_change;
+
+						if !self.contains(curr_rank, curr_file) {
+							break 'repetetive;
+						}
+
+						let square = self.get(Square::new(Rank(curr_rank as usize), File(curr_file as usize)));
+
+						if j != movement.steps.len() - 1 {
+							if movement.jump || movement.hop || square.is_empty() {
 								continue;
 							} else {
-								break;
+								break 'repetetive;
 							}
-						} else {
-							if !square.is_empty() {
-								let capturing_piece = square.piece.unwrap();
-								if capturing_piece.color != color &&
-									capturing_piece.name == *name
-								{
-									squares.push(*square);
-								}
+						}
 
+						// a hopper has to land right behind the piece it hops over,
+						// so the first square walking back has to be taken
+						if movement.hop && !hurdle {
+							if square.is_empty() {
 								break 'repetetive;
 							}
+
+							hurdle = true;
+							continue;
+						}
+
+						if !square.is_empty() {
+							let capturing_piece = square.piece.unwrap();
+							if capturing_piece.color != color && capturing_piece.name == *name {
+								squares.push(*square);
+							}
+
+							break 'repetetive;
 						}
 					}
 
-					if !piece.repetetive_moves() {
+					if !movement.repeat {
 						break;
 					}
 				}
 			}
 		}
 
-		return squares;
+		squares
 	}
 }
 
@@ -0,0 +1,82 @@
+use crate::color::Color;
+use crate::file::File;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum CastlingSide {
+	King,
+	Queen,
+}
+
+impl CastlingSide {
+	pub fn all() -> Vec<CastlingSide> {
+		vec![CastlingSide::King, CastlingSide::Queen]
+	}
+
+	// the king and the rook always end up on the same files,
+	// no matter where they started (which matters for Chess960)
+	pub fn king_to(&self, width: usize) -> File {
+		match self {
+			CastlingSide::King => File(width - 2),
+			CastlingSide::Queen => File(2),
+		}
+	}
+
+	pub fn rook_to(&self, width: usize) -> File {
+		match self {
+			CastlingSide::King => File(width - 3),
+			CastlingSide::Queen => File(3),
+		}
+	}
+
+	fn index(&self) -> usize {
+		match self {
+			CastlingSide::King => 0,
+			CastlingSide::Queen => 1,
+		}
+	}
+}
+
+// the castling rights are stored as the file of the rook that is
+// allowed to castle, so that any starting position can be described
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CastlingRights {
+	rooks: [[Option<File>; 2]; 2],
+}
+
+impl CastlingRights {
+	pub fn none() -> CastlingRights {
+		CastlingRights {
+			rooks: [[None; 2]; 2],
+		}
+	}
+
+	pub fn standard(width: usize) -> CastlingRights {
+		let mut rights = CastlingRights::none();
+
+		for color in &[Color::White, Color::Black] {
+			rights.set(*color, CastlingSide::King, Some(File(width - 1)));
+			rights.set(*color, CastlingSide::Queen, Some(File(0)));
+		}
+
+		rights
+	}
+
+	pub fn get(&self, color: Color, side: CastlingSide) -> Option<File> {
+		self.rooks[color.index()][side.index()]
+	}
+
+	pub fn set(&mut self, color: Color, side: CastlingSide, rook: Option<File>) {
+		self.rooks[color.index()][side.index()] = rook;
+	}
+
+	pub fn clear(&mut self, color: Color) {
+		self.rooks[color.index()] = [None; 2];
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.rooks.iter().flatten().all(|rook| rook.is_none())
+	}
+}
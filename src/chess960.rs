@@ -0,0 +1,162 @@
+use crate::board::Board;
+use crate::castling::{CastlingRights, CastlingSide};
+use crate::color::Color;
+use crate::file::File;
+use crate::game::Game;
+use crate::name::Name;
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+pub const STANDARD_INDEX: usize = 518;
+
+// the placement of the knights for the numbers 0 to 9,
+// counted among the five squares left after the bishops and the queen
+const KNIGHTS: [(usize, usize); 10] = [
+	(0, 1),
+	(0, 2),
+	(0, 3),
+	(0, 4),
+	(1, 2),
+	(1, 3),
+	(1, 4),
+	(2, 3),
+	(2, 4),
+	(3, 4),
+];
+
+// the starting back rank for a position number between 0 and 959,
+// using Scharnagl's numbering where 518 is the standard position
+pub fn back_rank(index: usize) -> Option<Vec<Name>> {
+	if index >= 960 {
+		return None;
+	}
+
+	let mut names: Vec<Option<Name>> = vec![None; 8];
+
+	let (n, light) = (index / 4, index % 4);
+	names[2 * light + 1] = Some(Name::Bishop);
+
+	let (n, dark) = (n / 4, n % 4);
+	names[2 * dark] = Some(Name::Bishop);
+
+	let (n, queen) = (n / 6, n % 6);
+	place(&mut names, queen, Name::Queen);
+
+	let (first, second) = KNIGHTS[n];
+	// placing the second knight first keeps the index of the first one valid
+	place(&mut names, second, Name::Knight);
+	place(&mut names, first, Name::Knight);
+
+	place(&mut names, 0, Name::Rook);
+	place(&mut names, 0, Name::King);
+	place(&mut names, 0, Name::Rook);
+
+	Some(names.into_iter().map(Option::unwrap).collect())
+}
+
+// puts the piece on the n:th empty square
+fn place(names: &mut [Option<Name>], n: usize, name: Name) {
+	let index = (0..names.len()).filter(|i| names[*i].is_none()).nth(n).unwrap();
+	names[index] = Some(name);
+}
+
+impl Game {
+	pub fn chess960(index: usize) -> Result<Game, String> {
+		let names = back_rank(index).ok_or(format!("{} is not a Chess960 position", index))?;
+
+		let mut castling = CastlingRights::none();
+		let rooks: Vec<usize> = (0..names.len()).filter(|i| names[*i] == Name::Rook).collect();
+
+		for color in &[Color::White, Color::Black] {
+			castling.set(*color, CastlingSide::Queen, Some(File(rooks[0])));
+			castling.set(*color, CastlingSide::King, Some(File(rooks[1])));
+		}
+
+		let mut game = Game::from_board(Board::with_back_rank(&names), castling);
+		game.chess960 = true;
+
+		Ok(game)
+	}
+
+	pub fn chess960_random() -> Game {
+		// the hasher is seeded randomly for every process,
+		// which is random enough for picking a starting position
+		let seed = RandomState::new().build_hasher().finish();
+
+		Game::chess960((seed % 960) as usize).unwrap()
+	}
+
+	pub fn set_chess960(&mut self, chess960: bool) {
+		self.chess960 = chess960;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_standard_index() {
+		let game = Game::chess960(STANDARD_INDEX).unwrap();
+
+		assert_eq!(game.board().placement(), Game::new().board().placement());
+		assert_eq!(game.to_fen(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+	}
+
+	#[test]
+	fn test_positions() {
+		assert_eq!(Game::chess960(0).unwrap().board().placement(), "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR");
+		assert_eq!(Game::chess960(959).unwrap().board().placement(), "rkrnnqbb/pppppppp/8/8/8/8/PPPPPPPP/RKRNNQBB");
+		assert!(Game::chess960(960).is_err());
+	}
+
+	#[test]
+	fn test_all_positions_are_valid() {
+		for index in 0..960 {
+			let names = back_rank(index).unwrap();
+			let position = |name| names.iter().position(|n| *n == name).unwrap();
+			let rooks: Vec<usize> = (0..8).filter(|i| names[*i] == Name::Rook).collect();
+			let bishops: Vec<usize> = (0..8).filter(|i| names[*i] == Name::Bishop).collect();
+
+			assert!(rooks[0] < position(Name::King) && position(Name::King) < rooks[1]);
+			assert_ne!(bishops[0] % 2, bishops[1] % 2);
+		}
+	}
+
+	#[test]
+	fn test_castling() {
+		// the king on b1 castles queen side with the rook on a1,
+		// which moves the king to c1 and the rook to d1
+		let mut game = Game::from_fen("4k2r/8/8/8/8/8/8/RK4R1 w GAh - 0 1").unwrap();
+		assert!(game.is_chess960());
+
+		let castle = game.parse_uci("b1a1").unwrap();
+		assert!(castle.is_castle());
+		assert_eq!(game.to_uci(castle), "b1a1");
+
+		game.make_move(castle).unwrap();
+		assert_eq!(game.to_shredder_fen(), "4k2r/8/8/8/8/8/8/2KR2R1 b h - 1 1");
+	}
+
+	#[test]
+	fn test_castling_king_stays() {
+		// the king is already on g1 and only the rook moves
+		let mut game = Game::from_fen("4k3/8/8/8/8/8/8/4R1KR w H - 0 1").unwrap();
+
+		let castle = game.parse_uci("g1h1").unwrap();
+		game.make_move(castle).unwrap();
+
+		assert_eq!(game.board().placement(), "4k3/8/8/8/8/8/8/4RRK1");
+	}
+
+	#[test]
+	fn test_perft() {
+		let game =
+			Game::from_fen("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9").unwrap();
+
+		assert_eq!(game.perft(1), 21);
+		assert_eq!(game.perft(2), 528);
+		assert_eq!(game.perft(3), 12189);
+	}
+}
@@ -0,0 +1,543 @@
+use crate::color::Color;
+use crate::game::Game;
+use crate::name::Name;
+use crate::variant::Outcome;
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// where the clock gets the time from, so that tests can move it by hand
+pub trait TimeSource: Send + Sync {
+	// the time since some fixed point, which only matters to the source
+	fn now(&self) -> Duration;
+}
+
+// the real time
+pub struct WallTime {
+	start: Instant,
+}
+
+impl Default for WallTime {
+	fn default() -> Self {
+		WallTime::new()
+	}
+}
+
+impl WallTime {
+	pub fn new() -> WallTime {
+		WallTime { start: Instant::now() }
+	}
+}
+
+impl TimeSource for WallTime {
+	fn now(&self) -> Duration {
+		self.start.elapsed()
+	}
+}
+
+// a time that only moves when it's told to
+#[derive(Default)]
+pub struct ManualTime {
+	now: Mutex<Duration>,
+}
+
+impl ManualTime {
+	pub fn new() -> ManualTime {
+		ManualTime::default()
+	}
+
+	pub fn advance(&self, duration: Duration) {
+		*self.now.lock().unwrap() += duration;
+	}
+}
+
+impl TimeSource for ManualTime {
+	fn now(&self) -> Duration {
+		*self.now.lock().unwrap()
+	}
+}
+
+// what a player gets for every move
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Bonus {
+	None,
+	// added after the move (Fischer)
+	Increment(Duration),
+	// the time used is given back after the move, up to the delay (Bronstein)
+	Bronstein(Duration),
+	// the clock only starts counting down once the delay has passed (simple or US delay)
+	Delay(Duration),
+}
+
+// a part of the time control, e.g. 40 moves in 90 minutes
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Period {
+	moves: Option<usize>,
+	time: Duration,
+	bonus: Bonus,
+}
+
+impl Period {
+	// the time for the rest of the game
+	pub fn sudden_death(time: Duration) -> Period {
+		Period {
+			moves: None,
+			time,
+			bonus: Bonus::None,
+		}
+	}
+
+	// the time for the given number of moves, after which the next period starts
+	pub fn moves(moves: usize, time: Duration) -> Period {
+		Period {
+			moves: Some(moves),
+			time,
+			bonus: Bonus::None,
+		}
+	}
+
+	pub fn increment(mut self, increment: Duration) -> Period {
+		self.bonus = Bonus::Increment(increment);
+		self
+	}
+
+	pub fn bronstein(mut self, delay: Duration) -> Period {
+		self.bonus = Bonus::Bronstein(delay);
+		self
+	}
+
+	pub fn delay(mut self, delay: Duration) -> Period {
+		self.bonus = Bonus::Delay(delay);
+		self
+	}
+
+	pub fn move_count(&self) -> Option<usize> {
+		self.moves
+	}
+
+	pub fn time(&self) -> Duration {
+		self.time
+	}
+
+	pub fn bonus(&self) -> Bonus {
+		self.bonus
+	}
+}
+
+// the periods are played in order, the last one is repeated
+// if it ends after a number of moves (e.g. 40 moves every 2 hours)
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimeControl {
+	periods: Vec<Period>,
+}
+
+impl TimeControl {
+	pub fn new(periods: Vec<Period>) -> Result<TimeControl, String> {
+		if periods.is_empty() {
+			return Err("A time control needs at least one period".to_string());
+		}
+
+		Ok(TimeControl { periods })
+	}
+
+	pub fn sudden_death(time: Duration) -> TimeControl {
+		TimeControl {
+			periods: vec![Period::sudden_death(time)],
+		}
+	}
+
+	pub fn fischer(time: Duration, increment: Duration) -> TimeControl {
+		TimeControl {
+			periods: vec![Period::sudden_death(time).increment(increment)],
+		}
+	}
+
+	pub fn periods(&self) -> &[Period] {
+		&self.periods
+	}
+
+	fn period(&self, index: usize) -> Period {
+		self.periods[index.min(self.periods.len() - 1)]
+	}
+}
+
+// the clocks of both players, only the one of the side to move runs
+pub struct Clock {
+	control: TimeControl,
+	source: Arc<dyn TimeSource>,
+	remaining: [Duration; 2],
+	period: [usize; 2],
+	// the moves made in the current period
+	moves: [usize; 2],
+	// the side whose clock is running and when it was started
+	running: Option<(Color, Duration)>,
+	flagged: Option<Color>,
+}
+
+impl Clock {
+	pub fn new(control: TimeControl, source: Arc<dyn TimeSource>) -> Clock {
+		let time = control.period(0).time;
+
+		Clock {
+			control,
+			source,
+			remaining: [time, time],
+			period: [0, 0],
+			moves: [0, 0],
+			running: None,
+			flagged: None,
+		}
+	}
+
+	pub fn control(&self) -> &TimeControl {
+		&self.control
+	}
+
+	// starts the clock of the given side, e.g. white at the start of the game
+	pub fn start(&mut self, color: Color) {
+		self.pause();
+		self.running = Some((color, self.source.now()));
+	}
+
+	// stops the running clock, the time used so far is taken off
+	// but the move isn't counted and no bonus is given
+	pub fn pause(&mut self) {
+		if let Some((color, _)) = self.running {
+			let used = self.used(color);
+			self.take(color, used);
+			self.running = None;
+		}
+	}
+
+	pub fn running(&self) -> Option<Color> {
+		self.running.map(|(color, _)| color)
+	}
+
+	// the side has made its move: the time is taken off, the bonus given,
+	// the next period started if it's due and the other clock started
+	pub fn press(&mut self, color: Color) -> Result<(), String> {
+		if self.running() != Some(color) {
+			return Err(format!("The clock of {:?} isn't running", color));
+		}
+
+		if let Some(flagged) = self.flagged() {
+			return Err(format!("{:?} has run out of time", flagged));
+		}
+
+		let used = self.used(color);
+		self.take(color, used);
+
+		let index = color.index();
+		let period = self.control.period(self.period[index]);
+
+		match period.bonus {
+			Bonus::Increment(increment) => self.remaining[index] += increment,
+			Bonus::Bronstein(delay) => self.remaining[index] += used.min(delay),
+			Bonus::None | Bonus::Delay(_) => (),
+		}
+
+		self.moves[index] += 1;
+		if Some(self.moves[index]) == period.moves {
+			self.period[index] += 1;
+			self.moves[index] = 0;
+			self.remaining[index] += self.control.period(self.period[index]).time;
+		}
+
+		self.running = Some((color.opposite(), self.source.now()));
+		Ok(())
+	}
+
+	// the time left on the clock, counted down while it's running
+	pub fn remaining(&self, color: Color) -> Duration {
+		match self.running {
+			Some((running, _)) if running == color => self.remaining[color.index()].saturating_sub(self.used(color)),
+			_ => self.remaining[color.index()],
+		}
+	}
+
+	// the period the side is playing in, counted from 0
+	pub fn period(&self, color: Color) -> usize {
+		self.period[color.index()]
+	}
+
+	// the moves left until the next period starts
+	pub fn moves_to_go(&self, color: Color) -> Option<usize> {
+		let period = self.control.period(self.period[color.index()]);
+		period.moves.map(|moves| moves - self.moves[color.index()])
+	}
+
+	// the side that has run out of time, once a flag has fallen it stays down
+	pub fn flagged(&mut self) -> Option<Color> {
+		if self.flagged.is_none() {
+			if let Some((color, _)) = self.running {
+				if self.remaining(color) == Duration::ZERO {
+					self.flagged = Some(color);
+					self.running = None;
+				}
+			}
+		}
+
+		self.flagged
+	}
+
+	// the result of the game, which is decided on the board before it's decided by the clock
+	pub fn outcome(&mut self, game: &Game) -> Option<Outcome> {
+		if let Some(outcome) = game.outcome() {
+			return Some(outcome);
+		}
+
+		self.flagged().map(|color| game.timeout_outcome(color))
+	}
+
+	// the time counted against the running side, the delay is free
+	fn used(&self, color: Color) -> Duration {
+		let started = match self.running {
+			Some((running, started)) if running == color => started,
+			_ => return Duration::ZERO,
+		};
+
+		let elapsed = self.source.now().saturating_sub(started);
+		match self.control.period(self.period[color.index()]).bonus {
+			Bonus::Delay(delay) => elapsed.saturating_sub(delay),
+			_ => elapsed,
+		}
+	}
+
+	fn take(&mut self, color: Color, used: Duration) {
+		let index = color.index();
+		self.remaining[index] = self.remaining[index].saturating_sub(used);
+
+		if self.remaining[index] == Duration::ZERO {
+			self.flagged = Some(color);
+		}
+	}
+}
+
+impl Game {
+	// whether the side could still win with any series of legal moves,
+	// by the rules of the variant
+	pub fn has_mating_material(&self, color: Color) -> bool {
+		self.variant().has_mating_material(self, color)
+	}
+
+	// whether the side has the pieces to mate in standard chess.
+	// A lone king can't, and neither can a single knight against a lone king
+	// or bishops that all stand on squares of the same color as every other piece
+	pub fn has_standard_mating_material(&self, color: Color) -> bool {
+		let pieces = |side: Color| -> Vec<(Name, usize)> {
+			self.board
+				.squares()
+				.filter_map(|square| match square.piece {
+					Some(piece) if piece.color == side && piece.name != Name::King => {
+						Some((piece.name, (usize::from(square.rank) + usize::from(square.file)) % 2))
+					},
+					_ => None,
+				})
+				.collect()
+		};
+
+		let own = pieces(color);
+		let other = pieces(color.opposite());
+
+		// a piece in a pocket can be dropped on either color
+		let in_pocket = |side: Color| !self.pockets[side.index()].is_empty();
+
+		if in_pocket(color) {
+			return true;
+		}
+
+		if own.is_empty() {
+			return false;
+		}
+
+		if in_pocket(color.opposite()) {
+			return true;
+		}
+
+		if own.len() == 1 && own[0].0 == Name::Knight && other.is_empty() {
+			return false;
+		}
+
+		let shade = own[0].1;
+		let same_bishops = |pieces: &[(Name, usize)]| pieces.iter().all(|(name, square)| *name == Name::Bishop && *square == shade);
+
+		!(same_bishops(&own) && same_bishops(&other))
+	}
+
+	// losing on time is a draw when the opponent couldn't have mated
+	pub fn timeout_outcome(&self, flagged: Color) -> Outcome {
+		if self.has_mating_material(flagged.opposite()) {
+			Outcome::Win(flagged.opposite())
+		} else {
+			Outcome::Draw
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::variant;
+
+	fn minutes(minutes: u64) -> Duration {
+		Duration::from_secs(minutes * 60)
+	}
+
+	fn seconds(seconds: u64) -> Duration {
+		Duration::from_secs(seconds)
+	}
+
+	// white moves after `white` and black after `black`
+	fn play(clock: &mut Clock, time: &ManualTime, white: Duration, black: Duration) {
+		time.advance(white);
+		clock.press(Color::White).unwrap();
+		time.advance(black);
+		clock.press(Color::Black).unwrap();
+	}
+
+	#[test]
+	fn test_increment_and_bronstein() {
+		let time = Arc::new(ManualTime::new());
+		let mut clock = Clock::new(TimeControl::fischer(minutes(3), seconds(2)), time.clone());
+		clock.start(Color::White);
+
+		play(&mut clock, &time, seconds(10), seconds(1));
+		assert_eq!(clock.remaining(Color::White), minutes(3) - seconds(8));
+		assert_eq!(clock.remaining(Color::Black), minutes(3) + seconds(1));
+
+		time.advance(seconds(5));
+		assert_eq!(clock.remaining(Color::White), minutes(3) - seconds(13));
+		assert_eq!(clock.running(), Some(Color::White));
+
+		let control = TimeControl::new(vec![Period::sudden_death(minutes(1)).bronstein(seconds(3))]).unwrap();
+		let mut clock = Clock::new(control, time.clone());
+		clock.start(Color::White);
+
+		play(&mut clock, &time, seconds(10), seconds(2));
+		assert_eq!(clock.remaining(Color::White), minutes(1) - seconds(7));
+		assert_eq!(clock.remaining(Color::Black), minutes(1));
+	}
+
+	#[test]
+	fn test_simple_delay() {
+		let time = Arc::new(ManualTime::new());
+		let control = TimeControl::new(vec![Period::sudden_death(seconds(30)).delay(seconds(5))]).unwrap();
+		let mut clock = Clock::new(control, time.clone());
+		clock.start(Color::White);
+
+		time.advance(seconds(4));
+		assert_eq!(clock.remaining(Color::White), seconds(30));
+		time.advance(seconds(4));
+		assert_eq!(clock.remaining(Color::White), seconds(27));
+
+		clock.press(Color::White).unwrap();
+		time.advance(seconds(34));
+		assert_eq!(clock.flagged(), None);
+		time.advance(seconds(1));
+		assert_eq!(clock.flagged(), Some(Color::Black));
+		assert!(clock.press(Color::Black).is_err());
+	}
+
+	#[test]
+	fn test_periods() {
+		// 40 moves in 90 minutes, then 30 minutes for the rest, with 30 seconds a move
+		let control = TimeControl::new(vec![
+			Period::moves(40, minutes(90)).increment(seconds(30)),
+			Period::sudden_death(minutes(30)).increment(seconds(30)),
+		])
+		.unwrap();
+
+		let time = Arc::new(ManualTime::new());
+		let mut clock = Clock::new(control, time.clone());
+		clock.start(Color::White);
+
+		for _ in 0..39 {
+			play(&mut clock, &time, minutes(2), seconds(30));
+		}
+
+		assert_eq!(clock.moves_to_go(Color::White), Some(1));
+		assert_eq!(clock.remaining(Color::White), minutes(90) - seconds(39 * 90));
+
+		play(&mut clock, &time, minutes(2), seconds(30));
+		assert_eq!(clock.period(Color::White), 1);
+		assert_eq!(clock.moves_to_go(Color::White), None);
+		assert_eq!(clock.remaining(Color::White), minutes(60));
+		assert_eq!(clock.remaining(Color::Black), minutes(120));
+
+		// a repeating period starts over
+		let mut clock = Clock::new(TimeControl::new(vec![Period::moves(2, minutes(5))]).unwrap(), time.clone());
+		clock.start(Color::White);
+		play(&mut clock, &time, minutes(1), minutes(1));
+		play(&mut clock, &time, minutes(1), minutes(1));
+		assert_eq!(clock.remaining(Color::White), minutes(8));
+		assert_eq!(clock.period(Color::White), 1);
+		assert_eq!(clock.moves_to_go(Color::White), Some(2));
+
+		assert!(TimeControl::new(Vec::new()).is_err());
+	}
+
+	#[test]
+	fn test_timeout_outcome() {
+		let time = Arc::new(ManualTime::new());
+		let game = Game::from_fen("4k3/8/8/8/8/8/3Q4/4K3 w - - 0 1").unwrap();
+
+		let mut clock = Clock::new(TimeControl::sudden_death(minutes(1)), time.clone());
+		clock.start(Color::White);
+		time.advance(minutes(1));
+		assert_eq!(clock.outcome(&game), Some(Outcome::Draw));
+
+		let mut clock = Clock::new(TimeControl::sudden_death(minutes(1)), time.clone());
+		clock.start(Color::Black);
+		time.advance(minutes(2));
+		assert_eq!(clock.outcome(&game), Some(Outcome::Win(Color::White)));
+		assert_eq!(clock.running(), None);
+
+		let mated = |fen: &str, color: Color| Game::from_fen(fen).unwrap().has_mating_material(color);
+		assert!(!mated("4k3/8/8/8/8/8/8/4KN2 w - - 0 1", Color::White));
+		assert!(mated("4k2r/8/8/8/8/8/8/4KN2 w - - 0 1", Color::White));
+		assert!(!mated("4kb2/8/8/8/8/8/8/2B1K3 w - - 0 1", Color::White));
+		assert!(mated("4k1b1/8/8/8/8/8/8/2B1K3 w - - 0 1", Color::White));
+		assert!(mated("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1", Color::White));
+	}
+
+	#[test]
+	fn test_variant_mating_material() {
+		let mated = |variant: &str, fen: &str, color: Color| {
+			let game = Game::from_fen_with_variant(fen, variant::from_name(variant).unwrap()).unwrap();
+			game.has_mating_material(color)
+		};
+
+		// the pieces in the pockets can still be dropped
+		assert!(mated("crazyhouse", "4k3/8/8/8/8/8/8/4K3[QQ] w - - 0 1", Color::White));
+		assert!(mated("crazyhouse", "4k3/8/8/8/8/8/8/4KN2[p] w - - 0 1", Color::White));
+		assert!(!mated("crazyhouse", "4k3/8/8/8/8/8/8/4K3[QQ] w - - 0 1", Color::Black));
+		assert!(!mated("crazyhouse", "4k3/8/8/8/8/8/8/4KN2 w - - 0 1", Color::White));
+
+		// a lone king can still reach the hill
+		assert!(mated("kingofthehill", "4k3/8/8/8/8/8/8/4K3 w - - 0 1", Color::White));
+		assert!(!mated("chess", "4k3/8/8/8/8/8/8/4K3 w - - 0 1", Color::White));
+
+		// a single knight gives three checks, but a lone king none
+		assert!(mated("3check", "4k3/8/8/8/8/8/8/4KN2 w - - 0 1", Color::White));
+		assert!(!mated("3check", "4k3/8/8/8/8/8/8/4KN2 w - - 0 1", Color::Black));
+
+		// antichess is won by giving every piece away
+		assert!(mated("antichess", "4k3/8/8/8/8/8/8/4K3 w - - 0 1", Color::White));
+
+		// black wins the horde by taking every pawn, white still has to mate
+		assert!(mated("horde", "4k3/8/8/8/8/8/8/P7 w - - 0 1", Color::Black));
+		assert!(mated("horde", "4k3/8/8/8/8/8/8/P7 w - - 0 1", Color::White));
+		assert!(!mated("horde", "4k3/8/8/8/8/8/8/N7 w - - 0 1", Color::White));
+
+		let time = Arc::new(ManualTime::new());
+		let game = Game::from_variant_fen(variant::KingOfTheHill, "4k3/8/8/8/8/8/8/4K3 b - - 0 1").unwrap();
+		let mut clock = Clock::new(TimeControl::sudden_death(minutes(1)), time.clone());
+		clock.start(Color::Black);
+		time.advance(minutes(2));
+		assert_eq!(clock.outcome(&game), Some(Outcome::Win(Color::White)));
+	}
+}
@@ -0,0 +1,373 @@
+use crate::color::Color;
+use crate::file::File;
+use crate::game::Game;
+use crate::name::Name;
+use crate::rank::Rank;
+use crate::square::Square;
+use crate::tree::GameTree;
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use std::str::FromStr;
+
+// the order pieces are written in a material signature, custom pieces last
+fn signature_order(name: Name) -> (usize, char) {
+	let rank = match name {
+		Name::King => 0,
+		Name::Queen => 1,
+		Name::Rook => 2,
+		Name::Bishop => 3,
+		Name::Knight => 4,
+		Name::Pawn => 5,
+		Name::Custom(_) => 6,
+	};
+
+	(rank, char::from(name))
+}
+
+fn signature_side(mut names: Vec<Name>) -> String {
+	names.sort_by_key(|name| signature_order(*name));
+	names.into_iter().map(char::from).collect()
+}
+
+// e.g. "KRP vs KR", "KRPvKR" or "krp v kr", with white first
+fn parse_signature(signature: &str) -> Result<String, String> {
+	let invalid = || format!("'{}' is not a material signature like KRPvKR", signature);
+
+	let compact: String = signature.split_whitespace().collect::<Vec<_>>().join("").to_uppercase().replace("VS", "V");
+	let (white, black) = compact.split_once('V').ok_or_else(invalid)?;
+
+	let side = |letters: &str| -> Result<String, String> {
+		let names = letters.chars().map(|c| Name::try_from(c).map_err(|_| invalid())).collect::<Result<Vec<_>, _>>()?;
+		Ok(signature_side(names))
+	};
+
+	Ok(format!("{}v{}", side(white)?, side(black)?))
+}
+
+impl Game {
+	// the pieces of both sides, white first, e.g. "KRPvKR"
+	pub fn material_signature(&self) -> String {
+		let side = |color: Color| {
+			let names = self
+				.board
+				.squares()
+				.filter_map(|square| square.piece)
+				.filter(|piece| piece.color == color)
+				.map(|piece| piece.name)
+				.collect();
+
+			signature_side(names)
+		};
+
+		format!("{}v{}", side(Color::White), side(Color::Black))
+	}
+
+	// the same for every position with the same pieces on the same squares,
+	// the same side to move and the same castling rights
+	pub fn position_key(&self) -> u64 {
+		if let Some(key) = self.polyglot_key() {
+			return key;
+		}
+
+		let mut hasher = DefaultHasher::new();
+		self.position_fen().hash(&mut hasher);
+		hasher.finish()
+	}
+
+	// the FEN without the move counters, and without the en passant square
+	// as a FEN can have one where no pawn can take
+	fn position_fen(&self) -> String {
+		self.to_fen().split_whitespace().take(3).collect::<Vec<_>>().join(" ")
+	}
+}
+
+#[derive(Clone, PartialEq, Debug)]
+enum Location {
+	Anywhere,
+	Square(Rank, File),
+	Rank(Rank),
+	File(File),
+}
+
+#[derive(Clone, PartialEq, Debug)]
+enum Term {
+	Piece {
+		color: Color,
+		name: Name,
+		location: Location,
+		negated: bool,
+	},
+	Material(String),
+}
+
+// a query on the pieces of a position. Terms are separated by spaces
+// or "and", and every one of them has to hold in the same position:
+//
+//   R@7          a white rook on the 7th rank
+//   k@g8         the black king on g8
+//   P@e          a white pawn on the e-file
+//   !q           no black queen
+//   material:KRPvKR
+//
+// the case of a piece letter tells its color like in FEN
+#[derive(Clone, PartialEq, Debug)]
+pub struct Query {
+	terms: Vec<Term>,
+}
+
+impl Query {
+	pub fn parse(query: &str) -> Result<Query, String> {
+		let terms = query
+			.split_whitespace()
+			.filter(|term| !term.eq_ignore_ascii_case("and"))
+			.map(Query::parse_term)
+			.collect::<Result<Vec<_>, _>>()?;
+
+		if terms.is_empty() {
+			return Err("The query is empty".to_string());
+		}
+
+		Ok(Query {
+			terms,
+		})
+	}
+
+	fn parse_term(term: &str) -> Result<Term, String> {
+		let invalid = || format!("Invalid term '{}'", term);
+
+		if let Some(signature) = term.strip_prefix("material:") {
+			return Ok(Term::Material(parse_signature(signature)?));
+		}
+
+		let (negated, rest) = match term.strip_prefix('!') {
+			Some(rest) => (true, rest),
+			None => (false, term),
+		};
+
+		let (piece, location) = match rest.split_once('@') {
+			Some((piece, location)) => (piece, Some(location)),
+			None => (rest, None),
+		};
+
+		let mut chars = piece.chars();
+		let letter = match (chars.next(), chars.next()) {
+			(Some(letter), None) => letter,
+			_ => return Err(invalid()),
+		};
+
+		let location = match location {
+			None | Some("*") => Location::Anywhere,
+			Some(location) => {
+				if let Ok(square) = Square::from_str(location) {
+					Location::Square(square.rank, square.file)
+				} else if let Ok(rank) = Rank::from_str(location) {
+					Location::Rank(rank)
+				} else if let Ok(file) = File::from_str(location) {
+					Location::File(file)
+				} else {
+					return Err(invalid());
+				}
+			},
+		};
+
+		Ok(Term::Piece {
+			color: if letter.is_ascii_uppercase() { Color::White } else { Color::Black },
+			name: Name::try_from(letter).map_err(|_| invalid())?,
+			location,
+			negated,
+		})
+	}
+
+	pub fn matches(&self, game: &Game) -> bool {
+		self.terms.iter().all(|term| match term {
+			Term::Material(signature) => game.material_signature() == *signature,
+			Term::Piece {
+				color,
+				name,
+				location,
+				negated,
+			} => {
+				let found = game.board.squares().any(|square| {
+					let on_location = match location {
+						Location::Anywhere => true,
+						Location::Square(rank, file) => square.rank == *rank && square.file == *file,
+						Location::Rank(rank) => square.rank == *rank,
+						Location::File(file) => square.file == *file,
+					};
+
+					on_location && square.piece.is_some_and(|piece| piece.color == *color && piece.name == *name)
+				});
+
+				found != *negated
+			},
+		})
+	}
+}
+
+// games indexed by the positions and the material of their main lines
+#[derive(Clone, Default)]
+pub struct GameCollection {
+	games: Vec<GameTree>,
+	positions: HashMap<u64, Vec<usize>>,
+	materials: HashMap<String, Vec<usize>>,
+}
+
+impl GameCollection {
+	pub fn new() -> GameCollection {
+		GameCollection::default()
+	}
+
+	pub fn from_pgn(pgn: &str) -> Result<GameCollection, String> {
+		let mut collection = GameCollection::new();
+
+		for tree in GameTree::read_pgns(pgn)? {
+			collection.add(tree);
+		}
+
+		Ok(collection)
+	}
+
+	pub fn open<P: AsRef<Path>>(path: P) -> Result<GameCollection, String> {
+		let text = std::fs::read_to_string(path).map_err(|error| error.to_string())?;
+		GameCollection::from_pgn(&text)
+	}
+
+	// returns the index of the game
+	pub fn add(&mut self, tree: GameTree) -> usize {
+		let index = self.games.len();
+
+		for game in GameCollection::positions(&tree) {
+			let games = self.positions.entry(game.position_key()).or_default();
+			if games.last() != Some(&index) {
+				games.push(index);
+			}
+
+			let games = self.materials.entry(game.material_signature()).or_default();
+			if games.last() != Some(&index) {
+				games.push(index);
+			}
+		}
+
+		self.games.push(tree);
+		index
+	}
+
+	pub fn len(&self) -> usize {
+		self.games.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.games.is_empty()
+	}
+
+	pub fn game(&self, index: usize) -> &GameTree {
+		&self.games[index]
+	}
+
+	pub fn games(&self) -> &[GameTree] {
+		&self.games
+	}
+
+	// the games whose main line reaches the position, move counters aside
+	pub fn find_position(&self, game: &Game) -> Vec<usize> {
+		let fen = game.position_fen();
+
+		// the key can collide, so the games are checked
+		self.positions
+			.get(&game.position_key())
+			.map_or(Vec::new(), |games| games.clone())
+			.into_iter()
+			.filter(|index| GameCollection::positions(&self.games[*index]).any(|found| found.position_fen() == fen))
+			.collect()
+	}
+
+	// the games with a position with this material, e.g. "KRP vs KR"
+	pub fn find_material(&self, signature: &str) -> Result<Vec<usize>, String> {
+		let signature = parse_signature(signature)?;
+		Ok(self.materials.get(&signature).cloned().unwrap_or_default())
+	}
+
+	// the games with a position matching the query, see Query
+	pub fn search(&self, query: &str) -> Result<Vec<usize>, String> {
+		let query = Query::parse(query)?;
+
+		Ok((0..self.games.len())
+			.filter(|index| GameCollection::positions(&self.games[*index]).any(|game| query.matches(game)))
+			.collect())
+	}
+
+	// the starting position and every position of the main line
+	fn positions(tree: &GameTree) -> impl Iterator<Item = &Game> {
+		std::iter::once(tree.root()).chain(tree.mainline()).map(move |id| tree.node(id).game())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const PGN: &str = r#"[Event "Ruy Lopez"]
+
+1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 *
+
+[Event "Italian by transposition"]
+
+1. Nf3 Nc6 2. e4 e5 3. Bc4 *
+
+[Event "Rook endgame"]
+[FEN "6k1/8/8/8/8/7r/4P3/R3K3 w - - 0 1"]
+
+1. Ra7 Rh2 2. Kf1 Rxe2 3. Rb7 *
+"#;
+
+	#[test]
+	fn test_find_position() {
+		let collection = GameCollection::from_pgn(PGN).unwrap();
+		assert_eq!(collection.len(), 3);
+
+		let mut game = Game::new();
+		for san in &["e4", "e5", "Nf3", "Nc6"] {
+			game.make_move(game.parse_san(san).unwrap()).unwrap();
+		}
+
+		assert_eq!(collection.find_position(&game), vec![0, 1]);
+		assert_eq!(collection.find_position(&Game::new()), vec![0, 1]);
+
+		game.make_move(game.parse_san("Bc4").unwrap()).unwrap();
+		assert_eq!(collection.find_position(&game), vec![1]);
+	}
+
+	#[test]
+	fn test_find_material() {
+		let collection = GameCollection::from_pgn(PGN).unwrap();
+
+		assert_eq!(Game::new().material_signature(), "KQRRBBNNPPPPPPPPvKQRRBBNNPPPPPPPP");
+		assert_eq!(collection.find_material("KRP vs KR").unwrap(), vec![2]);
+		assert_eq!(collection.find_material("krvkr").unwrap(), vec![2]);
+		assert!(collection.find_material("KPR v KRP").unwrap().is_empty());
+		assert!(collection.find_material("KRP").is_err());
+	}
+
+	#[test]
+	fn test_search() {
+		let collection = GameCollection::from_pgn(PGN).unwrap();
+
+		assert_eq!(collection.search("R@7 and k@g8").unwrap(), vec![2]);
+		assert_eq!(collection.search("R@7 P@e2").unwrap(), vec![2]);
+		assert_eq!(collection.search("R@7 !P").unwrap(), vec![2]);
+		assert!(collection.search("R@8").unwrap().is_empty());
+		assert_eq!(collection.search("B@b5").unwrap(), vec![0]);
+		assert_eq!(collection.search("!q").unwrap(), vec![2]);
+		assert_eq!(collection.search("material:KRvKR r@e").unwrap(), vec![2]);
+		assert_eq!(collection.search("n@c6 and p@a").unwrap(), vec![0, 1]);
+		assert_eq!(collection.search("n@c6 and p@a6").unwrap(), vec![0]);
+
+		assert!(collection.search("").is_err());
+		assert!(collection.search("X@e4").is_err());
+		assert!(collection.search("R@e99").is_err());
+	}
+}
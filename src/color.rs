@@ -1,5 +1,24 @@
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 pub enum Color {
 	White,
 	Black,
 }
+
+impl Color {
+	pub fn opposite(&self) -> Color {
+		match self {
+			Color::White => Color::Black,
+			Color::Black => Color::White,
+		}
+	}
+
+	// for tables that are stored per color
+	pub(crate) fn index(&self) -> usize {
+		match self {
+			Color::White => 0,
+			Color::Black => 1,
+		}
+	}
+}
@@ -0,0 +1,277 @@
+use crate::color::Color;
+use crate::game::Game;
+use crate::moves::{Move, MoveKind};
+use crate::name::Name;
+use crate::pocket::Pocket;
+use crate::square::Square;
+use crate::variant::Variant;
+
+// captured pieces go to the capturer's pocket and can be dropped back
+pub struct Crazyhouse;
+
+impl Variant for Crazyhouse {
+	fn name(&self) -> &str {
+		"crazyhouse"
+	}
+
+	fn standard_legality(&self) -> bool {
+		true
+	}
+
+	fn starting_fen(&self) -> &str {
+		"rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR[] w KQkq - 0 1"
+	}
+
+	fn has_drops(&self) -> bool {
+		true
+	}
+
+	fn pseudo_legal_moves(&self, game: &Game) -> Vec<Move> {
+		let mut moves = game.piece_moves();
+		moves.extend(game.castling_moves());
+		moves.extend(game.drop_moves());
+		moves
+	}
+
+	// the move has been played, so the capturer is the side that isn't to move
+	fn after_play(&self, game: &mut Game, _mv: Move, captured: Option<Name>) {
+		if let Some(name) = captured {
+			let color = game.turn.opposite();
+			game.pocket_mut(color).add(name);
+		}
+	}
+}
+
+// the rules on each board of a Bughouse match, where captured pieces
+// go to the partner's pocket instead (see BughouseMatch)
+pub struct Bughouse;
+
+impl Variant for Bughouse {
+	fn name(&self) -> &str {
+		"bughouse"
+	}
+
+	fn standard_legality(&self) -> bool {
+		true
+	}
+
+	fn starting_fen(&self) -> &str {
+		Crazyhouse.starting_fen()
+	}
+
+	fn has_drops(&self) -> bool {
+		true
+	}
+
+	fn pseudo_legal_moves(&self, game: &Game) -> Vec<Move> {
+		Crazyhouse.pseudo_legal_moves(game)
+	}
+}
+
+impl Game {
+	pub fn pocket(&self, color: Color) -> &Pocket {
+		&self.pockets[color.index()]
+	}
+
+	pub fn pocket_mut(&mut self, color: Color) -> &mut Pocket {
+		&mut self.pockets[color.index()]
+	}
+
+	pub fn drop_piece(&mut self, name: Name, square: Square) -> Result<(), String> {
+		if !self.variant.has_drops() {
+			return Err("Pieces can't be dropped in this variant".to_string());
+		}
+
+		if self.pocket(self.turn).count(name) == 0 {
+			return Err("That piece is not in your pocket".to_string());
+		}
+
+		if !self.board.get(square).is_empty() {
+			return Err("Pieces can only be dropped on empty squares".to_string());
+		}
+
+		let square = Square::new(square.rank, square.file);
+		let found = self
+			.legal_moves()
+			.into_iter()
+			.find(|mv| mv.kind == MoveKind::Drop(name) && mv.to == square);
+
+		match found {
+			Some(mv) => {
+				self.record(mv);
+				Ok(())
+			},
+			None if name == Name::Pawn && (square.rank == 0 || square.rank == self.board.height() - 1) => {
+				Err("Pawns can't be dropped on the first or the last rank".to_string())
+			},
+			None => Err("That drop would leave your king in check".to_string()),
+		}
+	}
+
+	// the piece that goes into a pocket when the move is played,
+	// promoted pieces turn back into pawns
+	pub fn captured(&self, mv: Move) -> Option<Name> {
+		let square = match mv.kind {
+			MoveKind::Normal => mv.to,
+			MoveKind::EnPassant => Square::new(mv.from.rank, mv.to.file),
+			MoveKind::Castle(_) | MoveKind::Drop(_) => return None,
+		};
+
+		self.board.get(square).piece.map(|piece| if piece.promoted { Name::Pawn } else { piece.name })
+	}
+}
+
+// two games of Bughouse played side by side, the player with white on one
+// board is the partner of the player with black on the other board
+pub struct BughouseMatch {
+	games: [Game; 2],
+}
+
+impl Default for BughouseMatch {
+	fn default() -> Self {
+		BughouseMatch::new()
+	}
+}
+
+impl BughouseMatch {
+	pub fn new() -> BughouseMatch {
+		BughouseMatch {
+			games: [Game::with_variant(Bughouse), Game::with_variant(Bughouse)],
+		}
+	}
+
+	pub fn from_fens(first: &str, second: &str) -> Result<BughouseMatch, String> {
+		Ok(BughouseMatch {
+			games: [
+				Game::from_variant_fen(Bughouse, first)?,
+				Game::from_variant_fen(Bughouse, second)?,
+			],
+		})
+	}
+
+	pub fn game(&self, board: usize) -> &Game {
+		&self.games[board]
+	}
+
+	pub fn make_move(&mut self, board: usize, mv: Move) -> Result<(), String> {
+		if board > 1 {
+			return Err(format!("There is no board {}", board));
+		}
+
+		let captured = self.games[board].captured(mv);
+		let color = self.games[board].turn;
+
+		self.games[board].make_move(mv)?;
+
+		// the partner plays the other color, so the piece can be dropped right away.
+		// The moves on the other board no longer lead to its position on their own
+		if let Some(name) = captured {
+			self.games[1 - board].pocket_mut(color.opposite()).add(name);
+			self.games[1 - board].forget_moves();
+		}
+
+		Ok(())
+	}
+
+	// the match is over as soon as one of the boards is
+	pub fn is_over(&self) -> bool {
+		self.games.iter().any(|game| game.legal_moves().is_empty())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn perft(fen: &str, expected: &[usize]) {
+		let game = Game::from_variant_fen(Crazyhouse, fen).unwrap();
+
+		for (depth, nodes) in expected.iter().enumerate() {
+			assert_eq!(game.perft(depth + 1), *nodes, "{} at depth {}", fen, depth + 1);
+		}
+	}
+
+	#[test]
+	fn test_perft() {
+		perft("2k5/8/8/8/8/8/8/4K3[QRBNPqrbnp] w - -", &[301]);
+		perft("2k5/8/8/8/8/8/8/4K3[Qn] w - -", &[67, 3083]);
+		perft("r1bqk2r/pppp1ppp/2n1p3/4P3/1b1Pn3/2NB1N2/PPP2PPP/R1BQK2R[] b KQkq -", &[42, 1347]);
+		perft("4k3/1Q~6/8/8/4b3/8/Kpp5/8[] b - - 0 1", &[20, 360, 5445]);
+	}
+
+	#[test]
+	fn test_captures_go_to_the_pocket() {
+		let mut game = Game::with_variant(Crazyhouse);
+
+		for uci in &["e2e4", "d7d5", "e4d5", "d8d5", "b1c3"] {
+			let mv = game.parse_uci(uci).unwrap();
+			game.make_move(mv).unwrap();
+		}
+
+		assert_eq!(game.pocket(Color::White).names(), vec![Name::Pawn]);
+		assert_eq!(game.pocket(Color::Black).names(), vec![Name::Pawn]);
+		assert_eq!(game.to_fen(), "rnb1kbnr/ppp1pppp/8/3q4/8/2N5/PPPP1PPP/R1BQKBNR[Pp] b KQkq - 1 3");
+
+		game.drop_piece(Name::Pawn, Square::from_str("e4").unwrap()).unwrap();
+		assert!(game.pocket(Color::Black).is_empty());
+		assert!(game.drop_piece(Name::Pawn, Square::from_str("d8").unwrap()).is_err());
+		assert!(game.drop_piece(Name::Pawn, Square::from_str("b1").unwrap()).is_err());
+		assert!(game.drop_piece(Name::Knight, Square::from_str("f3").unwrap()).is_err());
+	}
+
+	#[test]
+	fn test_promoted_pieces_become_pawns() {
+		let mut game = Game::from_variant_fen(Crazyhouse, "4k3/1Q~6/8/8/8/8/8/4K3[] b - - 0 1").unwrap();
+		assert_eq!(game.to_fen(), "4k3/1Q~6/8/8/8/8/8/4K3[] b - - 0 1");
+
+		let game_with_queen = Game::from_variant_fen(Crazyhouse, "4k3/1Q6/8/8/8/8/8/4K3[q] w - - 0 1").unwrap();
+		assert_eq!(game_with_queen.pocket(Color::Black).count(Name::Queen), 1);
+
+		game.pocket_mut(Color::Black).add(Name::Rook);
+		let mv = game.parse_uci("R@b8").unwrap();
+		game.make_move(mv).unwrap();
+		game.make_move(game.parse_uci("e1d1").unwrap()).unwrap();
+		game.make_move(game.parse_uci("b8b7").unwrap()).unwrap();
+
+		assert_eq!(game.pocket(Color::Black).names(), vec![Name::Pawn]);
+		assert_eq!(game.to_fen(), "4k3/1r6/8/8/8/8/8/3K4[p] w - - 0 3");
+	}
+
+	#[test]
+	fn test_drop_notation() {
+		let game = Game::from_variant_fen(Crazyhouse, "4k3/8/8/8/8/8/8/4K3[NP] w - - 0 1").unwrap();
+
+		let mv = game.parse_uci("N@f6").unwrap();
+		assert_eq!(game.to_uci(mv), "N@f6");
+		assert_eq!(game.to_san(mv), "N@f6+");
+		assert_eq!(game.parse_san("N@f6+").unwrap(), mv);
+
+		let mv = game.parse_san("@e4").unwrap();
+		assert_eq!(game.parse_san("P@e4").unwrap(), mv);
+		assert_eq!(game.to_san(mv), "@e4");
+		assert_eq!(format!("{:?}", mv), "P@e4");
+	}
+
+	#[test]
+	fn test_bughouse() {
+		let mut bughouse = BughouseMatch::new();
+
+		for uci in &["e2e4", "d7d5", "e4d5"] {
+			let mv = bughouse.game(0).parse_uci(uci).unwrap();
+			bughouse.make_move(0, mv).unwrap();
+		}
+
+		// white captured on the first board, so black gets the pawn on the second
+		assert!(bughouse.game(0).pocket(Color::White).is_empty());
+		assert_eq!(bughouse.game(1).pocket(Color::Black).names(), vec![Name::Pawn]);
+
+		let mv = bughouse.game(1).parse_uci("e2e4").unwrap();
+		bughouse.make_move(1, mv).unwrap();
+		let mv = bughouse.game(1).parse_uci("P@d3").unwrap();
+		bughouse.make_move(1, mv).unwrap();
+
+		assert!(bughouse.game(1).pocket(Color::Black).is_empty());
+		assert!(!bughouse.is_over());
+		assert!(bughouse.make_move(2, mv).is_err());
+	}
+}
@@ -1,3 +1,5 @@
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Direction {
 	Up,
 	Down,
@@ -10,6 +12,33 @@ pub enum Direction {
 }
 
 impl Direction {
+	pub fn all() -> Vec<Direction> {
+		vec![
+			Direction::Up,
+			Direction::Down,
+			Direction::Left,
+			Direction::Right,
+			Direction::UpLeft,
+			Direction::UpRight,
+			Direction::DownLeft,
+			Direction::DownRight,
+		]
+	}
+
+	pub fn from_delta(delta_x: isize, delta_y: isize) -> Option<Direction> {
+		match (delta_x, delta_y) {
+			(0, 1) => Some(Direction::Up),
+			(0, -1) => Some(Direction::Down),
+			(-1, 0) => Some(Direction::Left),
+			(1, 0) => Some(Direction::Right),
+			(-1, 1) => Some(Direction::UpLeft),
+			(1, 1) => Some(Direction::UpRight),
+			(-1, -1) => Some(Direction::DownLeft),
+			(1, -1) => Some(Direction::DownRight),
+			_ => None,
+		}
+	}
+
 	pub fn delta_x(&self) -> isize {
 		match self {
 			Direction::Up => 0,
@@ -0,0 +1,190 @@
+use crate::clock::{Bonus, Clock};
+use crate::color::Color;
+use crate::game::Game;
+use crate::moves::Move;
+
+use std::time::Duration;
+
+// the evaluation of an engine, from the point of view of the side to move
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Score {
+	Centipawns(i32),
+	// the moves until mate, negative when the side to move is getting mated
+	Mate(i32),
+}
+
+const MATE: i32 = 30_000;
+
+impl Score {
+	// mates are worth more than any material, the sooner the better
+	pub fn centipawns(self) -> i32 {
+		match self {
+			Score::Centipawns(centipawns) => centipawns,
+			Score::Mate(moves) if moves > 0 => MATE - moves,
+			Score::Mate(moves) => -MATE - moves,
+		}
+	}
+
+	// the score from white's point of view, where `color` is the side that was to move
+	pub fn for_white(self, color: Color) -> i32 {
+		match color {
+			Color::White => self.centipawns(),
+			Color::Black => -self.centipawns(),
+		}
+	}
+}
+
+// how long an engine searches for a move, as in the UCI go command
+#[derive(Clone, PartialEq, Default, Debug)]
+pub struct Limits {
+	remaining: [Option<Duration>; 2],
+	increment: [Duration; 2],
+	moves_to_go: Option<usize>,
+	depth: Option<u32>,
+	nodes: Option<u64>,
+	move_time: Option<Duration>,
+}
+
+impl Limits {
+	pub fn depth(depth: u32) -> Limits {
+		Limits {
+			depth: Some(depth),
+			..Limits::default()
+		}
+	}
+
+	pub fn nodes(nodes: u64) -> Limits {
+		Limits {
+			nodes: Some(nodes),
+			..Limits::default()
+		}
+	}
+
+	// exactly this long for the move
+	pub fn move_time(time: Duration) -> Limits {
+		Limits {
+			move_time: Some(time),
+			..Limits::default()
+		}
+	}
+
+	// the time left on the clock of both players, with the increments they will get
+	pub fn from_clock(clock: &Clock) -> Limits {
+		let increment = |color: Color| {
+			let periods = clock.control().periods();
+			match periods[clock.period(color).min(periods.len() - 1)].bonus() {
+				Bonus::Increment(increment) => increment,
+				_ => Duration::ZERO,
+			}
+		};
+
+		Limits {
+			remaining: [Some(clock.remaining(Color::White)), Some(clock.remaining(Color::Black))],
+			increment: [increment(Color::White), increment(Color::Black)],
+			moves_to_go: clock.running().and_then(|color| clock.moves_to_go(color)),
+			..Limits::default()
+		}
+	}
+
+	pub fn remaining(&self, color: Color) -> Option<Duration> {
+		self.remaining[color.index()]
+	}
+
+	pub fn increment(&self, color: Color) -> Duration {
+		self.increment[color.index()]
+	}
+
+	// the moves until the next time control of the side to move
+	pub fn moves_to_go(&self) -> Option<usize> {
+		self.moves_to_go
+	}
+
+	pub fn max_depth(&self) -> Option<u32> {
+		self.depth
+	}
+
+	pub fn max_nodes(&self) -> Option<u64> {
+		self.nodes
+	}
+
+	pub fn time_per_move(&self) -> Option<Duration> {
+		self.move_time
+	}
+
+	// the longest the search can take, there is none when it's
+	// only limited by depth or nodes
+	pub fn deadline(&self, color: Color) -> Option<Duration> {
+		self.move_time.or(self.remaining(color))
+	}
+}
+
+// the answer of an engine. The move is in UCI notation and
+// is only checked by whoever plays it, as engines have bugs
+#[derive(Clone, PartialEq, Debug)]
+pub struct Reply {
+	uci: String,
+	score: Option<Score>,
+}
+
+impl Reply {
+	pub fn new(uci: &str, score: Option<Score>) -> Reply {
+		Reply {
+			uci: uci.to_string(),
+			score,
+		}
+	}
+
+	pub fn uci(&self) -> &str {
+		&self.uci
+	}
+
+	pub fn score(&self) -> Option<Score> {
+		self.score
+	}
+}
+
+// something that picks moves, in this process or in another one
+pub trait Engine {
+	fn name(&self) -> String;
+
+	// called before every game, e.g. to clear the hash tables
+	fn new_game(&mut self) -> Result<(), String> {
+		Ok(())
+	}
+
+	// finds a move in `game`, which was reached by playing `moves` from `start`
+	fn go(&mut self, game: &Game, start: &Game, moves: &[Move], limits: &Limits) -> Result<Reply, String>;
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::clock::{ManualTime, Period, TimeControl};
+
+	use std::sync::Arc;
+
+	#[test]
+	fn test_score() {
+		assert_eq!(Score::Centipawns(35).for_white(Color::Black), -35);
+		assert!(Score::Mate(3).centipawns() > Score::Mate(5).centipawns());
+		assert!(Score::Mate(-3).centipawns() < Score::Mate(-5).centipawns());
+		assert!(Score::Mate(-1).centipawns() < Score::Centipawns(-5_000).centipawns());
+	}
+
+	#[test]
+	fn test_limits() {
+		let time = Arc::new(ManualTime::new());
+		let control = TimeControl::new(vec![Period::moves(40, Duration::from_secs(60)).increment(Duration::from_secs(2))]).unwrap();
+		let mut clock = Clock::new(control, time.clone());
+
+		clock.start(Color::White);
+		time.advance(Duration::from_secs(10));
+		clock.press(Color::White).unwrap();
+
+		let limits = Limits::from_clock(&clock);
+		assert_eq!(limits.remaining(Color::White), Some(Duration::from_secs(52)));
+		assert_eq!(limits.remaining(Color::Black), Some(Duration::from_secs(60)));
+		assert_eq!(limits.increment(Color::Black), Duration::from_secs(2));
+		assert_eq!(limits.moves_to_go(), Some(40));
+	}
+}
@@ -0,0 +1,440 @@
+use crate::direction::Direction;
+use crate::movement::Movement;
+
+use std::fmt;
+use std::sync::{Mutex, OnceLock};
+
+// every piece that has been registered, the definitions are never freed
+// so that a PieceKind can be copied around like the standard names
+static REGISTRY: Mutex<Vec<&'static PieceDefinition>> = Mutex::new(Vec::new());
+
+const STANDARD_LETTERS: [char; 6] = ['K', 'Q', 'R', 'B', 'N', 'P'];
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct PieceDefinition {
+	name: String,
+	letter: char,
+	value: u32,
+	movements: Vec<Movement>,
+}
+
+impl PieceDefinition {
+	pub fn new(name: &str, letter: char, value: u32, movements: Vec<Movement>) -> PieceDefinition {
+		PieceDefinition {
+			name: name.to_string(),
+			letter: letter.to_ascii_uppercase(),
+			value,
+			movements,
+		}
+	}
+
+	// describes the movement with Betza's funny notation, see `parse_betza`
+	pub fn from_betza(name: &str, letter: char, value: u32, betza: &str) -> Result<PieceDefinition, String> {
+		Ok(PieceDefinition::new(name, letter, value, parse_betza(betza)?))
+	}
+
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	pub fn letter(&self) -> char {
+		self.letter
+	}
+
+	// in centipawns, like the standard pieces
+	pub fn value(&self) -> u32 {
+		self.value
+	}
+
+	pub fn movements(&self) -> &[Movement] {
+		&self.movements
+	}
+}
+
+#[derive(Copy, Clone)]
+pub struct PieceKind(&'static PieceDefinition);
+
+// the presets are built the first time they are asked for, but they are only
+// known by their letters once registered or in a variant that uses them
+static ARCHBISHOP: OnceLock<PieceDefinition> = OnceLock::new();
+static CHANCELLOR: OnceLock<PieceDefinition> = OnceLock::new();
+static AMAZON: OnceLock<PieceDefinition> = OnceLock::new();
+static CAMEL: OnceLock<PieceDefinition> = OnceLock::new();
+static GRASSHOPPER: OnceLock<PieceDefinition> = OnceLock::new();
+static NIGHTRIDER: OnceLock<PieceDefinition> = OnceLock::new();
+
+const PRESETS: [&OnceLock<PieceDefinition>; 6] = [&ARCHBISHOP, &CHANCELLOR, &AMAZON, &CAMEL, &GRASSHOPPER, &NIGHTRIDER];
+
+impl PieceKind {
+	// registering the same definition twice gives back the same kind,
+	// but a letter can't be shared by two different pieces
+	pub fn register(definition: PieceDefinition) -> Result<PieceKind, String> {
+		if !definition.letter.is_ascii_alphabetic() || STANDARD_LETTERS.contains(&definition.letter) {
+			return Err(format!("The letter '{}' can't be used for a custom piece", definition.letter));
+		}
+
+		let mut registry = REGISTRY.lock().unwrap();
+
+		if let Some(existing) = registry.iter().find(|existing| existing.letter == definition.letter) {
+			if **existing == definition {
+				return Ok(PieceKind(existing));
+			}
+
+			return Err(format!(
+				"The letter '{}' is already used by the {}",
+				definition.letter, existing.name
+			));
+		}
+
+		// a registered preset is the same kind as the one the variants use
+		let preset = PRESETS.iter().filter_map(|preset| preset.get()).find(|preset| **preset == definition);
+		let definition: &'static PieceDefinition = match preset {
+			Some(preset) => preset,
+			None => Box::leak(Box::new(definition)),
+		};
+
+		registry.push(definition);
+
+		Ok(PieceKind(definition))
+	}
+
+	// only the registered pieces are known by their letters
+	pub fn from_letter(letter: char) -> Option<PieceKind> {
+		let letter = letter.to_ascii_uppercase();
+
+		REGISTRY
+			.lock()
+			.unwrap()
+			.iter()
+			.find(|definition| definition.letter == letter)
+			.map(|definition| PieceKind(definition))
+	}
+
+	// the presets that have been built and every registered piece
+	pub fn all() -> Vec<PieceKind> {
+		let mut kinds: Vec<PieceKind> = PRESETS.iter().filter_map(|preset| preset.get()).map(PieceKind).collect();
+
+		for definition in REGISTRY.lock().unwrap().iter() {
+			if !kinds.iter().any(|kind| std::ptr::eq(kind.0, *definition)) {
+				kinds.push(PieceKind(definition));
+			}
+		}
+
+		kinds
+	}
+
+	pub fn definition(&self) -> &'static PieceDefinition {
+		self.0
+	}
+
+	// the well known fairy pieces
+
+	pub fn archbishop() -> PieceKind {
+		PieceKind::preset(&ARCHBISHOP, "Archbishop", 'A', 875, "BN")
+	}
+
+	pub fn chancellor() -> PieceKind {
+		PieceKind::preset(&CHANCELLOR, "Chancellor", 'C', 900, "RN")
+	}
+
+	pub fn amazon() -> PieceKind {
+		PieceKind::preset(&AMAZON, "Amazon", 'M', 1200, "QN")
+	}
+
+	pub fn camel() -> PieceKind {
+		PieceKind::preset(&CAMEL, "Camel", 'L', 250, "C")
+	}
+
+	pub fn grasshopper() -> PieceKind {
+		PieceKind::preset(&GRASSHOPPER, "Grasshopper", 'G', 200, "gQ")
+	}
+
+	pub fn nightrider() -> PieceKind {
+		PieceKind::preset(&NIGHTRIDER, "Nightrider", 'H', 500, "NN")
+	}
+
+	// the notation of the presets is known to be valid
+	fn preset(
+		cell: &'static OnceLock<PieceDefinition>,
+		name: &str,
+		letter: char,
+		value: u32,
+		betza: &str,
+	) -> PieceKind {
+		PieceKind(cell.get_or_init(|| PieceDefinition::from_betza(name, letter, value, betza).unwrap()))
+	}
+}
+
+impl PartialEq for PieceKind {
+	fn eq(&self, other: &PieceKind) -> bool {
+		std::ptr::eq(self.0, other.0)
+	}
+}
+
+impl fmt::Debug for PieceKind {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}", self.0.name)
+	}
+}
+
+// Betza's notation describes a piece as a list of atoms, each being a leap
+// in every direction, optionally preceded by modifiers:
+//
+//   W (1,0)  F (1,1)  D (2,0)  N (2,1)  A (2,2)  H (3,0)  C (3,1)  Z (3,2)  G (3,3)
+//   K = WF   Q = RB   R = WW   B = FF
+//
+// doubling an atom (NN) or following it by a 0 (N0) makes the piece a rider,
+// m and c limit the atom to moving or capturing, n makes a leap lame
+// (every square on the way has to be empty), and g makes the piece a
+// grasshopper that hops over the first piece on the line
+pub fn parse_betza(betza: &str) -> Result<Vec<Movement>, String> {
+	let mut movements = Vec::new();
+	let chars: Vec<char> = betza.chars().collect();
+	let mut i = 0;
+
+	while i < chars.len() {
+		let mut move_only = false;
+		let mut capture_only = false;
+		let mut lame = false;
+		let mut hop = false;
+
+		while i < chars.len() && chars[i].is_ascii_lowercase() {
+			match chars[i] {
+				'm' => move_only = true,
+				'c' => capture_only = true,
+				'n' => lame = true,
+				'g' => hop = true,
+				c => return Err(format!("Unknown modifier '{}' in '{}'", c, betza)),
+			}
+			i += 1;
+		}
+
+		let atom = *chars.get(i).ok_or(format!("Missing atom at the end of '{}'", betza))?;
+		i += 1;
+
+		let mut rider = false;
+		if i < chars.len() && (chars[i] == atom || chars[i] == '0') {
+			rider = true;
+			i += 1;
+		}
+
+		let leaps: Vec<((isize, isize), bool)> = match atom {
+			'K' => vec![((1, 0), rider), ((1, 1), rider)],
+			'Q' => vec![((1, 0), true), ((1, 1), true)],
+			'R' => vec![((1, 0), true)],
+			'B' => vec![((1, 1), true)],
+			_ => vec![(leap(atom).ok_or(format!("Unknown atom '{}' in '{}'", atom, betza))?, rider)],
+		};
+
+		if move_only && capture_only {
+			return Err(format!("'{}' can neither move nor capture", betza));
+		}
+
+		for ((x, y), rider) in leaps {
+			for (dx, dy) in symmetries(x, y) {
+				let mut movement = Movement::new(steps(dx, dy));
+
+				// a hopper always moves along the whole line
+				movement.repeat = rider || hop;
+				movement.jump = movement.steps.len() > 1 && !lame;
+				movement.hop = hop;
+
+				if move_only {
+					movement = movement.move_only();
+				}
+
+				if capture_only {
+					movement = movement.capture_only();
+				}
+
+				movements.push(movement);
+			}
+		}
+	}
+
+	Ok(movements)
+}
+
+fn leap(atom: char) -> Option<(isize, isize)> {
+	match atom {
+		'W' => Some((1, 0)),
+		'F' => Some((1, 1)),
+		'D' => Some((2, 0)),
+		'N' => Some((2, 1)),
+		'A' => Some((2, 2)),
+		'H' => Some((3, 0)),
+		'C' => Some((3, 1)),
+		'Z' => Some((3, 2)),
+		'G' => Some((3, 3)),
+		_ => None,
+	}
+}
+
+// every distinct way of making the leap, mirrored and rotated
+fn symmetries(x: isize, y: isize) -> Vec<(isize, isize)> {
+	let mut leaps = Vec::new();
+
+	for (a, b) in &[(x, y), (y, x)] {
+		for (sa, sb) in &[(1, 1), (1, -1), (-1, 1), (-1, -1)] {
+			let leap = (a * sa, b * sb);
+			if !leaps.contains(&leap) {
+				leaps.push(leap);
+			}
+		}
+	}
+
+	leaps
+}
+
+// the diagonal steps are taken first and then the straight ones,
+// the same way the knight is described
+fn steps(dx: isize, dy: isize) -> Vec<Direction> {
+	let diagonal = dx.abs().min(dy.abs());
+	let mut steps = Vec::new();
+
+	for _ in 0..diagonal {
+		steps.push(Direction::from_delta(dx.signum(), dy.signum()).unwrap());
+	}
+
+	let (rest_x, rest_y) = (dx - diagonal * dx.signum(), dy - diagonal * dy.signum());
+	for _ in 0..(rest_x.abs() + rest_y.abs()) {
+		steps.push(Direction::from_delta(rest_x.signum(), rest_y.signum()).unwrap());
+	}
+
+	steps
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::board::Board;
+	use crate::color::Color;
+	use crate::game::Game;
+	use crate::name::Name;
+	use crate::piece::Piece;
+	use crate::square::Square;
+	use crate::variant::{Capablanca, Variant};
+
+	use std::convert::TryFrom;
+	use std::sync::Arc;
+
+	// the riders are known by their letters in the variant, registering
+	// them would change what the other tests see
+	struct Riders;
+
+	impl Variant for Riders {
+		fn name(&self) -> &str {
+			"riders"
+		}
+
+		fn pieces(&self) -> Vec<PieceKind> {
+			vec![PieceKind::nightrider(), PieceKind::grasshopper()]
+		}
+	}
+
+	fn riders(fen: &str) -> Game {
+		Game::from_fen_with_variant(fen, Arc::new(Riders)).unwrap()
+	}
+
+	fn moves_on_empty_board(kind: PieceKind, square: &str) -> usize {
+		let mut board = Board::empty();
+		let square = Square::from_str(square).unwrap();
+
+		board.get_mut(square).piece = Some(Piece::new(Color::White, Name::Custom(kind)));
+		board.legal_moves(square).len()
+	}
+
+	#[test]
+	fn test_betza_atoms() {
+		assert_eq!(parse_betza("N").unwrap().len(), 8);
+		assert_eq!(parse_betza("W").unwrap().len(), 4);
+		assert_eq!(parse_betza("BN").unwrap().len(), 12);
+		assert!(parse_betza("mNcK").unwrap().iter().all(|movement| !movement.repeat));
+		assert!(parse_betza("X").is_err());
+		assert!(parse_betza("mcN").is_err());
+		assert!(parse_betza("N").unwrap().iter().all(|movement| movement.jump));
+		assert!(parse_betza("NN").unwrap().iter().all(|movement| movement.repeat && movement.jump));
+	}
+
+	#[test]
+	fn test_presets_on_empty_board() {
+		// bishop 13 + knight 8
+		assert_eq!(moves_on_empty_board(PieceKind::archbishop(), "d4"), 21);
+		// rook 14 + knight 8
+		assert_eq!(moves_on_empty_board(PieceKind::chancellor(), "d4"), 22);
+		// queen 27 + knight 8
+		assert_eq!(moves_on_empty_board(PieceKind::amazon(), "d4"), 35);
+		assert_eq!(moves_on_empty_board(PieceKind::camel(), "d4"), 8);
+		assert_eq!(moves_on_empty_board(PieceKind::camel(), "a1"), 2);
+		// b3, c5, d7 and c2, e3, g4
+		assert_eq!(moves_on_empty_board(PieceKind::nightrider(), "a1"), 6);
+		// nothing to hop over
+		assert_eq!(moves_on_empty_board(PieceKind::grasshopper(), "d4"), 0);
+	}
+
+	#[test]
+	fn test_register() {
+		let kind = PieceKind::camel();
+
+		// a preset isn't known by its letter until it's registered
+		assert_eq!(PieceKind::camel(), kind);
+		assert_eq!(PieceKind::from_letter('l'), None);
+		assert_eq!(PieceKind::register(kind.definition().clone()), Ok(kind));
+		assert_eq!(PieceKind::from_letter('l'), Some(kind));
+
+		assert!(PieceKind::register(PieceDefinition::from_betza("Knight", 'N', 300, "N").unwrap()).is_err());
+		assert!(PieceKind::register(PieceDefinition::from_betza("Dromedary", 'L', 250, "C").unwrap()).is_err());
+	}
+
+	#[test]
+	fn test_letters_of_a_variant() {
+		let wazir = PieceKind::register(PieceDefinition::from_betza("Wazir", 'A', 100, "W").unwrap()).unwrap();
+		assert_eq!(Name::try_from('a'), Ok(Name::Custom(wazir)));
+
+		// the variant's own archbishop wins over the registered letter
+		let game = Game::with_variant(Capablanca);
+		let c1 = game.board().get(Square::from_str("c1").unwrap()).piece.unwrap();
+		assert_eq!(c1.name, Name::Custom(PieceKind::archbishop()));
+		assert_eq!(game.parse_san("Ad3").unwrap().from(), Square::from_str("c1").unwrap());
+
+		let game = Game::from_variant_fen(Capablanca, "5k4/P9/10/10/10/10/10/5K4 w - - 0 1").unwrap();
+		assert_eq!(game.legal_moves().len(), 5 + 6);
+		assert_eq!(game.parse_uci("a7a8a").unwrap().promotion(), Some(Name::Custom(PieceKind::archbishop())));
+
+		// and reading letters never registers anything
+		assert!(Game::from_fen("4k3/8/8/8/8/8/8/2C1K3 w - - 0 1").is_err());
+		assert_eq!(PieceKind::from_letter('c'), None);
+	}
+
+	#[test]
+	fn test_grasshopper() {
+		let game = riders("4k3/8/8/3p4/8/3G4/4K3/8 w - - 0 1");
+
+		let grasshopper = Square::from_str("d3").unwrap();
+		let moves: Vec<Square> = game
+			.board()
+			.legal_moves(grasshopper)
+			.iter()
+			.map(|square| Square::new(square.rank(), square.file()))
+			.collect();
+
+		// it hops over the black pawn to d6, and over its own king to f1
+		assert_eq!(moves.len(), 2);
+		assert!(moves.contains(&Square::from_str("d6").unwrap()));
+		assert!(moves.contains(&Square::from_str("f1").unwrap()));
+	}
+
+	#[test]
+	fn test_fairy_pieces_give_check() {
+		// the nightrider attacks the king from a distance
+		let game = riders("8/8/8/4k3/8/8/8/K5H1 b - - 0 1");
+		assert!(game.is_check());
+		assert_eq!(game.to_fen(), "8/8/8/4k3/8/8/8/K5H1 b - - 0 1");
+
+		// the grasshopper needs a piece right in front of the king to attack it
+		let game = riders("8/8/8/4k3/4p3/8/8/K3g3 w - - 0 1");
+		assert!(!game.is_check());
+		let game = riders("8/8/8/8/8/4k3/3p4/K1G5 b - - 0 1");
+		assert!(game.is_check());
+	}
+}
@@ -0,0 +1,542 @@
+use crate::board::{Board, MAX_SIZE};
+use crate::castling::{CastlingRights, CastlingSide};
+use crate::color::Color;
+use crate::fairy::PieceKind;
+use crate::file::File;
+use crate::game::Game;
+use crate::name::Name;
+use crate::piece::Piece;
+use crate::rank::Rank;
+use crate::square::Square;
+
+use std::str::FromStr;
+
+pub const STARTING_POSITION: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+impl Board {
+	// the size of the board is taken from the placement,
+	// empty squares can be counted with more than one digit
+	pub fn from_placement(placement: &str) -> Result<Board, String> {
+		Board::read_placement(placement, &[])
+	}
+
+	// `pieces` are the fairy pieces of the variant, known by their letters
+	// even when they aren't registered
+	pub(crate) fn read_placement(placement: &str, pieces: &[PieceKind]) -> Result<Board, String> {
+		let rows: Vec<Vec<Option<Piece>>> = placement
+			.split('/')
+			.map(|row| parse_row(row, pieces))
+			.collect::<Result<_, _>>()?;
+
+		let height = rows.len();
+		let width = rows[0].len();
+
+		if height > MAX_SIZE || width > MAX_SIZE {
+			return Err("The board in the FEN is too large".to_string());
+		}
+
+		if height < 2 || width == 0 {
+			return Err("The board in the FEN is too small".to_string());
+		}
+
+		let mut board = Board::with_size(width, height);
+
+		for (i, row) in rows.into_iter().enumerate() {
+			let rank = height - 1 - i;
+
+			if row.len() != width {
+				return Err(format!("Rank {} in the FEN has the wrong length", rank + 1));
+			}
+
+			for (file, piece) in row.into_iter().enumerate() {
+				let mut piece = match piece {
+					Some(piece) => piece,
+					None => continue,
+				};
+
+				// a pawn that isn't on its starting rank must have moved,
+				// and pawns on boards shorter than the standard one never double step
+				let start_rank = match piece.color {
+					Color::White => 1,
+					Color::Black => height - 2,
+				};
+				piece.moved = piece.name == Name::Pawn && (rank != start_rank || height < 8);
+
+				board.get_mut(Square::new(Rank(rank), File(file))).piece = Some(piece);
+			}
+		}
+
+		Ok(board)
+	}
+
+	pub fn placement(&self) -> String {
+		self.write_placement(false)
+	}
+
+	// promoted pieces are marked with a '~' in Crazyhouse
+	fn write_placement(&self, promoted: bool) -> String {
+		let mut placement = String::new();
+
+		for rank in (0..self.height()).rev() {
+			let mut empty = 0;
+
+			for file in 0..self.width() {
+				match self.get(Square::new(Rank(rank), File(file))).piece {
+					Some(piece) => {
+						if empty > 0 {
+							placement.push_str(&empty.to_string());
+							empty = 0;
+						}
+
+						placement.push(fen_char(piece));
+
+						if promoted && piece.promoted {
+							placement.push('~');
+						}
+					},
+					None => empty += 1,
+				}
+			}
+
+			if empty > 0 {
+				placement.push_str(&empty.to_string());
+			}
+
+			if rank > 0 {
+				placement.push('/');
+			}
+		}
+
+		placement
+	}
+}
+
+impl Game {
+	pub fn from_fen(fen: &str) -> Result<Game, String> {
+		Game::read_fen(fen, &[])
+	}
+
+	pub(crate) fn read_fen(fen: &str, pieces: &[PieceKind]) -> Result<Game, String> {
+		let fields: Vec<&str> = fen.split_whitespace().collect();
+
+		if fields.len() < 4 {
+			return Err("A FEN needs at least four fields".to_string());
+		}
+
+		// the pieces in the pockets are written after the placement, e.g. [Qp]
+		let (placement, pockets) = match fields[0].find('[') {
+			Some(start) if fields[0].ends_with(']') => {
+				(&fields[0][..start], Some(&fields[0][start + 1..fields[0].len() - 1]))
+			},
+			Some(_) => return Err("The pockets in the FEN are missing a ']'".to_string()),
+			None => (fields[0], None),
+		};
+
+		let board = Board::read_placement(placement, pieces)?;
+
+		let turn = match fields[1] {
+			"w" => Color::White,
+			"b" => Color::Black,
+			_ => return Err(format!("Unknown color '{}' in the FEN", fields[1])),
+		};
+
+		let castling = parse_castling(&board, fields[2])?;
+
+		let en_passant = match fields[3] {
+			"-" => None,
+			square => {
+				let target =
+					Square::from_str(square).map_err(|_| format!("Invalid en passant square '{}'", square))?;
+
+				// the square a pawn of the side that just moved passed over
+				let rank = match turn {
+					Color::White => board.height() as isize - 3,
+					Color::Black => 2,
+				};
+
+				if !board.contains(target.rank.into(), target.file.into()) || isize::from(target.rank) != rank {
+					return Err(format!("Invalid en passant square '{}'", square));
+				}
+
+				Some(target)
+			},
+		};
+
+		// three-check positions have the remaining checks after the
+		// en passant square (3+3), or the checks given at the end (+0+0)
+		let mut clocks: Vec<&str> = fields[4..].to_vec();
+		let checks = match clocks.iter().position(|field| field.contains('+')) {
+			Some(index) => Some(parse_checks(clocks.remove(index))?),
+			None => None,
+		};
+
+		let halfmove_clock = match clocks.first() {
+			Some(field) => field.parse().map_err(|_| "Invalid halfmove clock in the FEN".to_string())?,
+			None => 0,
+		};
+
+		let fullmove_number = match clocks.get(1) {
+			Some(field) => field.parse().map_err(|_| "Invalid fullmove number in the FEN".to_string())?,
+			None => 1,
+		};
+
+		let mut game = Game::from_board(board, castling);
+		game.turn = turn;
+		game.en_passant = en_passant;
+		game.halfmove_clock = halfmove_clock;
+		game.fullmove_number = fullmove_number;
+		game.chess960 = !is_standard_castling(&game.board, &castling);
+		game.remaining_checks = checks.unwrap_or([3, 3]);
+
+		for c in pockets.unwrap_or("").chars() {
+			let name = Name::from_letter(c, pieces).ok_or_else(|| format!("Unknown piece '{}' in the pockets", c))?;
+			let color = if c.is_ascii_uppercase() { Color::White } else { Color::Black };
+
+			game.pockets[color.index()].add(name);
+		}
+
+		Ok(game)
+	}
+
+	// castling rights are written as KQkq when that is unambiguous,
+	// and with the files of the rooks otherwise (X-FEN)
+	pub fn to_fen(&self) -> String {
+		self.fen_with_castling(&castling_field(&self.board, &self.castling, false))
+	}
+
+	// castling rights are always written with the files of the rooks
+	pub fn to_shredder_fen(&self) -> String {
+		self.fen_with_castling(&castling_field(&self.board, &self.castling, true))
+	}
+
+	fn fen_with_castling(&self, castling: &str) -> String {
+		let turn = match self.turn {
+			Color::White => "w",
+			Color::Black => "b",
+		};
+
+		let en_passant = match self.en_passant {
+			Some(square) => square.to_string(),
+			None => "-".to_string(),
+		};
+
+		let mut placement = self.board.write_placement(self.variant.has_drops());
+
+		if self.variant.has_drops() {
+			placement.push('[');
+
+			for color in &[Color::White, Color::Black] {
+				for name in self.pockets[color.index()].names() {
+					let piece = Piece::new(*color, name);
+					let count = self.pockets[color.index()].count(name);
+					placement.extend((0..count).map(|_| fen_char(piece)));
+				}
+			}
+
+			placement.push(']');
+		}
+
+		let mut fields = vec![placement, turn.to_string(), castling.to_string(), en_passant];
+
+		if self.variant.counts_checks() {
+			fields.push(format!("{}+{}", self.remaining_checks[0], self.remaining_checks[1]));
+		}
+
+		fields.push(self.halfmove_clock.to_string());
+		fields.push(self.fullmove_number.to_string());
+
+		fields.join(" ")
+	}
+}
+
+fn parse_checks(field: &str) -> Result<[usize; 2], String> {
+	let invalid = || format!("Invalid check counter '{}' in the FEN", field);
+
+	let (given, counts) = match field.strip_prefix('+') {
+		Some(counts) => (true, counts),
+		None => (false, field),
+	};
+
+	let (white, black) = counts.split_once('+').ok_or_else(invalid)?;
+	let white: usize = white.parse().map_err(|_| invalid())?;
+	let black: usize = black.parse().map_err(|_| invalid())?;
+
+	if white > 3 || black > 3 {
+		return Err(invalid());
+	}
+
+	if given {
+		Ok([3 - white, 3 - black])
+	} else {
+		Ok([white, black])
+	}
+}
+
+fn parse_row(row: &str, pieces: &[PieceKind]) -> Result<Vec<Option<Piece>>, String> {
+	let mut squares: Vec<Option<Piece>> = Vec::new();
+	let mut empty = 0;
+
+	for c in row.chars() {
+		if let Some(digit) = c.to_digit(10) {
+			empty = empty * 10 + digit as usize;
+
+			// checked digit by digit, so a long number can't overflow
+			if squares.len() + empty > MAX_SIZE {
+				return Err("The board in the FEN is too large".to_string());
+			}
+
+			continue;
+		}
+
+		squares.extend((0..empty).map(|_| None));
+		empty = 0;
+
+		if c == '~' {
+			match squares.last_mut() {
+				Some(Some(piece)) => piece.promoted = true,
+				_ => return Err("A '~' in the FEN has to follow a piece".to_string()),
+			}
+
+			continue;
+		}
+
+		let name = Name::from_letter(c, pieces).ok_or_else(|| format!("Unknown piece '{}' in the FEN", c))?;
+		let color = if c.is_ascii_uppercase() { Color::White } else { Color::Black };
+
+		squares.push(Some(Piece::new(color, name)));
+
+		if squares.len() > MAX_SIZE {
+			return Err("The board in the FEN is too large".to_string());
+		}
+	}
+
+	squares.extend((0..empty).map(|_| None));
+
+	Ok(squares)
+}
+
+pub(crate) fn fen_char(piece: Piece) -> char {
+	let c = char::from(piece.name);
+
+	match piece.color {
+		Color::White => c,
+		Color::Black => c.to_ascii_lowercase(),
+	}
+}
+
+fn back_rank(board: &Board, color: Color) -> Rank {
+	match color {
+		Color::White => Rank(0),
+		Color::Black => Rank(board.height() - 1),
+	}
+}
+
+fn is_rook(board: &Board, color: Color, file: usize) -> bool {
+	match board.get(Square::new(back_rank(board, color), File(file))).piece {
+		Some(piece) => piece.color == color && piece.name == Name::Rook,
+		None => false,
+	}
+}
+
+// finds the rook furthest away from the king on the given side
+fn outermost_rook(board: &Board, color: Color, side: CastlingSide, king: usize) -> Option<File> {
+	match side {
+		CastlingSide::King => ((king + 1)..board.width()).rev().find(|file| is_rook(board, color, *file)),
+		CastlingSide::Queen => (0..king).find(|file| is_rook(board, color, *file)),
+	}
+	.map(File)
+}
+
+fn parse_castling(board: &Board, field: &str) -> Result<CastlingRights, String> {
+	let mut castling = CastlingRights::none();
+
+	if field == "-" {
+		return Ok(castling);
+	}
+
+	for c in field.chars() {
+		let color = if c.is_ascii_uppercase() { Color::White } else { Color::Black };
+
+		let king = match board.find_king(color) {
+			Some(king) if king.rank == back_rank(board, color) => usize::from(king.file),
+			_ => return Err(format!("Castling right '{}' without a king on the back rank", c)),
+		};
+
+		let (side, rook) = match c.to_ascii_uppercase() {
+			'K' => (CastlingSide::King, outermost_rook(board, color, CastlingSide::King, king)),
+			'Q' => (CastlingSide::Queen, outermost_rook(board, color, CastlingSide::Queen, king)),
+			letter => {
+				let file: usize = File::from_str(&letter.to_string())
+					.map_err(|_| format!("Invalid castling right '{}'", c))?
+					.into();
+
+				let side = if file > king { CastlingSide::King } else { CastlingSide::Queen };
+				let rook = if is_rook(board, color, file) { Some(File(file)) } else { None };
+
+				(side, rook)
+			},
+		};
+
+		match rook {
+			Some(file) => castling.set(color, side, Some(file)),
+			None => return Err(format!("Castling right '{}' without a rook", c)),
+		}
+	}
+
+	Ok(castling)
+}
+
+fn castling_field(board: &Board, castling: &CastlingRights, shredder: bool) -> String {
+	let mut field = String::new();
+
+	for color in &[Color::White, Color::Black] {
+		let king = match board.find_king(*color) {
+			Some(king) => usize::from(king.file),
+			None => continue,
+		};
+
+		for side in CastlingSide::all() {
+			let file = match castling.get(*color, side) {
+				Some(file) => file,
+				None => continue,
+			};
+
+			let c = if !shredder && outermost_rook(board, *color, side, king) == Some(file) {
+				match side {
+					CastlingSide::King => 'K',
+					CastlingSide::Queen => 'Q',
+				}
+			} else {
+				file.to_string().chars().next().unwrap()
+			};
+
+			field.push(match color {
+				Color::White => c,
+				Color::Black => c.to_ascii_lowercase(),
+			});
+		}
+	}
+
+	if field.is_empty() {
+		field.push('-');
+	}
+
+	field
+}
+
+// whether the castling rights could have come from the standard starting position
+fn is_standard_castling(board: &Board, castling: &CastlingRights) -> bool {
+	if castling.is_empty() {
+		return true;
+	}
+
+	[Color::White, Color::Black].iter().all(|color| {
+		let king_on_e_file = match board.find_king(*color) {
+			Some(king) => usize::from(king.file) == board.width() / 2,
+			None => false,
+		};
+
+		let corner_rooks = CastlingSide::all().iter().all(|side| match castling.get(*color, *side) {
+			Some(file) => usize::from(file) == 0 || usize::from(file) == board.width() - 1,
+			None => true,
+		});
+
+		let has_rights = CastlingSide::all().iter().any(|side| castling.get(*color, *side).is_some());
+
+		!has_rights || (king_on_e_file && corner_rooks)
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_starting_position_round_trip() {
+		let game = Game::from_fen(STARTING_POSITION).unwrap();
+
+		assert_eq!(game.to_fen(), STARTING_POSITION);
+		assert_eq!(Game::new().to_fen(), STARTING_POSITION);
+		assert!(!game.is_chess960());
+	}
+
+	#[test]
+	fn test_shredder_fen() {
+		let game = Game::new();
+
+		assert_eq!(
+			game.to_shredder_fen(),
+			"rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w HAha - 0 1"
+		);
+	}
+
+	#[test]
+	fn test_x_fen_inner_rook() {
+		// the white king has two rooks on its king side,
+		// castling with the inner one has to be written with its file
+		let fen = "rk2r3/8/8/8/8/8/8/1K1R2R1 w Da - 0 1";
+		let game = Game::from_fen(fen).unwrap();
+
+		assert_eq!(game.castling_rights().get(Color::White, CastlingSide::King), Some(File(3)));
+		assert_eq!(game.castling_rights().get(Color::Black, CastlingSide::Queen), Some(File(0)));
+		assert_eq!(game.to_fen(), "rk2r3/8/8/8/8/8/8/1K1R2R1 w Dq - 0 1");
+		assert!(game.is_chess960());
+	}
+
+	#[test]
+	fn test_invalid_fen() {
+		assert!(Game::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBN w KQkq -").is_err());
+		assert!(Game::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNX w KQkq -").is_err());
+		assert!(Game::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq -").is_err());
+
+		// runs of empty squares that are far too wide, with and without a piece after them
+		assert!(Game::from_fen("4k3/8/8/8/8/8/8/99999999999999999999999 w - - 0 1").is_err());
+		assert!(Game::from_fen("4k3/8/8/8/8/8/8/99999999999K w - - 0 1").is_err());
+		assert!(Game::from_fen("4k3/8/8/8/8/8/8/33 w - - 0 1").is_err());
+
+		// en passant squares off the board or on the wrong rank
+		assert!(Game::from_fen("4k3/8/8/7P/8/8/8/4K3 w - i6 0 1").is_err());
+		assert!(Game::from_fen("4k3/8/8/4pP2/8/8/8/4K3 w - e9 0 1").is_err());
+		assert!(Game::from_fen("4k3/8/8/4pP2/8/8/8/4K3 w - e3 0 1").is_err());
+		assert!(Game::from_fen("4k3/8/8/8/8/8/8/4K3 b - e1 0 1").is_err());
+		assert!(Game::from_fen("4k3/8/8/8/4Pp2/8/8/4K3 b - e6 0 1").is_err());
+		assert!(Game::from_fen("4k3/8/8/4pP2/8/8/8/4K3 w - e6 0 1").is_ok());
+		assert!(Game::from_fen("4k3/8/8/8/4Pp2/8/8/4K3 b - e3 0 1").is_ok());
+	}
+
+	#[test]
+	fn test_board_size_from_placement() {
+		let fen = "rnbqkbnrrr/pppppppppp/10/10/10/10/10/10/PPPPPPPPPP/RNBQKBNRRR w - - 0 1";
+		let game = Game::from_fen(fen).unwrap();
+
+		assert_eq!(game.board().width(), 10);
+		assert_eq!(game.board().height(), 10);
+		assert_eq!(game.to_fen(), fen);
+	}
+
+	#[test]
+	fn test_perft_kiwipete() {
+		let game =
+			Game::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+
+		assert_eq!(game.perft(1), 48);
+		assert_eq!(game.perft(2), 2039);
+	}
+
+	#[test]
+	fn test_perft_promotions_and_checks() {
+		let game = Game::from_fen("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1").unwrap();
+
+		assert_eq!(game.perft(1), 6);
+		assert_eq!(game.perft(2), 264);
+	}
+
+	#[test]
+	fn test_perft_en_passant_pins() {
+		let game = Game::from_fen("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1").unwrap();
+
+		assert_eq!(game.perft(1), 14);
+		assert_eq!(game.perft(2), 191);
+		assert_eq!(game.perft(3), 2812);
+	}
+}
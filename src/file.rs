@@ -1,13 +1,17 @@
+use crate::board::MAX_SIZE;
+
 use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
 
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Copy, Clone, PartialEq, Debug)]
 pub struct File(pub usize);
 
 impl TryFrom<usize> for File {
 	type Error = ();
 
 	fn try_from(value: usize) -> Result<Self, Self::Error> {
-		if value >= 0 && value < 8 {
+		if value < MAX_SIZE {
 			return Ok(File(value));
 		}
 		Err(())
@@ -22,6 +26,8 @@ impl TryFrom<isize> for File {
 	}
 }
 
+// the single character notation only covers the files of a standard board,
+// use the string notation for larger boards
 impl TryFrom<char> for File {
 	type Error = ();
 
@@ -68,6 +74,40 @@ impl From<File> for char {
 	}
 }
 
+// files are named like spreadsheet columns, A to Z followed by AA, AB and so on
+impl FromStr for File {
+	type Err = ();
+
+	fn from_str(value: &str) -> Result<Self, Self::Err> {
+		if value.is_empty() || value.len() > 2 {
+			return Err(());
+		}
+
+		let mut number = 0;
+		for c in value.chars() {
+			if !c.is_ascii_alphabetic() {
+				return Err(());
+			}
+
+			number = number * 26 + (c.to_ascii_uppercase() as usize - 'A' as usize + 1);
+		}
+
+		File::try_from(number - 1)
+	}
+}
+
+impl fmt::Display for File {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let letter = |n: usize| (b'A' + n as u8) as char;
+
+		if self.0 < 26 {
+			write!(f, "{}", letter(self.0))
+		} else {
+			write!(f, "{}{}", letter(self.0 / 26 - 1), letter(self.0 % 26))
+		}
+	}
+}
+
 impl PartialEq<usize> for File {
 	fn eq(&self, other: &usize) -> bool {
 		let File(num) = self;
@@ -97,6 +137,16 @@ mod tests {
 		assert!(from_char.is_err());
 	}
 
+	#[test]
+	fn test_from_str_multiple_letters() {
+		assert_eq!(File::from_str("J"), Ok(File(9)));
+		assert_eq!(File::from_str("j"), Ok(File(9)));
+		assert_eq!(File::from_str("AB"), Ok(File(27)));
+		assert_eq!(File(27).to_string(), "AB");
+		assert_eq!(File(25).to_string(), "Z");
+		assert!(File::from_str("A1").is_err());
+	}
+
 	#[test]
 	fn test_from_char_lowercase_invalid() {
 		let from_char = File::try_from('a');
@@ -1,29 +1,116 @@
 use crate::board::Board;
+use crate::castling::{CastlingRights, CastlingSide};
 use crate::color::Color;
+use crate::file::File;
+use crate::moves::{Move, MoveKind};
 use crate::name::Name;
 use crate::piece::Piece;
-use crate::square::Square;
+use crate::pocket::Pocket;
 use crate::rank::Rank;
-use crate::file::File;
-use std::convert::TryFrom;
+use crate::square::Square;
+use crate::variant::{Standard, Variant};
 
+use std::sync::Arc;
+
+#[derive(Clone)]
 pub struct Game {
-	board: Board,
-	turn: Color,
-	moved_last: Option<Piece>,
+	pub(crate) board: Board,
+	pub(crate) turn: Color,
+	pub(crate) moved_last: Option<Piece>,
+	pub(crate) last_move: Option<Move>,
+	pub(crate) castling: CastlingRights,
+	pub(crate) en_passant: Option<Square>,
+	pub(crate) halfmove_clock: usize,
+	pub(crate) fullmove_number: usize,
+	pub(crate) chess960: bool,
+	pub(crate) variant: Arc<dyn Variant>,
+	pub(crate) pockets: [Pocket; 2],
+	// the checks each side still has to give to win in three-check
+	pub(crate) remaining_checks: [usize; 2],
+	// the FEN before the first move that was made, and the moves made since.
+	// Moves that are only tried out, e.g. by perft, aren't recorded
+	pub(crate) start_fen: Option<String>,
+	pub(crate) history: Vec<Move>,
+}
+
+impl Default for Game {
+	fn default() -> Self {
+		Game::new()
+	}
 }
 
 impl Game {
 	pub fn new() -> Game {
-		let board = Board::new();
+		Game::from_board(Board::new(), CastlingRights::standard(8))
+	}
 
+	pub fn from_board(board: Board, castling: CastlingRights) -> Game {
 		Game {
-			board: board,
+			board,
 			turn: Color::White,
 			moved_last: None,
+			last_move: None,
+			castling,
+			en_passant: None,
+			halfmove_clock: 0,
+			fullmove_number: 1,
+			chess960: false,
+			variant: Arc::new(Standard),
+			pockets: [Pocket::new(), Pocket::new()],
+			remaining_checks: [3, 3],
+			start_fen: None,
+			history: Vec::new(),
+		}
+	}
+
+	pub fn board(&self) -> &Board {
+		&self.board
+	}
+
+	pub fn turn(&self) -> Color {
+		self.turn
+	}
+
+	pub fn moved_last(&self) -> Option<Piece> {
+		self.moved_last
+	}
+
+	pub fn last_move(&self) -> Option<Move> {
+		self.last_move
+	}
+
+	pub fn castling_rights(&self) -> CastlingRights {
+		self.castling
+	}
+
+	pub fn en_passant_square(&self) -> Option<Square> {
+		self.en_passant
+	}
+
+	pub fn halfmove_clock(&self) -> usize {
+		self.halfmove_clock
+	}
+
+	pub fn fullmove_number(&self) -> usize {
+		self.fullmove_number
+	}
+
+	pub fn is_chess960(&self) -> bool {
+		self.chess960
+	}
+
+	// the position the game was played from
+	pub fn start_fen(&self) -> String {
+		match &self.start_fen {
+			Some(fen) => fen.clone(),
+			None => self.to_fen(),
 		}
 	}
 
+	pub fn moves_played(&self) -> &[Move] {
+		&self.history
+	}
+
 	pub fn print_board(&self) {
 		self.board.print();
 	}
@@ -33,6 +120,9 @@ impl Game {
 		from_square: Square,
 		to_square: Square,
 	) -> Result<(), String> {
+		let from_square = Square::new(from_square.rank, from_square.file);
+		let to_square = Square::new(to_square.rank, to_square.file);
+
 		let from_rank: usize = from_square.rank.into();
 		let from_file: usize = from_square.file.into();
 
@@ -47,11 +137,24 @@ impl Game {
 			return Err("You can't move the opponent's piece".to_string());
 		}
 
-		// check that the piece is allowed to go there
+		// pawns reaching the last rank are promoted to queens,
+		// use make_move to promote to anything else
+		let found = self.find_move(&self.legal_moves(), from_square, to_square);
+		if let Some(mv) = found {
+			self.record(mv);
+			return Ok(());
+		}
+
+		let allowed: Vec<Move> = self.pseudo_legal_moves().into_iter().filter(|mv| self.is_legal(*mv)).collect();
+		if self.find_move(&allowed, from_square, to_square).is_some() {
+			return Err("That move isn't allowed in this variant".to_string());
+		}
+
+		// find out why the piece isn't allowed to go there
 		match self.board.get(from_square).piece.unwrap().name {
 			Name::King => {
-				if (from_rank as isize - to_rank as isize).abs() != 1 &&
-					(from_file as isize - to_file as isize).abs() != 1
+				if (from_rank as isize - to_rank as isize).abs() > 1 ||
+					(from_file as isize - to_file as isize).abs() > 1
 				{
 					return Err("The king cannot move to that position".to_string());
 				}
@@ -110,34 +213,36 @@ impl Game {
 					}
 				} else {
 					if (from_rank as isize - to_rank as isize).abs() != 1 &&
-						!((from_rank as isize - to_rank as isize).abs() == 2 &&
-							self.board.get(from_square).piece.unwrap().moved == false)
+						((from_rank as isize - to_rank as isize).abs() != 2 ||
+							self.board.get(from_square).piece.unwrap().moved)
 					{
 						return Err("The pawn cannot move to that position".to_string());
 					}
 				}
 			},
+			// custom pieces can move in too many ways to explain
+			Name::Custom(_) => (),
 		}
 
-		if !self.board.get(to_square).is_empty() {
-			if self.board.get(to_square).piece.unwrap().color == self.turn {
-				return Err("You can't take your own piece".to_string());
-			} else {
-				// take the opponent's piece
-				self.board.get_mut(to_square).piece = None;
-			}
+		if !self.board.get(to_square).is_empty() &&
+			self.board.get(to_square).piece.unwrap().color == self.turn
+		{
+			return Err("You can't take your own piece".to_string());
 		}
 
-		self.board.move_piece(from_square, to_square);
+		if self.find_move(&self.pseudo_legal_moves(), from_square, to_square).is_some() {
+			let pinned = self.pins().into_iter().any(|pin| {
+				pin.is_absolute() && pin.pinned().rank == from_square.rank && pin.pinned().file == from_square.file
+			});
 
-		self.moved_last = self.board.get(to_square).piece;
+			if pinned && !self.is_check() {
+				return Err("The piece is pinned to the king".to_string());
+			}
 
-		self.turn = match self.turn {
-			Color::White => Color::Black,
-			Color::Black => Color::White,
-		};
+			return Err("That move would leave your king in check".to_string());
+		}
 
-		Ok(())
+		Err("The piece is blocked from moving to that position".to_string())
 	}
 
 	pub fn en_passant(
@@ -145,13 +250,10 @@ impl Game {
 		from_square: Square,
 		to_square: Square,
 	) -> Result<(), String> {
-		let from_rank: usize = from_square.rank.into();
-		let from_file: usize = from_square.file.into();
-
-		let to_rank: usize = to_square.rank.into();
-		let to_file: usize = to_square.file.into();
-
-		let pawn = self.board.get(from_square).piece.unwrap();
+		let pawn = match self.board.get(from_square).piece {
+			Some(piece) => piece,
+			None => return Err("There is no piece to move".to_string()),
+		};
 
 		if pawn.name != Name::Pawn {
 			return Err("Only pawns are allowed to perform en passant".to_string());
@@ -161,69 +263,487 @@ impl Game {
 			return Err("You can't move the opponent's piece".to_string());
 		}
 
-		if (from_rank as isize - to_rank as isize).abs() != 1 {
-			return Err("Not a valid en passant".to_string());
+		let from_square = Square::new(from_square.rank, from_square.file);
+		let to_square = Square::new(to_square.rank, to_square.file);
+
+		let found = self
+			.legal_moves()
+			.into_iter()
+			.find(|mv| mv.is_en_passant() && mv.from == from_square && mv.to == to_square);
+
+		match found {
+			Some(mv) => {
+				self.record(mv);
+				Ok(())
+			},
+			None => Err("Not a valid en passant".to_string()),
 		}
+	}
 
-		let captured;
+	pub fn make_move(&mut self, mv: Move) -> Result<(), String> {
+		if !self.legal_moves().contains(&mv) {
+			return Err(format!("{:?} is not a legal move", mv));
+		}
 
-		if pawn.color == Color::White {
-			if (from_rank as isize - to_rank as isize) > 0 {
-				return Err("Not a valid en passant".to_string());
-			}
+		self.record(mv);
+		Ok(())
+	}
+
+	pub fn legal_moves(&self) -> Vec<Move> {
+		if self.is_variant_end() {
+			return Vec::new();
+		}
+
+		let pinned = self.absolute_pins();
+		let moves: Vec<Move> = self
+			.pseudo_legal_moves()
+			.into_iter()
+			.filter(|mv| pinned.as_ref().is_some_and(|pinned| self.is_safe_move(*mv, pinned)) || self.is_legal(*mv))
+			.collect();
+
+		self.variant.filter_moves(self, moves)
+	}
+
+	pub fn is_capture(&self, mv: Move) -> bool {
+		match mv.kind {
+			MoveKind::Normal => !self.board.get(mv.to).is_empty(),
+			MoveKind::EnPassant => true,
+			MoveKind::Castle(_) | MoveKind::Drop(_) => false,
+		}
+	}
+
+	pub fn is_check(&self) -> bool {
+		self.variant.is_in_check(&self.board, self.turn)
+	}
+
+	pub fn is_checkmate(&self) -> bool {
+		self.is_check() && self.legal_moves().is_empty()
+	}
+
+	pub fn is_stalemate(&self) -> bool {
+		!self.is_check() && self.legal_moves().is_empty()
+	}
+
+	// counts the leaf nodes of the move tree, used to verify move generation
+	pub fn perft(&self, depth: usize) -> usize {
+		if depth == 0 {
+			return 1;
+		}
+
+		let moves = self.legal_moves();
+		if depth == 1 {
+			return moves.len();
+		}
 
-			if (from_file as isize - to_file as isize) == 1 {
-				// move to the right
-				captured = self.board.get(Square::new(Rank::try_from(from_rank).unwrap(), File::try_from(from_file - 1).unwrap())).clone();
-			} else if (from_file as isize - to_file as isize) == -1 {
-				// move to the left
-				captured = self.board.get(Square::new(Rank::try_from(from_rank).unwrap(), File::try_from(from_file + 1).unwrap())).clone();
-			} else {
-				return Err("Not a valid en passant".to_string());
+		let mut nodes = 0;
+		for mv in moves {
+			let mut game = self.clone();
+			game.play(mv);
+			nodes += game.perft(depth - 1);
+		}
+
+		nodes
+	}
+
+	// the square the king ends up on, which for castling
+	// isn't the square the move goes to
+	pub fn king_destination(&self, mv: Move) -> Square {
+		match mv.kind {
+			MoveKind::Castle(side) => Square::new(mv.from.rank, side.king_to(self.board.width())),
+			_ => mv.to,
+		}
+	}
+
+	fn find_move(&self, moves: &[Move], from_square: Square, to_square: Square) -> Option<Move> {
+		moves
+			.iter()
+			.filter(|mv| mv.from == from_square)
+			.filter(|mv| mv.to == to_square || (mv.is_castle() && self.king_destination(**mv) == to_square))
+			.find(|mv| mv.promotion.is_none() || mv.promotion == Some(Name::Queen))
+			.copied()
+	}
+
+	pub(crate) fn pseudo_legal_moves(&self) -> Vec<Move> {
+		self.variant.pseudo_legal_moves(self)
+	}
+
+	// the moves of the pieces on the board, without castling
+	pub fn piece_moves(&self) -> Vec<Move> {
+		let mut moves = Vec::new();
+		let last_rank = match self.turn {
+			Color::White => self.board.height() - 1,
+			Color::Black => 0,
+		};
+
+		for square in self.board.squares() {
+			let piece = match square.piece {
+				Some(piece) if piece.color == self.turn => piece,
+				_ => continue,
+			};
+
+			for target in self.board.legal_moves(*square) {
+				if piece.name == Name::Pawn && target.rank == last_rank {
+					for name in self.variant.promotions() {
+						moves.push(Move::new(*square, target).promote(name));
+					}
+				} else {
+					moves.push(Move::new(*square, target));
+				}
 			}
-		} else {
-			if (from_rank as isize - to_rank as isize) < 0 {
-				return Err("Not a valid en passant".to_string());
+
+			if piece.name == Name::Pawn {
+				if let Some(target) = self.en_passant {
+					let forward = match self.turn {
+						Color::White => 1,
+						Color::Black => -1,
+					};
+
+					if isize::from(target.rank) - isize::from(square.rank) == forward &&
+						(isize::from(target.file) - isize::from(square.file)).abs() == 1
+					{
+						moves.push(Move::with_kind(*square, target, MoveKind::EnPassant));
+					}
+				}
 			}
+		}
 
-			if (from_file as isize - to_file as isize) == 1 {
-				// move to the left
-				captured = self.board.get(Square::new(Rank::try_from(from_rank).unwrap(), File::try_from(from_file + 1).unwrap())).clone();
-			} else if (from_file as isize - to_file as isize) == -1 {
-				// move to the right
-				captured = self.board.get(Square::new(Rank::try_from(from_rank).unwrap(), File::try_from(from_file + 1).unwrap())).clone();
-			} else {
-				return Err("Not a valid en passant".to_string());
+		moves
+	}
+
+	// pawns can't be dropped on the first or the last rank
+	pub fn drop_moves(&self) -> Vec<Move> {
+		let mut moves = Vec::new();
+		let last_rank = self.board.height() - 1;
+
+		for name in self.pockets[self.turn.index()].names() {
+			for square in self.board.squares().filter(|square| square.is_empty()) {
+				if name == Name::Pawn && (square.rank == 0 || square.rank == last_rank) {
+					continue;
+				}
+
+				moves.push(Move::with_kind(*square, *square, MoveKind::Drop(name)));
 			}
 		}
 
-		if captured.is_empty() {
-			return Err("Not a valid en passant".to_string());
-		} else if captured.piece.unwrap() != self.moved_last.unwrap() {
-			return Err("Not a valid en passant".to_string());
-		} else {
-			if captured.piece.unwrap().color == self.turn ||
-				captured.piece.unwrap().name != Name::Pawn
-			{
-				return Err("Not a valid en passant".to_string());
+		moves
+	}
+
+	pub fn castling_moves(&self) -> Vec<Move> {
+		let mut moves = Vec::new();
+
+		let king = match self.board.find_king(self.turn) {
+			Some(king) => king,
+			_ => return moves,
+		};
+
+		let back_rank = match self.turn {
+			Color::White => 0,
+			Color::Black => self.board.height() - 1,
+		};
+
+		if king.rank != back_rank || self.variant.is_attacked(&self.board, king, self.turn) {
+			return moves;
+		}
+
+		for side in CastlingSide::all() {
+			let rook_file = match self.castling.get(self.turn, side) {
+				Some(file) => file,
+				None => continue,
+			};
+
+			let rook = *self.board.get(Square::new(king.rank, rook_file));
+			match rook.piece {
+				Some(piece) if piece.color == self.turn && piece.name == Name::Rook => (),
+				_ => continue,
+			}
+
+			let king_from: usize = king.file.into();
+			let king_to: usize = side.king_to(self.board.width()).into();
+			let rook_from: usize = rook_file.into();
+			let rook_to: usize = side.rook_to(self.board.width()).into();
+
+			// every square the king and the rook pass or land on
+			// has to be empty, apart from the king and the rook themselves
+			let lowest = *[king_from, king_to, rook_from, rook_to].iter().min().unwrap();
+			let highest = *[king_from, king_to, rook_from, rook_to].iter().max().unwrap();
+
+			let blocked = (lowest..=highest).any(|file| {
+				file != king_from &&
+					file != rook_from &&
+					!self.board.get(Square::new(king.rank, File(file))).is_empty()
+			});
+
+			if blocked {
+				continue;
+			}
+
+			// the king may not pass through check, where it lands is checked
+			// by the legality test. The king is taken off the board so that it
+			// doesn't hide the squares behind it (which matters in atomic chess,
+			// where the king itself isn't in check next to the other king)
+			let mut without_king = self.board.clone();
+			without_king.capture_piece(king);
+
+			let attacked = (king_from.min(king_to)..=king_from.max(king_to))
+				.filter(|file| *file == king_from || *file != king_to)
+				.any(|file| self.variant.is_attacked(&without_king, Square::new(king.rank, File(file)), self.turn));
+
+			if attacked {
+				continue;
 			}
+
+			moves.push(Move::with_kind(king, rook, MoveKind::Castle(side)));
+		}
+
+		moves
+	}
+
+	fn is_legal(&self, mv: Move) -> bool {
+		let mut board = self.board.clone();
+		Game::apply(&mut board, mv, self.turn, &*self.variant);
+
+		self.variant.is_legal(self, mv, &board)
+	}
+
+	// moves the pieces on the board, without updating any other state
+	pub(crate) fn apply(board: &mut Board, mv: Move, color: Color, variant: &dyn Variant) {
+		let capture = match mv.kind {
+			MoveKind::Normal => !board.get(mv.to).is_empty(),
+			MoveKind::EnPassant => true,
+			MoveKind::Castle(_) | MoveKind::Drop(_) => false,
+		};
+
+		match mv.kind {
+			MoveKind::Normal => {
+				board.capture_piece(mv.to);
+				board.move_piece(mv.from, mv.to);
+			},
+			MoveKind::EnPassant => {
+				board.move_piece(mv.from, mv.to);
+				board.capture_piece(Square::new(mv.from.rank, mv.to.file));
+			},
+			MoveKind::Castle(side) => {
+				let king = board.get(mv.from).piece;
+				let rook = board.get(mv.to).piece;
+
+				board.capture_piece(mv.from);
+				board.capture_piece(mv.to);
+
+				let rank = mv.from.rank;
+				let width = board.width();
+
+				board.get_mut(Square::new(rank, side.king_to(width))).piece = king;
+				board.get_mut(Square::new(rank, side.rook_to(width))).piece = rook;
+			},
+			MoveKind::Drop(name) => {
+				// a pawn dropped on its starting rank can still double step
+				let start_rank = match color {
+					Color::White => 1,
+					Color::Black => board.height() - 2,
+				};
+
+				let mut piece = Piece::new(color, name);
+				piece.moved = name == Name::Pawn && (mv.to.rank != start_rank || board.height() < 8);
+				board.get_mut(mv.to).piece = Some(piece);
+			},
+		}
+
+		if let Some(name) = mv.promotion {
+			let square = board.get_mut(mv.to);
+			let color = square.piece.unwrap().color;
+			let mut piece = Piece::new(color, name);
+			piece.moved = true;
+			piece.promoted = true;
+			square.piece = Some(piece);
 		}
 
-		// there is no need to check that the square that the pawn is moving to
-		// is empty, since the captured pawn wouldn't have been able to move
-		// if it wasn't
+		variant.after_apply(board, mv, color, capture);
+	}
 
-		self.board.move_piece(from_square, to_square);
+	// plays a move the players made, so that the game can be written as its moves
+	pub(crate) fn record(&mut self, mv: Move) {
+		if self.start_fen.is_none() {
+			self.start_fen = Some(self.to_fen());
+		}
 
-		self.board.capture_piece(captured);
+		self.history.push(mv);
+		self.play(mv);
+	}
 
-		self.moved_last = self.board.get(to_square).piece;
+	// the position was changed by hand, the moves so far don't lead to it any more
+	pub(crate) fn forget_moves(&mut self) {
+		self.start_fen = None;
+		self.history.clear();
+	}
 
-		self.turn = match self.turn {
-			Color::White => Color::Black,
-			Color::Black => Color::White,
+	pub(crate) fn play(&mut self, mv: Move) {
+		let piece = match mv.kind {
+			MoveKind::Drop(name) => Piece::new(self.turn, name),
+			_ => self.board.get(mv.from).piece.unwrap(),
 		};
+		let capture = !self.board.get(mv.to).is_empty() && !mv.is_castle();
 
-		Ok(())
+		let captured = self.captured(mv);
+		let variant = self.variant.clone();
+
+		if let MoveKind::Drop(name) = mv.kind {
+			self.pockets[self.turn.index()].remove(name);
+		}
+
+		Game::apply(&mut self.board, mv, self.turn, &*variant);
+
+		if piece.name == Name::King {
+			self.castling.clear(self.turn);
+		}
+
+		// moving a castling rook or capturing it (or blowing it up) loses that right
+		for color in &[Color::White, Color::Black] {
+			let back_rank = match color {
+				Color::White => 0,
+				Color::Black => self.board.height() - 1,
+			};
+
+			for side in CastlingSide::all() {
+				if let Some(file) = self.castling.get(*color, side) {
+					let rook = Square::new(Rank(back_rank), file);
+					let still_there = match self.board.get(rook).piece {
+						Some(piece) => piece.color == *color && piece.name == Name::Rook,
+						None => false,
+					};
+
+					if rook == mv.from || rook == mv.to || !still_there {
+						self.castling.set(*color, side, None);
+					}
+				}
+			}
+		}
+
+		self.en_passant = None;
+		if piece.name == Name::Pawn {
+			let from_rank: isize = mv.from.rank.into();
+			let to_rank: isize = mv.to.rank.into();
+
+			// horde pawns double stepping from the first rank can't be taken en passant
+			let start_rank = match self.turn {
+				Color::White => 1,
+				Color::Black => self.board.height() as isize - 2,
+			};
+
+			if (from_rank - to_rank).abs() == 2 && from_rank == start_rank {
+				let rank = Rank(((from_rank + to_rank) / 2) as usize);
+				self.en_passant = Some(Square::new(rank, mv.from.file));
+			}
+		}
+
+		if piece.name == Name::Pawn || capture || mv.is_en_passant() {
+			self.halfmove_clock = 0;
+		} else {
+			self.halfmove_clock += 1;
+		}
+
+		if self.turn == Color::Black {
+			self.fullmove_number += 1;
+		}
+
+		self.moved_last = self.board.get(self.king_destination(mv)).piece;
+		self.last_move = Some(mv);
+		self.turn = self.turn.opposite();
+
+		variant.after_play(self, mv, captured);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_perft_initial_position() {
+		let game = Game::new();
+
+		assert_eq!(game.perft(1), 20);
+		assert_eq!(game.perft(2), 400);
+		assert_eq!(game.perft(3), 8902);
+	}
+
+	#[test]
+	fn test_perft_gardner_minichess() {
+		let game = Game::from_fen("rnbqk/ppppp/5/PPPPP/RNBQK w - - 0 1").unwrap();
+
+		// the pawns meet on the third rank, where they can be captured
+		assert_eq!(game.perft(1), 7);
+		assert_eq!(game.perft(2), 53);
+	}
+
+	#[test]
+	fn test_large_board() {
+		let mut game = Game::from_board(Board::with_size(10, 10), CastlingRights::none());
+		let j10 = Square::from_str("J10").unwrap();
+		let a1 = Square::from_str("A1").unwrap();
+
+		game.board.get_mut(a1).piece = Some(Piece::new(Color::White, Name::Queen));
+		game.board.get_mut(j10).piece = Some(Piece::new(Color::Black, Name::King));
+
+		// 9 squares each along the rank, the file and the diagonal
+		assert_eq!(game.legal_moves().len(), 27);
+		assert!(game.move_piece(a1, j10).is_ok());
+	}
+
+	#[test]
+	fn test_move_piece() {
+		let mut game = Game::new();
+
+		let e2 = Square::from_str("E2").unwrap();
+		let e4 = Square::from_str("E4").unwrap();
+		let e5 = Square::from_str("E5").unwrap();
+
+		assert!(game.move_piece(e2, e4).is_ok());
+		assert_eq!(game.en_passant_square(), Square::from_str("E3").ok());
+		assert!(game.move_piece(e4, e5).is_err());
+		assert_eq!(game.turn(), Color::Black);
+	}
+
+	#[test]
+	fn test_en_passant() {
+		let mut game = Game::new();
+
+		for (from, to) in &[("E2", "E4"), ("A7", "A6"), ("E4", "E5"), ("D7", "D5")] {
+			let from = Square::from_str(from).unwrap();
+			let to = Square::from_str(to).unwrap();
+			game.move_piece(from, to).unwrap();
+		}
+
+		let e5 = Square::from_str("E5").unwrap();
+		let d6 = Square::from_str("D6").unwrap();
+		let d5 = Square::from_str("D5").unwrap();
+
+		assert!(game.en_passant(e5, d6).is_ok());
+		assert!(game.board().get(d5).is_empty());
+	}
+
+	#[test]
+	fn test_castle_by_moving_king_two_squares() {
+		let mut game = Game::new();
+
+		for (from, to) in &[
+			("E2", "E4"),
+			("E7", "E5"),
+			("G1", "F3"),
+			("B8", "C6"),
+			("F1", "C4"),
+			("G8", "F6"),
+			("E1", "G1"),
+		] {
+			let from = Square::from_str(from).unwrap();
+			let to = Square::from_str(to).unwrap();
+			game.move_piece(from, to).unwrap();
+		}
+
+		let g1 = *game.board().get(Square::from_str("G1").unwrap());
+		let f1 = *game.board().get(Square::from_str("F1").unwrap());
+
+		assert_eq!(g1.piece.unwrap().name, Name::King);
+		assert_eq!(f1.piece.unwrap().name, Name::Rook);
+		assert_eq!(game.castling_rights().get(Color::White, CastlingSide::King), None);
 	}
 }
@@ -0,0 +1,93 @@
+use crate::board::Board;
+use crate::color::Color;
+use crate::file::File;
+use crate::game::Game;
+use crate::moves::Move;
+use crate::name::Name;
+use crate::rank::Rank;
+use crate::square::Square;
+use crate::variant::{Outcome, Variant};
+
+// 36 white pawns against the usual black army
+pub struct Horde;
+
+impl Variant for Horde {
+	fn name(&self) -> &str {
+		"horde"
+	}
+
+	fn standard_legality(&self) -> bool {
+		true
+	}
+
+	fn is_symmetric(&self) -> bool {
+		true
+	}
+
+	fn starting_fen(&self) -> &str {
+		"rnbqkbnr/pppppppp/8/1PP2PP1/PPPPPPPP/PPPPPPPP/PPPPPPPP/PPPPPPPP w kq - 0 1"
+	}
+
+	// the white pawns on the first rank can still double step
+	fn setup(&self, game: &mut Game) {
+		for file in 0..game.board.width() {
+			unmove_pawn(&mut game.board, Square::new(Rank(0), File(file)));
+		}
+	}
+
+	// and so can a pawn that steps up from the first rank
+	fn after_apply(&self, board: &mut Board, mv: Move, _color: Color, _capture: bool) {
+		if mv.to.rank == 1 {
+			unmove_pawn(board, mv.to);
+		}
+	}
+
+	fn outcome(&self, game: &Game) -> Option<Outcome> {
+		if game.board().squares().any(|square| square.piece.is_some_and(|piece| piece.color == Color::White)) {
+			None
+		} else {
+			Some(Outcome::Win(Color::Black))
+		}
+	}
+
+	// black can take every pawn with any piece, even the king
+	fn has_mating_material(&self, game: &Game, color: Color) -> bool {
+		color == Color::Black || game.has_standard_mating_material(color)
+	}
+}
+
+fn unmove_pawn(board: &mut Board, square: Square) {
+	if let Some(piece) = board.get_mut(square).piece.as_mut() {
+		if piece.color == Color::White && piece.name == Name::Pawn {
+			piece.moved = false;
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_horde() {
+		let game = Game::with_variant(Horde);
+		assert_eq!(game.perft(1), 8);
+		assert_eq!(game.perft(2), 128);
+		assert_eq!(game.perft(3), 1274);
+
+		let game =
+			Game::from_variant_fen(Horde, "k7/5p2/4p2P/3p2P1/2p2P2/1p2P2P/p2P2P1/2P2P2 w - - 0 1").unwrap();
+		assert_eq!(game.perft(1), 13);
+		assert_eq!(game.perft(2), 172);
+
+		// pawns on the first rank can double step, but not be taken en passant
+		let mut game = game;
+		game.make_move(game.parse_uci("c1c3").unwrap()).unwrap();
+		assert_eq!(game.en_passant_square(), None);
+
+		// black wins by capturing every white piece
+		let mut game = Game::from_variant_fen(Horde, "4k3/8/8/8/8/8/r7/P7 b - - 0 1").unwrap();
+		game.make_move(game.parse_uci("a2a1").unwrap()).unwrap();
+		assert_eq!(game.outcome(), Some(Outcome::Win(Color::Black)));
+	}
+}
@@ -0,0 +1,59 @@
+use crate::board::Board;
+use crate::color::Color;
+use crate::game::Game;
+use crate::square::Square;
+use crate::variant::{Outcome, Variant};
+
+// bringing the king to one of the four center squares wins
+pub struct KingOfTheHill;
+
+impl Variant for KingOfTheHill {
+	fn name(&self) -> &str {
+		"kingofthehill"
+	}
+
+	fn standard_legality(&self) -> bool {
+		true
+	}
+
+	fn outcome(&self, game: &Game) -> Option<Outcome> {
+		let board = game.board();
+
+		[Color::White, Color::Black]
+			.iter()
+			.find(|color| board.find_king(**color).is_some_and(|king| is_on_hill(board, king)))
+			.map(|color| Outcome::Win(*color))
+	}
+
+	// even a lone king can walk to the hill
+	fn has_mating_material(&self, game: &Game, color: Color) -> bool {
+		game.board().find_king(color).is_some() || game.has_standard_mating_material(color)
+	}
+}
+
+// the four squares in the middle of the board
+fn is_on_hill(board: &Board, square: Square) -> bool {
+	let rank = usize::from(square.rank);
+	let file = usize::from(square.file);
+	let middle = |size: usize, n: usize| n + 1 == size / 2 || n == size / 2;
+
+	middle(board.height(), rank) && middle(board.width(), file)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_king_of_the_hill() {
+		let mut game = Game::from_variant_fen(KingOfTheHill, "4k3/8/8/8/8/8/3K4/8 w - - 0 1").unwrap();
+
+		game.make_move(game.parse_uci("d2e3").unwrap()).unwrap();
+		game.make_move(game.parse_uci("e8d7").unwrap()).unwrap();
+		assert_eq!(game.outcome(), None);
+
+		game.make_move(game.parse_uci("e3e4").unwrap()).unwrap();
+		assert_eq!(game.outcome(), Some(Outcome::Win(Color::White)));
+		assert!(game.legal_moves().is_empty());
+	}
+}
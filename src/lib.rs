@@ -1,9 +1,45 @@
-mod board;
-mod color;
-mod direction;
-mod file;
+pub mod antichess;
+pub mod arena;
+pub mod atomic;
+pub mod attacks;
+pub mod board;
+pub mod castling;
+pub mod chess960;
+pub mod clock;
+pub mod collection;
+pub mod color;
+pub mod crazyhouse;
+pub mod direction;
+pub mod engine;
+pub mod fairy;
+pub mod file;
+pub mod fen;
 pub mod game;
-mod name;
-mod piece;
-mod rank;
-mod square;
+pub mod horde;
+pub mod king_of_the_hill;
+pub mod movement;
+pub mod moves;
+pub mod motifs;
+pub mod name;
+pub mod opening;
+pub mod pgn;
+pub mod piece;
+pub mod pins;
+pub mod pocket;
+pub mod polyglot;
+pub mod puzzle;
+pub mod racing_kings;
+pub mod rank;
+pub mod retrograde;
+pub mod san;
+pub mod see;
+#[cfg(feature = "serde")]
+mod serialization;
+pub mod square;
+pub mod svg;
+pub mod syzygy;
+pub mod three_check;
+pub mod tree;
+pub mod uci;
+pub mod uci_engine;
+pub mod variant;
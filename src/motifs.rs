@@ -0,0 +1,302 @@
+use crate::color::Color;
+use crate::game::Game;
+use crate::moves::{Move, MoveKind};
+use crate::name::Name;
+use crate::square::Square;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum MotifKind {
+	Fork,
+	Pin,
+	Skewer,
+	DiscoveredCheck,
+	DoubleCheck,
+	BackRankThreat,
+	HangingPiece,
+	MateThreat,
+}
+
+// a tactical idea found in a move, with the squares of the pieces it's about
+#[derive(Clone, PartialEq, Debug)]
+pub struct Motif {
+	kind: MotifKind,
+	squares: Vec<Square>,
+}
+
+impl Motif {
+	fn new(kind: MotifKind, squares: Vec<Square>) -> Motif {
+		Motif {
+			kind,
+			squares,
+		}
+	}
+
+	pub fn kind(&self) -> MotifKind {
+		self.kind
+	}
+
+	// the squares in the order the motif reads, e.g. the forking piece first
+	// and then the pieces it attacks. They're the squares after the move
+	pub fn squares(&self) -> &[Square] {
+		&self.squares
+	}
+}
+
+fn same_square(a: Square, b: Square) -> bool {
+	a.rank == b.rank && a.file == b.file
+}
+
+fn is_king(square: Square) -> bool {
+	square.piece.is_some_and(|piece| piece.name == Name::King)
+}
+
+impl Game {
+	// the motifs the move creates, that weren't on the board before it
+	pub fn motifs(&self, mv: Move) -> Result<Vec<Motif>, String> {
+		let mut after = self.clone();
+		after.make_move(mv)?;
+
+		let mover = self.turn;
+		let mut motifs = Vec::new();
+
+		motifs.extend(self.checks(&after, mv));
+		motifs.extend(self.forks(&after, mv));
+
+		for pin in after.board.pins(mover.opposite()) {
+			if !self.board.pins(mover.opposite()).contains(&pin) {
+				motifs.push(Motif::new(MotifKind::Pin, vec![pin.pinner(), pin.pinned(), pin.target()]));
+			}
+		}
+
+		let before = self.board.line_ups(mover);
+		for (slider, front, back) in after.board.line_ups(mover) {
+			let skewer = front.piece.unwrap().color != mover &&
+				back.piece.unwrap().color != mover &&
+				!is_king(back) &&
+				(is_king(front) || front.piece.unwrap().value() > back.piece.unwrap().value());
+
+			if skewer && !before.contains(&(slider, front, back)) {
+				motifs.push(Motif::new(MotifKind::Skewer, vec![slider, front, back]));
+			}
+		}
+
+		for color in &[mover, mover.opposite()] {
+			let hanging = self.board.attack_map().hanging(*color);
+
+			for square in after.board.attack_map().hanging(*color) {
+				if !hanging.contains(&square) {
+					motifs.push(Motif::new(MotifKind::HangingPiece, vec![square]));
+				}
+			}
+		}
+
+		motifs.extend(Game::mate_threats(&after, mover));
+
+		Ok(motifs)
+	}
+
+	fn checks(&self, after: &Game, mv: Move) -> Vec<Motif> {
+		let mut motifs = Vec::new();
+
+		let king = match after.board.find_king(after.turn) {
+			Some(king) => king,
+			None => return motifs,
+		};
+
+		let checkers = after.board.can_capture(king, after.turn);
+
+		// the rook giving check after castling isn't uncovered
+		let uncovered = match mv.kind {
+			MoveKind::Normal | MoveKind::EnPassant => {
+				checkers.iter().find(|checker| !same_square(**checker, mv.to))
+			},
+			MoveKind::Castle(_) | MoveKind::Drop(_) => None,
+		};
+
+		if let Some(checker) = uncovered {
+			motifs.push(Motif::new(MotifKind::DiscoveredCheck, vec![*checker, king]));
+		}
+
+		if checkers.len() > 1 {
+			let mut squares = checkers.clone();
+			squares.push(king);
+			motifs.push(Motif::new(MotifKind::DoubleCheck, squares));
+		}
+
+		motifs
+	}
+
+	// the moved piece attacking two pieces it wins one of, because they
+	// are the king, worth more than the piece, or not defended
+	fn forks(&self, after: &Game, mv: Move) -> Vec<Motif> {
+		if let MoveKind::Castle(_) = mv.kind {
+			return Vec::new();
+		}
+
+		// in atomic chess the piece is gone once it has captured
+		let forker = *after.board.get(mv.to);
+		let value = match forker.piece {
+			Some(piece) => piece.value(),
+			None => return Vec::new(),
+		};
+		let map = after.board.attack_map();
+
+		let targets: Vec<Square> = after
+			.board
+			.squares()
+			.filter(|square| square.piece.is_some_and(|piece| piece.color == after.turn))
+			.filter(|square| map.attackers(**square).iter().any(|attacker| same_square(*attacker, forker)))
+			.filter(|square| {
+				is_king(**square) || square.piece.unwrap().value() > value || map.defenders(**square).is_empty()
+			})
+			.copied()
+			.collect();
+
+		if targets.len() < 2 {
+			return Vec::new();
+		}
+
+		let mut squares = vec![forker];
+		squares.extend(targets);
+
+		vec![Motif::new(MotifKind::Fork, squares)]
+	}
+
+	// the mates the color would have if it could move again,
+	// on the back rank when the king is stuck on its first rank
+	fn mate_threats(after: &Game, color: Color) -> Vec<Motif> {
+		let mut motifs = Vec::new();
+
+		if after.is_check() || after.legal_moves().is_empty() {
+			return motifs;
+		}
+
+		let king = match after.board.find_king(color.opposite()) {
+			Some(king) => king,
+			None => return motifs,
+		};
+
+		let back_rank = match color.opposite() {
+			Color::White => 0,
+			Color::Black => after.board.height() - 1,
+		};
+
+		let mut passed = after.clone();
+		passed.turn = color;
+		passed.en_passant = None;
+
+		for threat in passed.legal_moves() {
+			let mut mated = passed.clone();
+			mated.play(threat);
+
+			if !mated.is_checkmate() {
+				continue;
+			}
+
+			let on_back_rank = |square: Square| usize::from(square.rank) == back_rank;
+
+			let kind = if on_back_rank(king) && on_back_rank(threat.to) {
+				MotifKind::BackRankThreat
+			} else {
+				MotifKind::MateThreat
+			};
+
+			motifs.push(Motif::new(kind, vec![*after.board.get(threat.from), threat.to, king]));
+		}
+
+		motifs
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::variant::Atomic;
+
+	fn motifs(fen: &str, uci: &str) -> Vec<(MotifKind, Vec<String>)> {
+		let game = Game::from_fen(fen).unwrap();
+		let motifs = game.motifs(game.parse_uci(uci).unwrap()).unwrap();
+
+		motifs
+			.iter()
+			.map(|motif| (motif.kind(), motif.squares().iter().map(|square| square.to_string()).collect()))
+			.collect()
+	}
+
+	fn kinds(fen: &str, uci: &str) -> Vec<MotifKind> {
+		motifs(fen, uci).into_iter().map(|(kind, _)| kind).collect()
+	}
+
+	#[test]
+	fn test_fork() {
+		let found = motifs("r3k3/8/8/8/8/8/3N4/4K3 w - - 0 1", "d2c4");
+		assert!(found.is_empty());
+
+		let found = motifs("r3k3/8/8/8/8/2N5/8/4K3 w - - 0 1", "c3b5");
+		assert!(found.is_empty());
+
+		let found = motifs("r3k3/8/8/1N6/8/8/8/4K3 w - - 0 1", "b5c7");
+		assert_eq!(found[0], (MotifKind::Fork, vec!["c7".into(), "a8".into(), "e8".into()]));
+		assert_eq!(found[1], (MotifKind::HangingPiece, vec!["a8".into()]));
+	}
+
+	#[test]
+	fn test_pin_and_skewer() {
+		let found = motifs("4k3/1p6/2n5/8/8/8/8/4KB2 w - - 0 1", "f1b5");
+		assert_eq!(found, vec![(MotifKind::Pin, vec!["b5".into(), "c6".into(), "e8".into()])]);
+
+		let found = motifs("8/8/8/q3k3/8/8/8/6KR w - - 0 1", "h1h5");
+		assert_eq!(found, vec![(MotifKind::Skewer, vec!["h5".into(), "e5".into(), "a5".into()])]);
+		assert!(kinds("8/8/8/4k3/q7/8/8/6KR w - - 0 1", "h1h5").is_empty());
+	}
+
+	#[test]
+	fn test_checks() {
+		assert_eq!(kinds("4k3/8/8/8/4N3/8/8/4RK2 w - - 0 1", "e4c3"), vec![MotifKind::DiscoveredCheck]);
+
+		// the knight gives check too
+		assert_eq!(
+			kinds("4k3/8/8/8/4N3/8/8/4RK2 w - - 0 1", "e4f6"),
+			vec![MotifKind::DiscoveredCheck, MotifKind::DoubleCheck]
+		);
+	}
+
+	#[test]
+	fn test_hanging_piece() {
+		let found = motifs("4k3/8/8/3p4/8/8/8/Q3K3 w - - 0 1", "a1a2");
+		assert_eq!(found, vec![(MotifKind::HangingPiece, vec!["d5".into()])]);
+
+		// the queen steps into the reach of the pawn it attacks
+		let found = motifs("4k3/8/8/5p2/8/8/8/3QK3 w - - 0 1", "d1g4");
+		assert_eq!(
+			found,
+			vec![(MotifKind::HangingPiece, vec!["g4".into()]), (MotifKind::HangingPiece, vec!["f5".into()])]
+		);
+	}
+
+	#[test]
+	fn test_mate_threats() {
+		let found = motifs("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", "g1f2");
+		assert_eq!(found, vec![(MotifKind::BackRankThreat, vec!["a1".into(), "a8".into(), "g8".into()])]);
+
+		let found = motifs("r1bqkbnr/pppp1ppp/2n5/4p3/2B1P3/8/PPPP1PPP/RNBQK1NR w KQkq - 2 3", "d1h5");
+		assert!(found.contains(&(MotifKind::MateThreat, vec!["h5".into(), "f7".into(), "e8".into()])));
+	}
+
+	#[test]
+	fn test_capturing_piece_explodes() {
+		let game = Game::from_variant_fen(Atomic, "4k3/8/2p1p3/3p4/8/8/8/3QK3 w - - 0 1").unwrap();
+		let motifs = game.motifs(game.parse_uci("d1d5").unwrap()).unwrap();
+
+		assert!(motifs.iter().all(|motif| motif.kind() != MotifKind::Fork));
+	}
+
+	#[test]
+	fn test_illegal_move() {
+		let game = Game::new();
+		let e2 = Square::from_str("e2").unwrap();
+		let e5 = Square::from_str("e5").unwrap();
+
+		assert!(game.motifs(Move::new(e2, e5)).is_err());
+	}
+}
@@ -0,0 +1,81 @@
+use crate::direction::Direction;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MoveMode {
+	Any,
+	MoveOnly,
+	CaptureOnly,
+}
+
+// a single line of movement: the steps are taken in order to reach the
+// next square, and for riders they are repeated until something is in the way
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Movement {
+	pub(crate) steps: Vec<Direction>,
+	pub(crate) repeat: bool,
+	pub(crate) jump: bool,
+	pub(crate) hop: bool,
+	pub(crate) mode: MoveMode,
+}
+
+impl Movement {
+	pub fn new(steps: Vec<Direction>) -> Movement {
+		Movement {
+			steps,
+			repeat: false,
+			jump: false,
+			hop: false,
+			mode: MoveMode::Any,
+		}
+	}
+
+	// the steps are repeated until the piece runs into something
+	pub fn repeat(mut self) -> Movement {
+		self.repeat = true;
+		self
+	}
+
+	// squares passed between two landing squares are ignored
+	pub fn jump(mut self) -> Movement {
+		self.jump = true;
+		self
+	}
+
+	// the piece moves along the line, jumps over the first piece it
+	// meets and lands right behind it (like the grasshopper)
+	pub fn hop(mut self) -> Movement {
+		self.hop = true;
+		self
+	}
+
+	pub fn move_only(mut self) -> Movement {
+		self.mode = MoveMode::MoveOnly;
+		self
+	}
+
+	pub fn capture_only(mut self) -> Movement {
+		self.mode = MoveMode::CaptureOnly;
+		self
+	}
+
+	pub fn steps(&self) -> &[Direction] {
+		&self.steps
+	}
+
+	pub fn can_move(&self) -> bool {
+		self.mode != MoveMode::CaptureOnly
+	}
+
+	pub fn can_capture(&self) -> bool {
+		self.mode != MoveMode::MoveOnly
+	}
+
+	// the same movement walked from the destination back to where it started
+	pub(crate) fn backwards(&self) -> Movement {
+		let mut movement = self.clone();
+		movement.steps = self.steps.iter().rev().map(|step| step.backwards()).collect();
+		movement
+	}
+}
@@ -0,0 +1,91 @@
+use crate::castling::CastlingSide;
+use crate::name::Name;
+use crate::square::Square;
+
+use std::fmt;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum MoveKind {
+	Normal,
+	EnPassant,
+	// a castling move goes from the king to the castling rook
+	Castle(CastlingSide),
+	// a piece from the pocket is put on the empty square the move goes to
+	Drop(Name),
+}
+
+#[derive(Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Move {
+	pub(crate) from: Square,
+	pub(crate) to: Square,
+	pub(crate) promotion: Option<Name>,
+	pub(crate) kind: MoveKind,
+}
+
+impl Move {
+	pub fn new(from: Square, to: Square) -> Move {
+		Move::with_kind(from, to, MoveKind::Normal)
+	}
+
+	pub(crate) fn with_kind(from: Square, to: Square, kind: MoveKind) -> Move {
+		// only the coordinates are interesting, not what was standing there
+		Move {
+			from: Square::new(from.rank, from.file),
+			to: Square::new(to.rank, to.file),
+			promotion: None,
+			kind,
+		}
+	}
+
+	pub fn promote(mut self, name: Name) -> Move {
+		self.promotion = Some(name);
+		self
+	}
+
+	pub fn from(&self) -> Square {
+		self.from
+	}
+
+	pub fn to(&self) -> Square {
+		self.to
+	}
+
+	pub fn promotion(&self) -> Option<Name> {
+		self.promotion
+	}
+
+	pub fn kind(&self) -> MoveKind {
+		self.kind
+	}
+
+	pub fn is_castle(&self) -> bool {
+		matches!(self.kind, MoveKind::Castle(_))
+	}
+
+	pub fn is_en_passant(&self) -> bool {
+		self.kind == MoveKind::EnPassant
+	}
+
+	pub fn is_drop(&self) -> bool {
+		matches!(self.kind, MoveKind::Drop(_))
+	}
+}
+
+impl fmt::Debug for Move {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		if let MoveKind::Drop(name) = self.kind {
+			return write!(f, "{}@{}", char::from(name), self.to);
+		}
+
+		write!(f, "{}{}", self.from, self.to)?;
+
+		if let Some(name) = self.promotion {
+			write!(f, "{}", char::from(name).to_ascii_lowercase())?;
+		}
+
+		Ok(())
+	}
+}
@@ -1,4 +1,8 @@
-#[derive(Copy, Clone, PartialEq)]
+use crate::fairy::PieceKind;
+
+use std::convert::TryFrom;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
 pub enum Name {
 	King,
 	Queen,
@@ -6,17 +10,85 @@ pub enum Name {
 	Bishop,
 	Knight,
 	Pawn,
+	Custom(PieceKind),
 }
 
 impl Name {
 	pub fn all() -> Vec<Name> {
-		return vec![
+		vec![
 			Name::King,
 			Name::Queen,
 			Name::Rook,
 			Name::Bishop,
 			Name::Knight,
 			Name::Pawn,
-		];
+		]
+	}
+
+	// the standard pieces together with every custom piece in use
+	pub fn all_kinds() -> Vec<Name> {
+		let mut names = Name::all();
+		names.extend(PieceKind::all().into_iter().map(Name::Custom));
+		names
+	}
+
+	// in centipawns
+	pub fn value(&self) -> u32 {
+		match self {
+			Name::King => 0,
+			Name::Queen => 900,
+			Name::Rook => 500,
+			Name::Bishop => 300,
+			Name::Knight => 300,
+			Name::Pawn => 100,
+			Name::Custom(kind) => kind.definition().value(),
+		}
+	}
+
+	// like `try_from`, but the fairy pieces of a variant are known
+	// by their letters without being registered
+	pub fn from_letter(letter: char, pieces: &[PieceKind]) -> Option<Name> {
+		let upper = letter.to_ascii_uppercase();
+
+		match pieces.iter().find(|kind| kind.definition().letter() == upper) {
+			Some(kind) => Some(Name::Custom(*kind)),
+			None => Name::try_from(letter).ok(),
+		}
+	}
+
+	pub fn promotions() -> Vec<Name> {
+		vec![Name::Queen, Name::Rook, Name::Bishop, Name::Knight]
+	}
+}
+
+// letters are case insensitive, the case of a letter in e.g. FEN
+// tells the color of the piece and not what kind of piece it is
+impl TryFrom<char> for Name {
+	type Error = ();
+
+	fn try_from(value: char) -> Result<Self, Self::Error> {
+		match value.to_ascii_uppercase() {
+			'K' => Ok(Name::King),
+			'Q' => Ok(Name::Queen),
+			'R' => Ok(Name::Rook),
+			'B' => Ok(Name::Bishop),
+			'N' => Ok(Name::Knight),
+			'P' => Ok(Name::Pawn),
+			letter => PieceKind::from_letter(letter).map(Name::Custom).ok_or(()),
+		}
+	}
+}
+
+impl From<Name> for char {
+	fn from(name: Name) -> Self {
+		match name {
+			Name::King => 'K',
+			Name::Queen => 'Q',
+			Name::Rook => 'R',
+			Name::Bishop => 'B',
+			Name::Knight => 'N',
+			Name::Pawn => 'P',
+			Name::Custom(kind) => kind.definition().letter(),
+		}
 	}
 }
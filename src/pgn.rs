@@ -0,0 +1,328 @@
+use crate::game::Game;
+use crate::tree::{GameTree, NodeId};
+use crate::variant;
+
+use std::path::Path;
+
+#[derive(Clone, PartialEq, Debug)]
+enum Token {
+	Tag(String, String),
+	Comment(String),
+	Nag(u8),
+	Open,
+	Close,
+	// a move, a move number or a result
+	Word(String),
+}
+
+const RESULTS: [&str; 4] = ["1-0", "0-1", "1/2-1/2", "*"];
+
+// the annotation symbols and the glyphs they stand for
+const SUFFIXES: [(&str, u8); 6] = [("!!", 3), ("??", 4), ("!?", 5), ("?!", 6), ("!", 1), ("?", 2)];
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+	let mut tokens = Vec::new();
+	let mut chars = text.chars().peekable();
+	let mut line_start = true;
+
+	while let Some(c) = chars.next() {
+		match c {
+			'\n' => {
+				line_start = true;
+				continue;
+			},
+			// an escaped line, used by programs for their own data
+			'%' if line_start => {
+				chars.by_ref().find(|c| *c == '\n');
+				continue;
+			},
+			c if c.is_whitespace() => continue,
+			'[' => {
+				// the value may hold a ']', so the tag only ends after its closing quote
+				let mut tag: String = chars.by_ref().take_while(|c| *c != '"').collect();
+				tag.push('"');
+				while let Some(c) = chars.next() {
+					tag.push(c);
+					match c {
+						'\\' => tag.extend(chars.next()),
+						'"' => break,
+						_ => (),
+					}
+				}
+
+				while chars.next_if(|c| c.is_whitespace()).is_some() {}
+				if chars.next() != Some(']') {
+					return Err(format!("Invalid tag [{}", tag));
+				}
+
+				tokens.push(read_tag(&tag)?);
+			},
+			'{' => {
+				let comment: String = chars.by_ref().take_while(|c| *c != '}').collect();
+				tokens.push(Token::Comment(comment.split_whitespace().collect::<Vec<_>>().join(" ")));
+			},
+			';' => {
+				let comment: String = chars.by_ref().take_while(|c| *c != '\n').collect();
+				tokens.push(Token::Comment(comment.trim().to_string()));
+				line_start = true;
+				continue;
+			},
+			'(' => tokens.push(Token::Open),
+			')' => tokens.push(Token::Close),
+			'$' => {
+				let mut number = String::new();
+				while let Some(digit) = chars.next_if(|c| c.is_ascii_digit()) {
+					number.push(digit);
+				}
+
+				tokens.push(Token::Nag(number.parse().map_err(|_| "Invalid NAG".to_string())?));
+			},
+			c => {
+				let mut word = c.to_string();
+				while let Some(c) = chars.next_if(|c| !c.is_whitespace() && !"()[]{};$".contains(*c)) {
+					word.push(c);
+				}
+
+				read_word(&word, &mut tokens);
+			},
+		}
+
+		line_start = false;
+	}
+
+	Ok(tokens)
+}
+
+// a tag pair is written as [Name "value"], with \" and \\ escaped in the value
+fn read_tag(tag: &str) -> Result<Token, String> {
+	let invalid = || format!("Invalid tag [{}]", tag);
+
+	let (name, value) = tag.trim().split_once(char::is_whitespace).ok_or_else(invalid)?;
+	let value = value.trim().strip_prefix('"').and_then(|value| value.strip_suffix('"')).ok_or_else(invalid)?;
+
+	let mut unescaped = String::new();
+	let mut chars = value.chars();
+	while let Some(c) = chars.next() {
+		match c {
+			'\\' => unescaped.extend(chars.next()),
+			c => unescaped.push(c),
+		}
+	}
+
+	Ok(Token::Tag(name.to_string(), unescaped))
+}
+
+// splits the move number off the front and the annotation symbols off the end
+fn read_word(word: &str, tokens: &mut Vec<Token>) {
+	if RESULTS.contains(&word) {
+		tokens.push(Token::Word(word.to_string()));
+		return;
+	}
+
+	// a move number is digits followed by dots, the zeros of "0-0" are kept
+	let number = word.trim_start_matches(|c: char| c.is_ascii_digit());
+	let word = if number.starts_with('.') { number.trim_start_matches('.') } else { word };
+	if word.is_empty() {
+		return;
+	}
+
+	let san = word.trim_end_matches(['!', '?']);
+	tokens.push(Token::Word(san.to_string()));
+
+	let suffix = &word[san.len()..];
+	if let Some((_, nag)) = SUFFIXES.iter().find(|(symbol, _)| *symbol == suffix) {
+		tokens.push(Token::Nag(*nag));
+	}
+}
+
+impl GameTree {
+	// the first game of the PGN
+	pub fn from_pgn(pgn: &str) -> Result<GameTree, String> {
+		GameTree::read_pgns(pgn)?.into_iter().next().ok_or_else(|| "There is no game in the PGN".to_string())
+	}
+
+	// every game of a PGN database, with its variations and annotations
+	pub fn read_pgns(pgn: &str) -> Result<Vec<GameTree>, String> {
+		let mut games = Vec::new();
+		let mut tokens = tokenize(pgn)?.into_iter().peekable();
+
+		while tokens.peek().is_some() {
+			let mut headers = Vec::new();
+			while let Some(Token::Tag(name, value)) = tokens.next_if(|token| matches!(token, Token::Tag(_, _))) {
+				headers.push((name, value));
+			}
+
+			let mut tree = GameTree::new(GameTree::starting_position(&headers)?);
+			for (name, value) in headers.iter() {
+				tree.set_header(name, value);
+			}
+
+			// the nodes the open variations branch off from
+			let mut variations: Vec<NodeId> = Vec::new();
+
+			while let Some(token) = tokens.next_if(|token| !matches!(token, Token::Tag(_, _))) {
+				match token {
+					Token::Word(word) if RESULTS.contains(&word.as_str()) => {
+						if tree.header("Result").is_none() {
+							tree.set_header("Result", &word);
+						}
+
+						break;
+					},
+					Token::Word(san) => {
+						tree.play_san(&san).map_err(|error| format!("Game {}: {}", games.len() + 1, error))?;
+					},
+					Token::Comment(comment) => {
+						let current = tree.current();
+						let comment = match tree.node(current).comment() {
+							"" => comment,
+							previous => format!("{} {}", previous, comment),
+						};
+
+						tree.set_comment(current, &comment)?;
+					},
+					Token::Nag(nag) => tree.add_nag(tree.current(), nag)?,
+					// a variation replaces the move before it
+					Token::Open => {
+						variations.push(tree.current());
+						if !tree.back() {
+							return Err(format!("Game {}: a variation has to follow a move", games.len() + 1));
+						}
+					},
+					Token::Close => {
+						let current = variations.pop().ok_or_else(|| format!("Game {}: unmatched ')'", games.len() + 1))?;
+						tree.go_to(current)?;
+					},
+					Token::Tag(_, _) => unreachable!(),
+				}
+			}
+
+			if !variations.is_empty() {
+				return Err(format!("Game {}: unclosed variation", games.len() + 1));
+			}
+
+			tree.go_to_start();
+			games.push(tree);
+		}
+
+		Ok(games)
+	}
+
+	pub fn open<P: AsRef<Path>>(path: P) -> Result<Vec<GameTree>, String> {
+		let text = std::fs::read_to_string(path).map_err(|error| error.to_string())?;
+		GameTree::read_pgns(&text)
+	}
+
+	// the FEN and Variant tags tell where the game starts
+	fn starting_position(headers: &[(String, String)]) -> Result<Game, String> {
+		let header = |name: &str| headers.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str());
+
+		let variant = match header("Variant") {
+			Some(name) => Some(variant::from_name(name).ok_or_else(|| format!("Unknown variant '{}'", name))?),
+			None => None,
+		};
+
+		let fen = match (header("FEN"), &variant) {
+			(Some(fen), _) => fen.to_string(),
+			(None, Some(variant)) => variant.starting_fen().to_string(),
+			(None, None) => return Ok(Game::new()),
+		};
+
+		match variant {
+			Some(variant) => Game::from_fen_with_variant(&fen, variant),
+			None => Game::from_fen(&fen),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const PGN: &str = r#"[Event "Paris"]
+[White "Morphy, Paul"]
+[Black "Duke Karl / Count Isouard"]
+[Result "1-0"]
+
+1. e4 e5 2. Nf3 d6 {Philidor's defence} 3. d4 Bg4?! (3... exd4 4. Nxd4) 4. dxe5
+Bxf3 5. Qxf3 dxe5 6. Bc4 Nf6 7. Qb3 Qe7 8. Nc3 c6 9. Bg5 b5 10. Nxb5 cxb5 11.
+Bxb5+ Nbd7 12. O-O-O Rd8 13. Rxd7 Rxd7 14. Rd1 Qe6 15. Bxd7+ Nxd7 16. Qb8+ Nxb8
+17. Rd8# 1-0
+
+[Event "?"]
+[FEN "4k3/8/8/8/8/8/8/4K2R w K - 0 1"]
+
+1. O-O $1 Kd7 *
+"#;
+
+	#[test]
+	fn test_read() {
+		let games = GameTree::read_pgns(PGN).unwrap();
+		assert_eq!(games.len(), 2);
+
+		let morphy = &games[0];
+		assert_eq!(morphy.header("Black"), Some("Duke Karl / Count Isouard"));
+		assert_eq!(morphy.mainline().len(), 33);
+
+		let end = *morphy.mainline().last().unwrap();
+		assert!(morphy.node(end).game().is_checkmate());
+
+		let bg4 = morphy.mainline()[5];
+		assert_eq!(morphy.node(bg4).nags(), &[6]);
+		assert_eq!(morphy.node(morphy.mainline()[3]).comment(), "Philidor's defence");
+
+		let parent = morphy.node(bg4).parent().unwrap();
+		assert_eq!(morphy.node(parent).children().len(), 2);
+
+		let endgame = &games[1];
+		assert_eq!(endgame.header("Result"), Some("*"));
+		assert_eq!(endgame.node(endgame.mainline()[0]).nags(), &[1]);
+		assert!(endgame.to_pgn().ends_with("1. O-O $1 Kd7 *\n"));
+	}
+
+	#[test]
+	fn test_castling_with_zeros() {
+		let tree = GameTree::from_pgn("1. e4 e5 2. Nf3 Nc6 3. Bc4 Bc5 4. 0-0 Nf6 5.d3 d6 *").unwrap();
+		assert!(tree.to_pgn().ends_with("4. O-O Nf6 5. d3 d6 *\n"));
+
+		let fen = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+		let tree = GameTree::from_pgn(&format!("[FEN \"{}\"]\n\n1. 0-0-0 0-0 *", fen)).unwrap();
+		assert!(tree.to_pgn().ends_with("1. O-O-O O-O *\n"));
+	}
+
+	#[test]
+	fn test_brackets_in_tags() {
+		let tree = GameTree::from_pgn("[Event \"Blitz [5+0]\"]\n[Site \"\\\"x]\\\"\" ]\n\n1. e4 *").unwrap();
+		assert_eq!(tree.header("Event"), Some("Blitz [5+0]"));
+		assert_eq!(tree.header("Site"), Some("\"x]\""));
+		assert_eq!(tree.mainline().len(), 1);
+
+		assert!(GameTree::from_pgn("[Event \"Blitz\" x]\n\n1. e4 *").is_err());
+	}
+
+	#[test]
+	fn test_round_trip() {
+		let mut tree = GameTree::from_pgn(PGN).unwrap();
+		let again = GameTree::from_pgn(&tree.to_pgn()).unwrap();
+
+		assert_eq!(again.to_pgn(), tree.to_pgn());
+
+		// a '}' would end the comment early
+		let id = tree.mainline()[4];
+		tree.set_comment(id, "a } b").unwrap();
+		let again = GameTree::from_pgn(&tree.to_pgn()).unwrap();
+
+		assert_eq!(again.node(again.mainline()[4]).comment(), "a b");
+		assert_eq!(again.to_pgn(), tree.to_pgn());
+	}
+
+	#[test]
+	fn test_errors() {
+		let error = GameTree::read_pgns(&PGN.replace("Qe7", "Qe6")).err().unwrap();
+		assert!(error.starts_with("Game 1"));
+		assert!(GameTree::from_pgn("1. e4 (e5) *").is_err());
+		assert!(GameTree::from_pgn("1. e4 e5 (1... c5 *").is_err());
+		assert!(GameTree::from_pgn("[Variant \"shogi\"] *").is_err());
+		assert!(GameTree::from_pgn("").is_err());
+	}
+}
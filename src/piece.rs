@@ -1,32 +1,47 @@
 use crate::color::Color;
 use crate::direction::Direction;
+use crate::movement::Movement;
 use crate::name::Name;
-use crate::square::Square;
 
 use std::fmt;
 
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Copy, Clone, PartialEq, Debug)]
 pub struct Piece {
 	pub(crate) color: Color,
 	pub(crate) name: Name,
 	pub(crate) moved: bool,
+	// promoted pieces turn back into pawns when they are captured in Crazyhouse
+	pub(crate) promoted: bool,
 }
 
 impl Piece {
+	pub fn color(&self) -> Color {
+		self.color
+	}
+
+	pub fn name(&self) -> Name {
+		self.name
+	}
+
+	pub fn is_promoted(&self) -> bool {
+		self.promoted
+	}
+
 	pub fn new(color: Color, name: Name) -> Piece {
 		Piece {
-			color: color,
-			name: name,
+			color,
+			name,
 			moved: false,
+			promoted: false,
 		}
 	}
 
 	pub fn simple(name: Name) -> Piece {
-		let square = Square::from_str("A1").unwrap();
 		Piece {
 			color: Color::White,
-			name: name,
+			name,
 			moved: false,
+			promoted: false,
 		}
 	}
 
@@ -71,12 +86,26 @@ impl Piece {
 					vec![vec![Direction::Up], vec![Direction::Up, Direction::Up]]
 				}
 			},
+			Name::Custom(kind) => kind
+				.definition()
+				.movements()
+				.iter()
+				.filter(|movement| movement.can_move())
+				.map(|movement| movement.steps.clone())
+				.collect(),
 		}
 	}
 
 	pub fn capture_moves(&self) -> Vec<Vec<Direction>> {
 		match self.name {
 			Name::Pawn => vec![vec![Direction::UpLeft], vec![Direction::UpRight]],
+			Name::Custom(kind) => kind
+				.definition()
+				.movements()
+				.iter()
+				.filter(|movement| movement.can_capture())
+				.map(|movement| movement.steps.clone())
+				.collect(),
 			_ => self.moves(),
 		}
 	}
@@ -84,6 +113,7 @@ impl Piece {
 	pub fn special_capture_move(&self) -> bool {
 		match self.name {
 			Name::Pawn => true,
+			Name::Custom(_) => self.moves() != self.capture_moves(),
 			_ => false,
 		}
 	}
@@ -92,26 +122,48 @@ impl Piece {
 		match self.name {
 			Name::King | Name::Knight | Name::Pawn => false,
 			Name::Queen | Name::Rook | Name::Bishop => true,
+			Name::Custom(kind) => kind.definition().movements().iter().any(|movement| movement.repeat),
 		}
 	}
 
 	pub fn can_jump(&self) -> bool {
 		match self.name {
 			Name::Knight => true,
+			Name::Custom(kind) => kind.definition().movements().iter().any(|movement| movement.jump),
 			_ => false,
 		}
 	}
+
+	// every line the piece moves along, with the flags that apply to it
+	pub fn movements(&self) -> Vec<Movement> {
+		if let Name::Custom(kind) = self.name {
+			return kind.definition().movements().to_vec();
+		}
+
+		let with_flags = |steps: Vec<Direction>| {
+			let mut movement = Movement::new(steps);
+			movement.repeat = self.repetetive_moves();
+			movement.jump = self.can_jump();
+			movement
+		};
+
+		if self.special_capture_move() {
+			let moves = self.moves().into_iter().map(|steps| with_flags(steps).move_only());
+			let captures = self.capture_moves().into_iter().map(|steps| with_flags(steps).capture_only());
+
+			moves.chain(captures).collect()
+		} else {
+			self.moves().into_iter().map(with_flags).collect()
+		}
+	}
+
+	pub fn value(&self) -> u32 {
+		self.name.value()
+	}
 }
 
 impl fmt::Display for Piece {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-		match self.name {
-			Name::King => write!(f, "K"),
-			Name::Queen => write!(f, "Q"),
-			Name::Rook => write!(f, "R"),
-			Name::Bishop => write!(f, "B"),
-			Name::Knight => write!(f, "N"),
-			Name::Pawn => write!(f, "P"),
-		}
+		write!(f, "{}", char::from(self.name))
 	}
 }
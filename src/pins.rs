@@ -0,0 +1,334 @@
+use crate::board::Board;
+use crate::color::Color;
+use crate::file::File;
+use crate::game::Game;
+use crate::moves::{Move, MoveKind};
+use crate::name::Name;
+use crate::rank::Rank;
+use crate::square::Square;
+
+// a piece that can't leave the line between a slider (or any rider)
+// and the piece behind it without exposing that piece
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Pin {
+	pinned: Square,
+	pinner: Square,
+	target: Square,
+}
+
+impl Pin {
+	pub fn pinned(&self) -> Square {
+		self.pinned
+	}
+
+	pub fn pinner(&self) -> Square {
+		self.pinner
+	}
+
+	// the piece the pinned piece is shielding
+	pub fn target(&self) -> Square {
+		self.target
+	}
+
+	// the pinned piece can't move off the line at all when it's shielding the king,
+	// otherwise the pin is relative and moving only costs the piece behind it
+	pub fn is_absolute(&self) -> bool {
+		self.target.piece.is_some_and(|piece| piece.name == Name::King)
+	}
+}
+
+// a piece that uncovers an attack by a slider of its own side when it moves away
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct DiscoveredAttack {
+	piece: Square,
+	slider: Square,
+	target: Square,
+}
+
+impl DiscoveredAttack {
+	// the piece that has to move
+	pub fn piece(&self) -> Square {
+		self.piece
+	}
+
+	pub fn slider(&self) -> Square {
+		self.slider
+	}
+
+	pub fn target(&self) -> Square {
+		self.target
+	}
+
+	pub fn is_check(&self) -> bool {
+		self.target.piece.is_some_and(|piece| piece.name == Name::King)
+	}
+}
+
+impl Board {
+	// the pieces of the color pinned by the other side, relative pins are
+	// only counted when the piece behind is worth more than the pinned one
+	pub fn pins(&self, color: Color) -> Vec<Pin> {
+		let mut pins = Vec::new();
+
+		for (pinner, first, second) in self.line_ups(color.opposite()) {
+			if first.piece.unwrap().color != color || second.piece.unwrap().color != color {
+				continue;
+			}
+
+			let pinned = first.piece.unwrap();
+			let target = second.piece.unwrap();
+
+			let pin = Pin {
+				pinned: first,
+				pinner,
+				target: second,
+			};
+
+			if (pin.is_absolute() || (pinned.name != Name::King && target.value() > pinned.value())) &&
+				!pins.contains(&pin)
+			{
+				pins.push(pin);
+			}
+		}
+
+		pins
+	}
+
+	// the pieces of the color standing between one of their own sliders
+	// and a piece of the other side
+	pub fn discovered_attacks(&self, color: Color) -> Vec<DiscoveredAttack> {
+		let mut attacks = Vec::new();
+
+		for (slider, first, second) in self.line_ups(color) {
+			if first.piece.unwrap().color != color || second.piece.unwrap().color == color {
+				continue;
+			}
+
+			let attack = DiscoveredAttack {
+				piece: first,
+				slider,
+				target: second,
+			};
+
+			if !attacks.contains(&attack) {
+				attacks.push(attack);
+			}
+		}
+
+		attacks
+	}
+
+	// every rider of the color with the first two pieces along its lines
+	pub(crate) fn line_ups(&self, color: Color) -> Vec<(Square, Square, Square)> {
+		let mut line_ups = Vec::new();
+
+		for square in self.squares() {
+			match square.piece {
+				Some(piece) if piece.color == color => (),
+				_ => continue,
+			}
+
+			for line in self.lines(*square) {
+				let mut pieces = line.into_iter().filter(|square| !square.is_empty());
+
+				if let (Some(first), Some(second)) = (pieces.next(), pieces.next()) {
+					line_ups.push((*square, first, second));
+				}
+			}
+		}
+
+		line_ups
+	}
+
+	// the squares along every line the piece on the square captures along,
+	// as if the pieces on the line weren't there
+	fn lines(&self, square: Square) -> Vec<Vec<Square>> {
+		let piece = square.piece.unwrap();
+		let direction_change = match piece.color {
+			Color::White => 1,
+			Color::Black => -1,
+		};
+
+		let mut lines = Vec::new();
+
+		for movement in piece.movements() {
+			if !movement.repeat || movement.hop || !movement.can_capture() {
+				continue;
+			}
+
+			let mut line = Vec::new();
+			let mut curr_rank: isize = square.rank.into();
+			let mut curr_file: isize = square.file.into();
+
+			'repetetive: loop {
+				for (j, direction) in movement.steps.iter().enumerate() {
+					curr_rank += direction.delta_y() * direction_change;
+					curr_file += direction.delta_x() * direction_change;
+
+					if !self.contains(curr_rank, curr_file) {
+						break 'repetetive;
+					}
+
+					let target = *self.get(Square::new(Rank(curr_rank as usize), File(curr_file as usize)));
+
+					// a lame rider is stopped by the squares it passes
+					if j != movement.steps.len() - 1 {
+						if target.is_empty() || movement.jump {
+							continue;
+						} else {
+							break 'repetetive;
+						}
+					}
+
+					line.push(target);
+				}
+			}
+
+			lines.push(line);
+		}
+
+		lines
+	}
+
+	// whether every way the color captures either steps to the next square,
+	// or jumps over what's in between. Then a move can only expose a piece
+	// to an attack by leaving the line of a rider, which the pins tell
+	fn attacks_follow_lines(&self, color: Color) -> bool {
+		self.squares()
+			.filter_map(|square| square.piece)
+			.filter(|piece| piece.color == color)
+			.all(|piece| {
+				piece
+					.movements()
+					.iter()
+					.all(|movement| !movement.hop && (movement.steps.len() == 1 || movement.jump))
+			})
+	}
+}
+
+impl Game {
+	// the pieces of the side to move that are pinned
+	pub fn pins(&self) -> Vec<Pin> {
+		self.board.pins(self.turn)
+	}
+
+	// the moves of the side to move that would uncover an attack
+	pub fn discovered_attacks(&self) -> Vec<DiscoveredAttack> {
+		self.board.discovered_attacks(self.turn)
+	}
+
+	// the squares of the pieces pinned to the king, when the pins are enough
+	// to tell that a move is legal without trying it. They aren't when the king
+	// is in check, or when the variant has rules of its own for what's legal
+	pub(crate) fn absolute_pins(&self) -> Option<Vec<Square>> {
+		if !self.variant.standard_legality() ||
+			self.board.find_king(self.turn).is_none() ||
+			self.is_check() ||
+			!self.board.attacks_follow_lines(self.turn.opposite())
+		{
+			return None;
+		}
+
+		Some(self.pins().iter().filter(|pin| pin.is_absolute()).map(|pin| pin.pinned).collect())
+	}
+
+	// a move by a piece that isn't pinned and isn't the king keeps the king safe
+	pub(crate) fn is_safe_move(&self, mv: Move, pinned: &[Square]) -> bool {
+		let is_pinned = pinned.iter().any(|square| square.rank == mv.from.rank && square.file == mv.from.file);
+
+		if mv.kind != MoveKind::Normal || is_pinned {
+			return false;
+		}
+
+		self.board.get(mv.from).piece.is_some_and(|piece| piece.name != Name::King)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::fairy::PieceKind;
+	use crate::variant::Variant;
+
+	use std::sync::Arc;
+
+	// the riders are known by their letters in the variant, registering
+	// them would change what the other tests see
+	struct Riders;
+
+	impl Variant for Riders {
+		fn name(&self) -> &str {
+			"riders"
+		}
+
+		fn pieces(&self) -> Vec<PieceKind> {
+			vec![PieceKind::nightrider(), PieceKind::grasshopper()]
+		}
+	}
+
+	fn riders(fen: &str) -> Game {
+		Game::from_fen_with_variant(fen, Arc::new(Riders)).unwrap()
+	}
+
+	fn square(name: &str) -> Square {
+		Square::from_str(name).unwrap()
+	}
+
+	fn at(square: Square) -> String {
+		square.to_string()
+	}
+
+	#[test]
+	fn test_pins() {
+		let game = Game::from_fen("4k3/4r3/8/8/1b2N3/8/3Q4/4K2q w - - 0 1").unwrap();
+
+		let mut pins = game.pins();
+		pins.sort_by_key(|pin| at(pin.pinned()));
+
+		assert_eq!(pins.len(), 2);
+		assert_eq!(at(pins[0].pinned()), "d2");
+		assert_eq!(at(pins[0].pinner()), "b4");
+		assert_eq!(at(pins[0].target()), "e1");
+		assert!(pins[0].is_absolute());
+		assert_eq!(at(pins[1].pinned()), "e4");
+		assert!(pins[1].is_absolute());
+
+		// a queen in front of a rook isn't pinned by a rook
+		let game = Game::from_fen("4k3/8/8/8/8/8/8/r1Q1R1K1 w - - 0 1").unwrap();
+		assert!(game.pins().is_empty());
+
+		// a knight in front of a rook is
+		let game = Game::from_fen("4k3/8/8/8/8/8/8/r1N1R1K1 w - - 0 1").unwrap();
+		let pins = game.pins();
+		assert_eq!(pins.len(), 1);
+		assert!(!pins[0].is_absolute());
+		assert_eq!(at(pins[0].target()), "e1");
+	}
+
+	#[test]
+	fn test_discovered_attacks() {
+		let game = Game::from_fen("4k3/8/8/8/4N3/8/8/4RK2 w - - 0 1").unwrap();
+		let attacks = game.discovered_attacks();
+
+		assert_eq!(attacks.len(), 1);
+		assert_eq!(at(attacks[0].piece()), "e4");
+		assert_eq!(at(attacks[0].slider()), "e1");
+		assert!(attacks[0].is_check());
+
+		// the move is explained when it isn't allowed
+		let mut game = Game::from_fen("4k3/4r3/8/8/4N3/8/8/4K3 w - - 0 1").unwrap();
+		assert_eq!(game.move_piece(square("e4"), square("f6")), Err("The piece is pinned to the king".to_string()));
+	}
+
+	#[test]
+	fn test_nightrider_pin() {
+		// the nightrider on a1 pins the bishop on c2 to the king on e3
+		let game = riders("7k/8/8/8/8/4K3/2B5/h7 w - - 0 1");
+		let pins = game.pins();
+
+		assert_eq!(pins.len(), 1);
+		assert_eq!(at(pins[0].pinner()), "a1");
+		assert!(pins[0].is_absolute());
+		assert!(game.legal_moves().iter().all(|mv| mv.from != square("c2")));
+	}
+}
@@ -0,0 +1,77 @@
+use crate::name::Name;
+
+// the captured pieces a player can drop back onto the board
+#[derive(Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Pocket {
+	pieces: Vec<Name>,
+}
+
+impl Pocket {
+	pub fn new() -> Pocket {
+		Pocket { pieces: Vec::new() }
+	}
+
+	pub fn add(&mut self, name: Name) {
+		self.pieces.push(name);
+	}
+
+	// returns false when there is no such piece in the pocket
+	pub fn remove(&mut self, name: Name) -> bool {
+		match self.pieces.iter().position(|piece| *piece == name) {
+			Some(index) => {
+				self.pieces.remove(index);
+				true
+			},
+			None => false,
+		}
+	}
+
+	pub fn count(&self, name: Name) -> usize {
+		self.pieces.iter().filter(|piece| **piece == name).count()
+	}
+
+	pub fn len(&self) -> usize {
+		self.pieces.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.pieces.is_empty()
+	}
+
+	// every kind of piece in the pocket once, strongest first
+	pub fn names(&self) -> Vec<Name> {
+		let mut names: Vec<Name> = Name::all().into_iter().filter(|name| self.count(*name) > 0).collect();
+		for name in self.pieces.iter() {
+			if !names.contains(name) {
+				names.push(*name);
+			}
+		}
+
+		names.sort_by_key(|name| std::cmp::Reverse(name.value()));
+		names
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_pocket() {
+		let mut pocket = Pocket::new();
+		pocket.add(Name::Pawn);
+		pocket.add(Name::Knight);
+		pocket.add(Name::Pawn);
+
+		assert_eq!(pocket.len(), 3);
+		assert_eq!(pocket.count(Name::Pawn), 2);
+		assert_eq!(pocket.names(), vec![Name::Knight, Name::Pawn]);
+
+		assert!(pocket.remove(Name::Pawn));
+		assert!(pocket.remove(Name::Knight));
+		assert!(!pocket.remove(Name::Knight));
+		assert_eq!(pocket.names(), vec![Name::Pawn]);
+	}
+}
@@ -0,0 +1,323 @@
+use crate::game::Game;
+use crate::moves::Move;
+
+use std::path::Path;
+
+// a position with the line that solves it, as in the Lichess puzzle database.
+// The first move is the opponent's, the solver answers it
+#[derive(Clone, PartialEq, Debug)]
+pub struct Puzzle {
+	id: String,
+	fen: String,
+	moves: Vec<String>,
+	rating: Option<u32>,
+	themes: Vec<String>,
+}
+
+impl Puzzle {
+	// the moves are in UCI notation
+	pub fn new(id: &str, fen: &str, moves: &[&str]) -> Result<Puzzle, String> {
+		let puzzle = Puzzle {
+			id: id.to_string(),
+			fen: fen.to_string(),
+			moves: moves.iter().map(|mv| mv.to_string()).collect(),
+			rating: None,
+			themes: Vec::new(),
+		};
+
+		puzzle.check()?;
+		Ok(puzzle)
+	}
+
+	// a line of the Lichess puzzle CSV, which starts with
+	// PuzzleId,FEN,Moves,Rating,RatingDeviation,Popularity,NbPlays,Themes
+	pub fn from_csv_line(line: &str) -> Result<Puzzle, String> {
+		let fields: Vec<&str> = line.trim().split(',').collect();
+
+		if fields.len() < 3 {
+			return Err(format!("'{}' isn't a puzzle, it needs an id, a FEN and moves", line));
+		}
+
+		let moves: Vec<&str> = fields[2].split_whitespace().collect();
+		let mut puzzle = Puzzle::new(fields[0], fields[1], &moves)?;
+
+		puzzle.rating = match fields.get(3) {
+			Some(rating) if !rating.is_empty() => {
+				Some(rating.parse().map_err(|_| format!("Invalid rating in puzzle {}", puzzle.id))?)
+			},
+			_ => None,
+		};
+
+		puzzle.themes = fields.get(7).map_or(Vec::new(), |themes| {
+			themes.split_whitespace().map(|theme| theme.to_string()).collect()
+		});
+
+		Ok(puzzle)
+	}
+
+	// skips the header and empty lines
+	pub fn from_csv(text: &str) -> Result<Vec<Puzzle>, String> {
+		text.lines()
+			.filter(|line| !line.trim().is_empty() && !line.starts_with("PuzzleId"))
+			.map(Puzzle::from_csv_line)
+			.collect()
+	}
+
+	pub fn open<P: AsRef<Path>>(path: P) -> Result<Vec<Puzzle>, String> {
+		let text = std::fs::read_to_string(path).map_err(|error| error.to_string())?;
+		Puzzle::from_csv(&text)
+	}
+
+	pub fn id(&self) -> &str {
+		&self.id
+	}
+
+	pub fn fen(&self) -> &str {
+		&self.fen
+	}
+
+	pub fn moves(&self) -> &[String] {
+		&self.moves
+	}
+
+	pub fn rating(&self) -> Option<u32> {
+		self.rating
+	}
+
+	pub fn themes(&self) -> &[String] {
+		&self.themes
+	}
+
+	// plays the opponent's first move and hands over to the solver
+	pub fn start(&self) -> PuzzleRun {
+		let mut game = Game::from_fen(&self.fen).unwrap();
+		let setup = game.parse_uci(&self.moves[0]).unwrap();
+		game.make_move(setup).unwrap();
+
+		PuzzleRun {
+			puzzle: self.clone(),
+			game,
+			next: 1,
+			attempts: 0,
+			mistakes: 0,
+		}
+	}
+
+	// the whole line has to be legal, with a move for the solver after the setup
+	fn check(&self) -> Result<(), String> {
+		let mut game = Game::from_fen(&self.fen).map_err(|error| format!("Invalid FEN in puzzle {}: {}", self.id, error))?;
+
+		if self.moves.len() < 2 {
+			return Err(format!("Puzzle {} has no solution", self.id));
+		}
+
+		for uci in self.moves.iter() {
+			let mv = game.parse_uci(uci).map_err(|error| format!("Invalid solution in puzzle {}: {}", self.id, error))?;
+			game.make_move(mv)?;
+		}
+
+		Ok(())
+	}
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum PuzzleStatus {
+	InProgress,
+	Solved,
+	// a wrong move was tried, the line can still be played to its end
+	Failed,
+}
+
+// what became of a move of the solver
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Attempt {
+	// the move was right and the opponent answered it
+	Correct(Move),
+	// the move was the last one of the solution, or another mate
+	Solved,
+	// the move wasn't played
+	Wrong,
+}
+
+// a puzzle being solved
+#[derive(Clone)]
+pub struct PuzzleRun {
+	puzzle: Puzzle,
+	game: Game,
+	// the index of the next move of the solution
+	next: usize,
+	attempts: usize,
+	mistakes: usize,
+}
+
+impl PuzzleRun {
+	pub fn puzzle(&self) -> &Puzzle {
+		&self.puzzle
+	}
+
+	pub fn game(&self) -> &Game {
+		&self.game
+	}
+
+	// every move that was tried, right or wrong
+	pub fn attempts(&self) -> usize {
+		self.attempts
+	}
+
+	pub fn mistakes(&self) -> usize {
+		self.mistakes
+	}
+
+	pub fn is_finished(&self) -> bool {
+		self.next >= self.puzzle.moves.len()
+	}
+
+	pub fn status(&self) -> PuzzleStatus {
+		if self.mistakes > 0 {
+			PuzzleStatus::Failed
+		} else if self.is_finished() {
+			PuzzleStatus::Solved
+		} else {
+			PuzzleStatus::InProgress
+		}
+	}
+
+	// the move the solution expects next
+	pub fn hint(&self) -> Option<Move> {
+		let uci = self.puzzle.moves.get(self.next)?;
+		self.game.parse_uci(uci).ok()
+	}
+
+	// illegal moves are an error and aren't counted as attempts
+	pub fn play(&mut self, mv: Move) -> Result<Attempt, String> {
+		if self.is_finished() {
+			return Err(format!("Puzzle {} is already over", self.puzzle.id));
+		}
+
+		if !self.game.legal_moves().contains(&mv) {
+			return Err(format!("{:?} is not a legal move", mv));
+		}
+
+		self.attempts += 1;
+
+		let mut after = self.game.clone();
+		after.make_move(mv)?;
+
+		// any mate solves a puzzle, even when the solution mates differently
+		if after.is_checkmate() {
+			self.game = after;
+			self.next = self.puzzle.moves.len();
+			return Ok(Attempt::Solved);
+		}
+
+		if Some(mv) != self.hint() {
+			self.mistakes += 1;
+			return Ok(Attempt::Wrong);
+		}
+
+		self.game = after;
+		self.next += 1;
+
+		match self.hint() {
+			Some(reply) => {
+				self.game.make_move(reply)?;
+				self.next += 1;
+				Ok(Attempt::Correct(reply))
+			},
+			None => Ok(Attempt::Solved),
+		}
+	}
+
+	pub fn play_uci(&mut self, uci: &str) -> Result<Attempt, String> {
+		let mv = self.game.parse_uci(uci)?;
+		self.play(mv)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::color::Color;
+
+	const CSV: &str = "PuzzleId,FEN,Moves,Rating,RatingDeviation,Popularity,NbPlays,Themes,GameUrl,OpeningTags
+00008,r6k/pp2r2p/4Rp1Q/3p4/8/1N1P2R1/PqP2bPP/7K b - - 0 24,f2g3 e6e7 b2b1 b3c1 b1c1 h6c1,1913,75,94,6230,crushing hangingPiece long middlegame,https://lichess.org/787zsVup/black#48,
+0000D,6k1/2p2ppp/8/8/8/8/8/RR4K1 b - - 0 1,c7c6 a1a8,600,80,90,100,backRankMate mate mateIn1 oneMove,,
+";
+
+	#[test]
+	fn test_load() {
+		let puzzles = Puzzle::from_csv(CSV).unwrap();
+
+		assert_eq!(puzzles.len(), 2);
+		assert_eq!(puzzles[0].id(), "00008");
+		assert_eq!(puzzles[0].moves().len(), 6);
+		assert_eq!(puzzles[0].rating(), Some(1913));
+		assert_eq!(puzzles[0].themes()[1], "hangingPiece");
+
+		assert!(Puzzle::from_csv_line("1,6k1/8/8/8/8/8/8/6K1 w - - 0 1,g1g2").is_err());
+		assert!(Puzzle::from_csv_line("1,6k1/8/8/8/8/8/8/6K1 w - - 0 1,g1g2 g8g6").is_err());
+		assert!(Puzzle::from_csv_line("1,6k1/8/8/8/8/8/8/6K1 w - - 0 1,g1g2 g8g7").is_ok());
+	}
+
+	// the reply of the opponent in UCI notation
+	fn play(run: &mut PuzzleRun, uci: &str) -> Result<Option<String>, Attempt> {
+		let before = run.game().clone();
+
+		match run.play_uci(uci).unwrap() {
+			Attempt::Correct(reply) => {
+				let mut game = before;
+				game.make_move(game.parse_uci(uci).unwrap()).unwrap();
+				Ok(Some(game.to_uci(reply)))
+			},
+			Attempt::Solved => Ok(None),
+			Attempt::Wrong => Err(Attempt::Wrong),
+		}
+	}
+
+	#[test]
+	fn test_solve() {
+		let puzzles = Puzzle::from_csv(CSV).unwrap();
+		let mut run = puzzles[0].start();
+
+		assert_eq!(run.game().turn(), Color::White);
+		assert_eq!(run.status(), PuzzleStatus::InProgress);
+		assert_eq!(run.game().to_uci(run.hint().unwrap()), "e6e7");
+
+		assert_eq!(play(&mut run, "e6e7"), Ok(Some("b2b1".to_string())));
+		assert_eq!(play(&mut run, "b3c1"), Ok(Some("b1c1".to_string())));
+		assert_eq!(play(&mut run, "h6c1"), Ok(None));
+
+		assert!(run.is_finished());
+		assert_eq!(run.status(), PuzzleStatus::Solved);
+		assert_eq!(run.attempts(), 3);
+		assert!(run.play_uci("h1g1").is_err());
+	}
+
+	#[test]
+	fn test_mistakes() {
+		let puzzles = Puzzle::from_csv(CSV).unwrap();
+		let mut run = puzzles[0].start();
+
+		// illegal moves aren't attempts
+		assert!(run.play_uci("h1h3").is_err());
+		assert_eq!(run.attempts(), 0);
+
+		assert_eq!(play(&mut run, "h2g3"), Err(Attempt::Wrong));
+		assert_eq!(run.status(), PuzzleStatus::Failed);
+		assert_eq!(run.game().turn(), Color::White);
+
+		// the line can still be played out, but the puzzle stays failed
+		assert_eq!(play(&mut run, "e6e7"), Ok(Some("b2b1".to_string())));
+		assert_eq!((run.attempts(), run.mistakes()), (2, 1));
+	}
+
+	#[test]
+	fn test_other_mate() {
+		let puzzles = Puzzle::from_csv(CSV).unwrap();
+		let mut run = puzzles[1].start();
+
+		assert_eq!(play(&mut run, "b1b8"), Ok(None));
+		assert_eq!(run.status(), PuzzleStatus::Solved);
+		assert!(run.game().is_checkmate());
+	}
+}
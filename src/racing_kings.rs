@@ -0,0 +1,91 @@
+use crate::board::Board;
+use crate::color::Color;
+use crate::game::Game;
+use crate::moves::Move;
+use crate::variant::{Outcome, Variant};
+
+// no checks allowed, the first king to reach the last rank wins
+pub struct RacingKings;
+
+impl Variant for RacingKings {
+	fn name(&self) -> &str {
+		"racingkings"
+	}
+
+	fn starting_fen(&self) -> &str {
+		"8/8/8/8/8/8/krbnNBRK/qrbnNBRQ w - - 0 1"
+	}
+
+	// giving check isn't allowed either
+	fn is_legal(&self, game: &Game, _mv: Move, after: &Board) -> bool {
+		!self.is_in_check(after, game.turn()) && !self.is_in_check(after, game.turn().opposite())
+	}
+
+	fn outcome(&self, game: &Game) -> Option<Outcome> {
+		let board = game.board();
+		let goal = board.height() - 1;
+		let reached = |color: Color| board.find_king(color).is_some_and(|king| king.rank == goal);
+
+		let white = reached(Color::White);
+		let black = reached(Color::Black);
+
+		if !white && !black {
+			return None;
+		}
+
+		// black moves second, so when white gets there first
+		// black still has one move to draw the race
+		if white && !black && game.turn() == Color::Black {
+			let king = board.find_king(Color::Black)?;
+			let can_follow = board.neighbours(king).iter().any(|square| {
+				square.rank == goal &&
+					square.piece.is_none_or(|piece| piece.color == Color::White) &&
+					!board.is_attacked(*square, Color::Black)
+			});
+
+			if can_follow {
+				return None;
+			}
+		}
+
+		match (white, black) {
+			(true, true) => Some(Outcome::Draw),
+			(true, false) => Some(Outcome::Win(Color::White)),
+			_ => Some(Outcome::Win(Color::Black)),
+		}
+	}
+
+	// the king wins the race on its own
+	fn has_mating_material(&self, game: &Game, color: Color) -> bool {
+		game.board().find_king(color).is_some()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_racing_kings() {
+		let game = Game::with_variant(RacingKings);
+		assert_eq!(game.perft(1), 21);
+		assert_eq!(game.perft(2), 421);
+
+		// moves that give check aren't allowed
+		let game = Game::from_variant_fen(RacingKings, "8/8/8/8/8/8/k7/6RK w - - 0 1").unwrap();
+		assert!(game.parse_uci("g1a1").is_err());
+		assert!(game.parse_uci("g1g2").is_err());
+		assert!(game.parse_uci("g1b1").is_ok());
+
+		// black gets one more move to catch up when white reaches the goal first
+		let mut game = Game::from_variant_fen(RacingKings, "8/K5k1/8/8/8/8/8/8 w - - 0 1").unwrap();
+		game.make_move(game.parse_uci("a7a8").unwrap()).unwrap();
+		assert_eq!(game.outcome(), None);
+		game.make_move(game.parse_uci("g7g8").unwrap()).unwrap();
+		assert_eq!(game.outcome(), Some(Outcome::Draw));
+
+		let mut game = Game::from_variant_fen(RacingKings, "8/K7/6k1/8/8/8/8/8 w - - 0 1").unwrap();
+		game.make_move(game.parse_uci("a7b8").unwrap()).unwrap();
+		assert_eq!(game.outcome(), Some(Outcome::Win(Color::White)));
+	}
+}
@@ -1,13 +1,17 @@
+use crate::board::MAX_SIZE;
+
 use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
 
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Copy, Clone, PartialEq, Debug)]
 pub struct Rank(pub usize);
 
 impl TryFrom<usize> for Rank {
 	type Error = ();
 
 	fn try_from(value: usize) -> Result<Self, Self::Error> {
-		if value >= 0 && value < 8 {
+		if value < MAX_SIZE {
 			return Ok(Rank(value));
 		}
 		Err(())
@@ -22,6 +26,8 @@ impl TryFrom<isize> for Rank {
 	}
 }
 
+// the single character notation only covers the ranks of a standard board,
+// use the string notation for larger boards
 impl TryFrom<char> for Rank {
 	type Error = ();
 
@@ -68,6 +74,27 @@ impl From<Rank> for char {
 	}
 }
 
+impl FromStr for Rank {
+	type Err = ();
+
+	fn from_str(value: &str) -> Result<Self, Self::Err> {
+		if value.is_empty() || !value.chars().all(|c| c.is_ascii_digit()) {
+			return Err(());
+		}
+
+		match value.parse::<usize>() {
+			Ok(number) if number >= 1 => Rank::try_from(number - 1),
+			_ => Err(()),
+		}
+	}
+}
+
+impl fmt::Display for Rank {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}", self.0 + 1)
+	}
+}
+
 impl PartialEq<usize> for Rank {
 	fn eq(&self, other: &usize) -> bool {
 		let Rank(num) = self;
@@ -96,4 +123,12 @@ mod tests {
 
 		assert!(from_char.is_err());
 	}
+
+	#[test]
+	fn test_from_str_multiple_digits() {
+		assert_eq!(Rank::from_str("10"), Ok(Rank(9)));
+		assert_eq!(Rank(11).to_string(), "12");
+		assert!(Rank::from_str("0").is_err());
+		assert!(Rank::from_str("+1").is_err());
+	}
 }
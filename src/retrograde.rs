@@ -0,0 +1,1087 @@
+use crate::board::Board;
+use crate::castling::CastlingRights;
+use crate::color::Color;
+use crate::direction::Direction;
+use crate::fairy::PieceKind;
+use crate::file::File;
+use crate::game::Game;
+use crate::moves::Move;
+use crate::name::Name;
+use crate::piece::Piece;
+use crate::rank::Rank;
+use crate::square::Square;
+use crate::variant::{self, Outcome, Standard, Variant};
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+const MAGIC: [u8; 4] = *b"RDTM";
+
+// a stored value is 0 for a draw, or the plies to mate plus one,
+// negative when the side to move gets mated
+const MAX_PLIES: usize = 126;
+
+// the distance to mate in plies with perfect play, a side that is
+// mated right now has lost in 0 plies
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Dtm {
+	Win(usize),
+	Loss(usize),
+	Draw,
+}
+
+impl Dtm {
+	fn from_byte(byte: u8) -> Dtm {
+		match byte as i8 {
+			0 => Dtm::Draw,
+			value if value > 0 => Dtm::Win(value as usize - 1),
+			value => Dtm::Loss((-value) as usize - 1),
+		}
+	}
+
+	fn to_byte(self) -> u8 {
+		match self {
+			Dtm::Draw => 0,
+			Dtm::Win(plies) => (plies as i8 + 1) as u8,
+			Dtm::Loss(plies) => (-(plies as i8) - 1) as u8,
+		}
+	}
+
+	// the value one ply earlier, for the side that made the move
+	fn before(self) -> Dtm {
+		match self {
+			Dtm::Win(plies) => Dtm::Loss(plies + 1),
+			Dtm::Loss(plies) => Dtm::Win(plies + 1),
+			Dtm::Draw => Dtm::Draw,
+		}
+	}
+
+	fn plies(self) -> usize {
+		match self {
+			Dtm::Win(plies) | Dtm::Loss(plies) => plies,
+			Dtm::Draw => 0,
+		}
+	}
+}
+
+// the state of a position while a table is being generated
+#[derive(Copy, Clone, PartialEq)]
+enum Cell {
+	Invalid,
+	Unknown,
+	Known(u8),
+}
+
+// a way of turning the board: the ranks and files are swapped first,
+// then the ranks and then the files are mirrored
+#[derive(Copy, Clone, Default, PartialEq, Debug)]
+struct Symmetry {
+	transpose: bool,
+	flip_ranks: bool,
+	flip_files: bool,
+}
+
+impl Symmetry {
+	fn all() -> Vec<Symmetry> {
+		let mut all = Vec::new();
+		for transpose in [false, true] {
+			for flip_ranks in [false, true] {
+				for flip_files in [false, true] {
+					all.push(Symmetry {
+						transpose,
+						flip_ranks,
+						flip_files,
+					});
+				}
+			}
+		}
+
+		all
+	}
+
+	fn apply(self, width: usize, height: usize, square: usize) -> usize {
+		let (mut rank, mut file) = (square / width, square % width);
+		if self.transpose {
+			std::mem::swap(&mut rank, &mut file);
+		}
+
+		if self.flip_ranks {
+			rank = height - 1 - rank;
+		}
+
+		if self.flip_files {
+			file = width - 1 - file;
+		}
+
+		rank * width + file
+	}
+
+	// whether the piece still moves the same way once the board is turned,
+	// e.g. a pawn only allows the files to be mirrored
+	fn keeps(self, name: Name) -> bool {
+		let movements = Piece::simple(name).movements();
+
+		movements.iter().all(|movement| {
+			let steps: Option<Vec<Direction>> = movement
+				.steps
+				.iter()
+				.map(|step| {
+					let (mut delta_x, mut delta_y) = (step.delta_x(), step.delta_y());
+					if self.transpose {
+						std::mem::swap(&mut delta_x, &mut delta_y);
+					}
+
+					if self.flip_ranks {
+						delta_y = -delta_y;
+					}
+
+					if self.flip_files {
+						delta_x = -delta_x;
+					}
+
+					Direction::from_delta(delta_x, delta_y)
+				})
+				.collect();
+
+			let mut turned = movement.clone();
+			match steps {
+				Some(steps) => turned.steps = steps,
+				None => return false,
+			}
+
+			movements.contains(&turned)
+		})
+	}
+}
+
+// the distance to mate of every position with one set of pieces, e.g. KRvK.
+// A position is indexed by the square of every piece and the side to move.
+// Positions that are the same once the board is turned share an index, so
+// the first piece only needs the squares that can't be turned into a lower one
+#[derive(Clone, PartialEq, Debug)]
+pub struct EndgameTable {
+	variant: String,
+	width: usize,
+	height: usize,
+	symmetric: bool,
+	pieces: Vec<(Color, Name)>,
+	symmetries: Vec<Symmetry>,
+	first_squares: Vec<usize>,
+	values: Vec<u8>,
+}
+
+impl EndgameTable {
+	fn new(variant: &str, width: usize, height: usize, symmetric: bool, pieces: Vec<(Color, Name)>) -> EndgameTable {
+		let symmetries: Vec<Symmetry> = if symmetric {
+			Symmetry::all()
+				.into_iter()
+				.filter(|symmetry| !symmetry.transpose || width == height)
+				.filter(|symmetry| pieces.iter().all(|(_, name)| symmetry.keeps(*name)))
+				.collect()
+		} else {
+			vec![Symmetry::default()]
+		};
+
+		let first_squares = (0..width * height)
+			.filter(|square| symmetries.iter().all(|symmetry| symmetry.apply(width, height, *square) >= *square))
+			.collect();
+
+		EndgameTable {
+			variant: variant.to_string(),
+			width,
+			height,
+			symmetric,
+			pieces,
+			symmetries,
+			first_squares,
+			values: Vec::new(),
+		}
+	}
+
+	// generates a table for standard chess, and the smaller ones it needs
+	pub fn generate(material: &str) -> Result<EndgameTable, String> {
+		let mut generator = Generator::standard();
+		Ok(generator.generate(material)?.clone())
+	}
+
+	// the pieces of both sides, e.g. KQvK
+	pub fn material(&self) -> String {
+		material_key(&self.pieces)
+	}
+
+	pub fn variant(&self) -> &str {
+		&self.variant
+	}
+
+	pub fn len(&self) -> usize {
+		self.values.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.values.is_empty()
+	}
+
+	// the position has to have exactly the pieces of the table, and no
+	// castling rights, pockets or en passant captures the table can't know about
+	pub fn probe(&self, game: &Game) -> Option<Dtm> {
+		if game.variant().name() != self.variant ||
+			!game.castling_rights().is_empty() ||
+			game.pockets.iter().any(|pocket| !pocket.is_empty())
+		{
+			return None;
+		}
+
+		if game.en_passant.is_some() && game.legal_moves().iter().any(|mv| mv.is_en_passant()) {
+			return None;
+		}
+
+		self.value(&game.board, game.turn)
+	}
+
+	// the longest mate in the table
+	pub fn longest_mate(&self) -> usize {
+		self.values.iter().map(|byte| Dtm::from_byte(*byte).plies()).max().unwrap_or(0)
+	}
+
+	pub fn from_bytes(bytes: &[u8]) -> Result<EndgameTable, String> {
+		let invalid = || "The endgame table is cut short".to_string();
+
+		if bytes.len() < 4 || bytes[..4] != MAGIC {
+			return Err("Not an endgame table".to_string());
+		}
+
+		let mut at = 4;
+		let mut field = |length: usize| -> Result<&[u8], String> {
+			let field = bytes.get(at..at + length).ok_or_else(invalid)?;
+			at += length;
+			Ok(field)
+		};
+
+		let width = usize::from(field(1)?[0]);
+		let height = usize::from(field(1)?[0]);
+		let symmetric = field(1)?[0] != 0;
+
+		let length = usize::from(field(1)?[0]);
+		let variant = String::from_utf8(field(length)?.to_vec()).map_err(|error| error.to_string())?;
+
+		let length = usize::from(field(1)?[0]);
+		let material = String::from_utf8(field(length)?.to_vec()).map_err(|error| error.to_string())?;
+		// the fairy pieces are known by their letters in the variant
+		let kinds = variant::from_name(&variant).map(|variant| variant.pieces()).unwrap_or_default();
+		let pieces = parse_material(&material, &kinds)?;
+
+		let mut table = EndgameTable::new(&variant, width, height, symmetric, pieces);
+		let size = table.size()?;
+		table.values = field(size)?.to_vec();
+
+		Ok(table)
+	}
+
+	pub fn open<P: AsRef<Path>>(path: P) -> Result<EndgameTable, String> {
+		let bytes = std::fs::read(path).map_err(|error| error.to_string())?;
+		EndgameTable::from_bytes(&bytes)
+	}
+
+	// the magic, the size of the board, whether it can be turned, the variant and
+	// the material (both written with their length in front) and then a byte per position
+	pub fn to_bytes(&self) -> Vec<u8> {
+		let material = self.material();
+
+		let mut bytes = Vec::with_capacity(9 + self.variant.len() + material.len() + self.values.len());
+		bytes.extend_from_slice(&MAGIC);
+		bytes.push(self.width as u8);
+		bytes.push(self.height as u8);
+		bytes.push(self.symmetric as u8);
+		bytes.push(self.variant.len() as u8);
+		bytes.extend_from_slice(self.variant.as_bytes());
+		bytes.push(material.len() as u8);
+		bytes.extend_from_slice(material.as_bytes());
+		bytes.extend_from_slice(&self.values);
+		bytes
+	}
+
+	pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+		std::fs::write(path, self.to_bytes()).map_err(|error| error.to_string())
+	}
+
+	fn size(&self) -> Result<usize, String> {
+		let too_large = || "The endgame table would be too large".to_string();
+		let squares = self.width * self.height;
+
+		if self.pieces.is_empty() {
+			return Ok(2);
+		}
+
+		(1..self.pieces.len())
+			.try_fold(self.first_squares.len() * 2, |size, _| size.checked_mul(squares).ok_or_else(too_large))
+	}
+
+	fn value(&self, board: &Board, turn: Color) -> Option<Dtm> {
+		self.index(board, turn).map(|index| Dtm::from_byte(self.values[index]))
+	}
+
+	fn index(&self, board: &Board, turn: Color) -> Option<usize> {
+		if board.width() != self.width || board.height() != self.height {
+			return None;
+		}
+
+		let mut squares = vec![usize::MAX; self.pieces.len()];
+		let mut found = 0;
+
+		for square in board.squares() {
+			if let Some(piece) = square.piece {
+				let slot = (0..self.pieces.len())
+					.find(|slot| squares[*slot] == usize::MAX && self.pieces[*slot] == (piece.color, piece.name))?;
+
+				squares[slot] = square_index(board, *square);
+				found += 1;
+			}
+		}
+
+		if found != self.pieces.len() {
+			return None;
+		}
+
+		Some(self.index_of(&squares, turn))
+	}
+
+	// the lowest index the squares can be turned into. Pieces of the same
+	// kind can be swapped, they go into their slots in the order of their squares
+	fn index_of(&self, squares: &[usize], turn: Color) -> usize {
+		let mut lowest: Option<Vec<usize>> = None;
+
+		for symmetry in self.symmetries.iter() {
+			let mut turned: Vec<usize> =
+				squares.iter().map(|square| symmetry.apply(self.width, self.height, *square)).collect();
+
+			let mut start = 0;
+			while start < turned.len() {
+				let kind = self.pieces[start];
+				let end = start + self.pieces[start..].iter().take_while(|piece| **piece == kind).count();
+				turned[start..end].sort_unstable();
+				start = end;
+			}
+
+			if lowest.as_ref().is_none_or(|lowest| turned < *lowest) {
+				lowest = Some(turned);
+			}
+		}
+
+		let mut index = 0;
+		for (slot, square) in lowest.unwrap_or_default().into_iter().enumerate() {
+			index = match slot {
+				0 => self.first_squares.binary_search(&square).unwrap(),
+				_ => index * self.width * self.height + square,
+			};
+		}
+
+		index * 2 + turn.index()
+	}
+
+	// the inverse of index_of, None when two pieces end up on the same square
+	fn position(&self, index: usize) -> Option<(Vec<usize>, Color)> {
+		let turn = if index.is_multiple_of(2) { Color::White } else { Color::Black };
+		let mut rest = index / 2;
+		let mut squares = vec![0; self.pieces.len()];
+
+		for slot in (0..self.pieces.len()).rev() {
+			squares[slot] = match slot {
+				0 => *self.first_squares.get(rest)?,
+				_ => rest % (self.width * self.height),
+			};
+			rest /= self.width * self.height;
+		}
+
+		if (1..squares.len()).any(|slot| squares[..slot].contains(&squares[slot])) {
+			return None;
+		}
+
+		Some((squares, turn))
+	}
+
+	// puts the pieces on the squares of a board that might still have a position on it
+	fn place(&self, squares: &[usize], board: &mut Board) {
+		for square in 0..self.width * self.height {
+			board.get_mut(Square::new(Rank(square / self.width), File(square % self.width))).piece = None;
+		}
+
+		for (square, (color, name)) in squares.iter().zip(self.pieces.iter()) {
+			let square = Square::new(Rank(square / self.width), File(square % self.width));
+			board.get_mut(square).piece = Some(placed(board, square, *color, *name));
+		}
+	}
+}
+
+// generates tables for one variant and board size, keeping every
+// table it makes so that the larger ones can look up their captures
+pub struct Generator {
+	variant: Arc<dyn Variant>,
+	width: usize,
+	height: usize,
+	tables: HashMap<String, EndgameTable>,
+}
+
+impl Generator {
+	pub fn new(variant: Arc<dyn Variant>, width: usize, height: usize) -> Result<Generator, String> {
+		if variant.has_drops() || variant.counts_checks() {
+			return Err(format!("Tables can't be generated for {}", variant.name()));
+		}
+
+		if width < 2 || height < 3 {
+			return Err("The board is too small for an endgame table".to_string());
+		}
+
+		Ok(Generator {
+			variant,
+			width,
+			height,
+			tables: HashMap::new(),
+		})
+	}
+
+	pub fn standard() -> Generator {
+		Generator::new(Arc::new(Standard), 8, 8).unwrap()
+	}
+
+	// the table if it has been generated
+	pub fn table(&self, material: &str) -> Option<&EndgameTable> {
+		let key = material_key(&parse_material(material, &self.variant.pieces()).ok()?);
+		self.tables.get(&key)
+	}
+
+	pub fn generate(&mut self, material: &str) -> Result<&EndgameTable, String> {
+		let pieces = parse_material(material, &self.variant.pieces())?;
+		let key = material_key(&pieces);
+
+		if !self.tables.contains_key(&key) {
+			let table = self.build(pieces)?;
+			self.tables.insert(key.clone(), table);
+		}
+
+		Ok(&self.tables[&key])
+	}
+
+	// the positions are solved from the mates backwards. A position is won as soon
+	// as one move leads to a lost position, and lost once every move has been
+	// counted off as leading to a won one. Since the positions are solved in order
+	// of their distance to mate, the first win and the last loss are the quickest.
+	// The positions are set up on one game and the moves are tried on one board,
+	// so nothing has to be cloned for each of them
+	fn build(&mut self, pieces: Vec<(Color, Name)>) -> Result<EndgameTable, String> {
+		let mut table =
+			EndgameTable::new(self.variant.name(), self.width, self.height, self.variant.is_symmetric(), pieces);
+		let size = table.size()?;
+
+		let mut cells = vec![Cell::Invalid; size];
+		let mut moves_left = vec![0u16; size];
+		let mut queue: Vec<Vec<(usize, Dtm)>> = vec![Vec::new(); MAX_PLIES + 2];
+
+		let mut game = self.empty_game();
+		let mut after = game.board.clone();
+		let mut successors = Vec::new();
+
+		for index in 0..size {
+			if !self.set_up(&table, index, &mut game) {
+				continue;
+			}
+
+			let moves = game.legal_moves();
+			if let Some(dtm) = terminal(&game, &moves) {
+				cells[index] = Cell::Known(dtm.to_byte());
+				continue;
+			}
+
+			cells[index] = Cell::Unknown;
+			successors.clear();
+			let mut conversions = 0;
+
+			for mv in moves {
+				after.clone_from(&game.board);
+				Game::apply(&mut after, mv, game.turn, &*self.variant);
+
+				match table.index(&after, game.turn.opposite()) {
+					// the same position can come up after two moves once the board
+					// is turned, but it is only found once when its unmoves are
+					Some(successor) => {
+						if !successors.contains(&successor) {
+							successors.push(successor);
+						}
+					},
+					// captures and promotions go into the tables with fewer pieces
+					None => {
+						conversions += 1;
+						let dtm = self.convert(&after, game.turn.opposite())?.before();
+						if dtm != Dtm::Draw {
+							queue[dtm.plies()].push((index, dtm));
+						}
+					},
+				}
+			}
+
+			moves_left[index] = (successors.len() + conversions) as u16;
+		}
+
+		for (index, cell) in cells.iter().enumerate() {
+			if let Cell::Known(byte) = cell {
+				self.set_up(&table, index, &mut game);
+				self.queue_predecessors(&table, &game, Dtm::from_byte(*byte), &mut queue)?;
+			}
+		}
+
+		for plies in 1..queue.len() {
+			for (index, dtm) in std::mem::take(&mut queue[plies]) {
+				if cells[index] != Cell::Unknown {
+					continue;
+				}
+
+				if let Dtm::Loss(_) = dtm {
+					moves_left[index] = moves_left[index].saturating_sub(1);
+					if moves_left[index] > 0 {
+						continue;
+					}
+				}
+
+				// the unmoves can find a few positions the move can't be played
+				// from, so the position is looked at again before it's solved
+				self.set_up(&table, index, &mut game);
+				if let Some(dtm) = self.evaluate(&table, &cells, &game, &mut after) {
+					cells[index] = Cell::Known(dtm.to_byte());
+					self.queue_predecessors(&table, &game, dtm, &mut queue)?;
+				}
+			}
+		}
+
+		table.values = cells
+			.iter()
+			.map(|cell| match cell {
+				Cell::Known(byte) => *byte,
+				_ => 0,
+			})
+			.collect();
+
+		Ok(table)
+	}
+
+	fn empty_game(&self) -> Game {
+		let mut game = Game::from_board(Board::with_size(self.width, self.height), CastlingRights::none());
+		game.set_variant(self.variant.clone());
+		game
+	}
+
+	// sets the position up on the game, false for positions that can't come up:
+	// pieces on the same square, pawns on the first or last rank or the side that
+	// just moved in check. Positions that are the same once the board is turned
+	// or pieces of the same kind are swapped around are only solved once
+	fn set_up(&self, table: &EndgameTable, index: usize, game: &mut Game) -> bool {
+		let (squares, turn) = match table.position(index) {
+			Some((squares, turn)) if table.index_of(&squares, turn) == index => (squares, turn),
+			_ => return false,
+		};
+
+		let last_rank = self.height - 1;
+		let misplaced_pawn = squares.iter().zip(table.pieces.iter()).any(|(square, (_, name))| {
+			*name == Name::Pawn && (square / self.width == 0 || square / self.width == last_rank)
+		});
+
+		if misplaced_pawn {
+			return false;
+		}
+
+		table.place(&squares, &mut game.board);
+		if self.variant.is_in_check(&game.board, turn.opposite()) {
+			return false;
+		}
+
+		game.turn = turn;
+		game.en_passant = None;
+		self.variant.setup(game);
+
+		true
+	}
+
+	// the value of the position after a capture or a promotion, which
+	// is in a table with fewer pieces that is generated the first time
+	fn convert(&mut self, board: &Board, turn: Color) -> Result<Dtm, String> {
+		let material = board_material(board);
+		if !self.tables.contains_key(&material) {
+			self.generate(&material)?;
+		}
+
+		Ok(self.tables[&material].value(board, turn).unwrap_or(Dtm::Draw))
+	}
+
+	// the value of the position once enough of the positions after it are known
+	fn evaluate(&self, table: &EndgameTable, cells: &[Cell], game: &Game, after: &mut Board) -> Option<Dtm> {
+		let mut quickest_win: Option<usize> = None;
+		let mut slowest_loss: Option<usize> = Some(0);
+
+		for mv in game.legal_moves() {
+			after.clone_from(&game.board);
+			Game::apply(after, mv, game.turn, &*self.variant);
+
+			let turn = game.turn.opposite();
+			let dtm = match table.index(after, turn) {
+				Some(index) => match cells[index] {
+					Cell::Known(byte) => Some(Dtm::from_byte(byte)),
+					_ => None,
+				},
+				None => self.tables.get(&board_material(after)).and_then(|table| table.value(after, turn)),
+			};
+
+			match dtm.map(Dtm::before) {
+				Some(Dtm::Win(plies)) => quickest_win = Some(quickest_win.map_or(plies, |quickest| quickest.min(plies))),
+				Some(Dtm::Loss(plies)) => slowest_loss = slowest_loss.map(|slowest| slowest.max(plies)),
+				_ => slowest_loss = None,
+			}
+		}
+
+		quickest_win.map(Dtm::Win).or_else(|| slowest_loss.map(Dtm::Loss))
+	}
+
+	// every position before this one is queued once, even when
+	// more than one unmove leads to it once the board is turned
+	fn queue_predecessors(
+		&self,
+		table: &EndgameTable,
+		game: &Game,
+		dtm: Dtm,
+		queue: &mut [Vec<(usize, Dtm)>],
+	) -> Result<(), String> {
+		if dtm == Dtm::Draw {
+			return Ok(());
+		}
+
+		let dtm = dtm.before();
+		if dtm.plies() > MAX_PLIES {
+			return Err(format!("The mates in {} are too long to be stored", table.material()));
+		}
+
+		// the unmoves aren't checked here, a position that can't come up is
+		// never solved and the others are looked at again before they are
+		let color = game.turn.opposite();
+		let mut before = game.board.clone();
+		let mut predecessors = Vec::new();
+
+		for square in game.board.squares() {
+			let piece = match square.piece {
+				Some(piece) if piece.color == color => piece,
+				_ => continue,
+			};
+
+			for origin in unmove_origins(&game.board, *square, piece) {
+				unplay(&mut before, Move::new(origin, *square));
+
+				if let Some(index) = table.index(&before, color) {
+					if !predecessors.contains(&index) {
+						predecessors.push(index);
+					}
+				}
+
+				before.get_mut(origin).piece = None;
+				before.get_mut(*square).piece = Some(piece);
+			}
+		}
+
+		for index in predecessors {
+			queue[dtm.plies()].push((index, dtm));
+		}
+
+		Ok(())
+	}
+}
+
+impl Game {
+	// the moves the side that just moved could have played to get here. Captures,
+	// promotions, castling, en passant and drops change more than where one piece
+	// stands, so they can't be taken back without knowing the position before
+	pub fn unmoves(&self) -> Vec<Move> {
+		unmove_candidates(&self.board, self.turn, &*self.variant)
+			.into_iter()
+			.filter(|mv| {
+				let mut before = self.clone();
+				before.unplay(*mv);
+				before.legal_moves().contains(mv)
+			})
+			.collect()
+	}
+
+	pub fn probe_endgame(&self, table: &EndgameTable) -> Option<Dtm> {
+		table.probe(self)
+	}
+
+	pub fn unmake_move(&mut self, mv: Move) -> Result<(), String> {
+		if !self.unmoves().contains(&mv) {
+			return Err(format!("{:?} can't be taken back", mv));
+		}
+
+		self.unplay(mv);
+		Ok(())
+	}
+
+	// the counters and the en passant square can't be known, so they are only guessed
+	fn unplay(&mut self, mv: Move) {
+		unplay(&mut self.board, mv);
+
+		self.turn = self.turn.opposite();
+		if self.turn == Color::Black {
+			self.fullmove_number = self.fullmove_number.saturating_sub(1).max(1);
+		}
+
+		self.halfmove_clock = self.halfmove_clock.saturating_sub(1);
+		self.en_passant = None;
+		self.moved_last = None;
+		self.last_move = None;
+	}
+}
+
+// the unmoves without checking that the move is legal, the piece
+// can get there but it might not be allowed to (e.g. by a pin)
+fn unmove_candidates(board: &Board, turn: Color, variant: &dyn Variant) -> Vec<Move> {
+	let mut moves = Vec::new();
+	let color = turn.opposite();
+	let mut before = board.clone();
+
+	for square in board.squares() {
+		let piece = match square.piece {
+			Some(piece) if piece.color == color => piece,
+			_ => continue,
+		};
+
+		let hops = piece.movements().iter().any(|movement| movement.hop);
+
+		for origin in unmove_origins(board, *square, piece) {
+			let mv = Move::new(origin, *square);
+
+			before.clone_from(board);
+			unplay(&mut before, mv);
+
+			// the side to move now can't have been left in check,
+			// and a hopper might not be able to land here from there
+			let reaches = !hops || before.legal_moves(origin).contains(&mv.to);
+			if reaches && !moves.contains(&mv) && !variant.is_in_check(&before, turn) {
+				moves.push(mv);
+			}
+		}
+	}
+
+	moves
+}
+
+// moves the piece back, a pawn back on its starting rank can double step again
+fn unplay(board: &mut Board, mv: Move) {
+	board.move_piece(mv.to, mv.from);
+
+	let piece = board.get(mv.from).piece.map(|piece| placed(board, mv.from, piece.color, piece.name));
+	board.get_mut(mv.from).piece = piece;
+}
+
+// the empty squares the piece could have come from, pawns only walk back
+// and the double step only leads back to the starting rank
+fn unmove_origins(board: &Board, square: Square, piece: Piece) -> Vec<Square> {
+	let mut origins = Vec::new();
+
+	let direction_change = match piece.color {
+		Color::White => 1,
+		Color::Black => -1,
+	};
+
+	let (first_rank, start_rank) = match piece.color {
+		Color::White => (0, 1),
+		Color::Black => (board.height() - 1, board.height() - 2),
+	};
+
+	// a pawn that has moved has lost its double step,
+	// but it might have been the move that got it here
+	let mut unmoved = piece;
+	unmoved.moved = false;
+
+	for movement in unmoved.movements().into_iter().filter(|movement| movement.can_move()) {
+		let double_step = piece.name == Name::Pawn && movement.steps.len() == 2;
+		let movement = movement.backwards();
+
+		let mut curr_rank: isize = square.rank.into();
+		let mut curr_file: isize = square.file.into();
+
+		'repetetive: loop {
+			for (j, direction) in movement.steps.iter().enumerate() {
+				curr_rank += direction.delta_y() * direction_change;
+				curr_file += direction.delta_x() * direction_change;
+
+				if !board.contains(curr_rank, curr_file) {
+					break 'repetetive;
+				}
+
+				let origin = *board.get(Square::new(Rank(curr_rank as usize), File(curr_file as usize)));
+
+				if j != movement.steps.len() - 1 {
+					if origin.is_empty() || movement.jump || movement.hop {
+						continue;
+					} else {
+						break 'repetetive;
+					}
+				}
+
+				// a hopper can come from anywhere further along the line,
+				// the ones that couldn't have hopped here are sorted out later
+				if !origin.is_empty() {
+					if movement.hop {
+						continue;
+					}
+
+					break 'repetetive;
+				}
+
+				let pawn_misplaced = piece.name == Name::Pawn &&
+					(origin.rank == first_rank || (double_step && (origin.rank != start_rank || board.height() < 8)));
+
+				if !pawn_misplaced {
+					origins.push(Square::new(origin.rank, origin.file));
+				}
+			}
+
+			if !movement.repeat {
+				break;
+			}
+		}
+	}
+
+	origins
+}
+
+// a piece as it is set up from a FEN, pawns on their starting rank can double step
+fn placed(board: &Board, square: Square, color: Color, name: Name) -> Piece {
+	let start_rank = match color {
+		Color::White => 1,
+		Color::Black => board.height() - 2,
+	};
+
+	let mut piece = Piece::new(color, name);
+	piece.moved = name == Name::Pawn && (square.rank != start_rank || board.height() < 8);
+	piece
+}
+
+// the result of a position where the game is already over
+fn terminal(game: &Game, moves: &[Move]) -> Option<Dtm> {
+	let outcome = match game.variant.outcome(game) {
+		Some(outcome) => outcome,
+		None if moves.is_empty() => game.variant.no_moves_outcome(game),
+		None => return None,
+	};
+
+	Some(match outcome {
+		Outcome::Win(color) if color == game.turn => Dtm::Win(0),
+		Outcome::Win(_) => Dtm::Loss(0),
+		Outcome::Draw => Dtm::Draw,
+	})
+}
+
+fn square_index(board: &Board, square: Square) -> usize {
+	usize::from(square.rank) * board.width() + usize::from(square.file)
+}
+
+// the white pieces, a v and the black pieces, e.g. KBNvK
+fn parse_material(material: &str, kinds: &[PieceKind]) -> Result<Vec<(Color, Name)>, String> {
+	let (white, black) = material
+		.split_once(['v', 'V'])
+		.ok_or_else(|| format!("The material '{}' needs a v between the sides", material))?;
+
+	let mut pieces = Vec::new();
+	for (color, letters) in [(Color::White, white), (Color::Black, black)].iter() {
+		for letter in letters.chars() {
+			let name =
+				Name::from_letter(letter, kinds).ok_or_else(|| format!("Unknown piece '{}' in the material", letter))?;
+			pieces.push((*color, name));
+		}
+	}
+
+	sort_material(&mut pieces);
+	Ok(pieces)
+}
+
+// the same material is always written (and indexed) the same way,
+// the standard pieces strongest first and then the fairy pieces by letter
+fn sort_material(pieces: &mut [(Color, Name)]) {
+	let standard = Name::all();
+	pieces.sort_by_key(|(color, name)| {
+		(color.index(), standard.iter().position(|kind| kind == name).unwrap_or(standard.len()), char::from(*name))
+	});
+}
+
+fn material_key(pieces: &[(Color, Name)]) -> String {
+	let side = |side: Color| -> String {
+		pieces.iter().filter(|(color, _)| *color == side).map(|(_, name)| char::from(*name)).collect()
+	};
+
+	format!("{}v{}", side(Color::White), side(Color::Black))
+}
+
+fn board_material(board: &Board) -> String {
+	let mut pieces: Vec<(Color, Name)> =
+		board.squares().filter_map(|square| square.piece).map(|piece| (piece.color, piece.name)).collect();
+
+	sort_material(&mut pieces);
+	material_key(&pieces)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// standard chess with the amazon, known by its letter without registering it
+	struct Amazons;
+
+	impl Variant for Amazons {
+		fn name(&self) -> &str {
+			"amazons"
+		}
+
+		fn pieces(&self) -> Vec<PieceKind> {
+			vec![PieceKind::amazon()]
+		}
+	}
+
+	fn generator(width: usize, height: usize) -> Generator {
+		Generator::new(Arc::new(Standard), width, height).unwrap()
+	}
+
+	// every position has to agree with the best of the positions after it
+	fn check_table(generator: &Generator, material: &str) {
+		let table = generator.table(material).unwrap();
+
+		let mut game = generator.empty_game();
+		for index in 0..table.len() {
+			if !generator.set_up(table, index, &mut game) {
+				continue;
+			}
+
+			let stored = Dtm::from_byte(table.values[index]);
+			if let Some(dtm) = terminal(&game, &game.legal_moves()) {
+				assert_eq!(stored, dtm, "{}", game.to_fen());
+				continue;
+			}
+
+			let mut best = Dtm::Loss(0);
+			for mv in game.legal_moves() {
+				let mut after = game.clone();
+				after.play(mv);
+
+				let dtm = generator.tables[&board_material(&after.board)].probe(&after).unwrap().before();
+				best = match (best, dtm) {
+					(Dtm::Win(a), Dtm::Win(b)) => Dtm::Win(a.min(b)),
+					(Dtm::Win(_), _) => best,
+					(_, Dtm::Win(_)) => dtm,
+					(Dtm::Draw, _) | (_, Dtm::Draw) => Dtm::Draw,
+					(Dtm::Loss(a), Dtm::Loss(b)) => Dtm::Loss(a.max(b)),
+				};
+			}
+
+			assert_eq!(stored, best, "{}", game.to_fen());
+		}
+	}
+
+	#[test]
+	fn test_unmoves() {
+		let game = Game::from_fen("7k/8/8/8/4P3/8/8/K7 b - - 0 1").unwrap();
+		let mut unmoves: Vec<String> = game.unmoves().iter().map(|mv| format!("{:?}", mv)).collect();
+		unmoves.sort();
+
+		assert_eq!(unmoves, vec!["a2a1", "b1a1", "b2a1", "e2e4", "e3e4"]);
+
+		// the black king couldn't have been in check with white to move
+		let game = Game::from_fen("7k/8/8/8/8/8/8/K5R1 b - - 0 1").unwrap();
+		let mv = Move::new(Square::from_str("g8").unwrap(), Square::from_str("g1").unwrap());
+		assert!(!game.unmoves().contains(&mv));
+
+		let mut game = game;
+		let mv = Move::new(Square::from_str("c1").unwrap(), Square::from_str("g1").unwrap());
+		game.unmake_move(mv).unwrap();
+		assert_eq!(game.to_fen(), "7k/8/8/8/8/8/8/K1R5 w - - 0 1");
+		assert!(game.unmake_move(mv).is_err());
+	}
+
+	#[test]
+	fn test_krvk() {
+		let mut generator = generator(4, 4);
+		generator.generate("KRvK").unwrap();
+		check_table(&generator, "KRvK");
+
+		let table = generator.table("krvk").unwrap();
+		assert_eq!(table.material(), "KRvK");
+		// the white king only needs a1, b1 and b2 once the board is turned
+		assert_eq!(table.len(), 3 * 16 * 16 * 2);
+
+		let game = Game::from_fen("k3/2K1/4/3R w - - 0 1").unwrap();
+		assert_eq!(game.probe_endgame(table), Some(Dtm::Win(1)));
+
+		let game = Game::from_fen("k2R/4/K3/4 b - - 0 1").unwrap();
+		assert_eq!(game.probe_endgame(table), Some(Dtm::Loss(0)));
+
+		// black takes the rook
+		let game = Game::from_fen("4/4/4/Rk1K b - - 0 1").unwrap();
+		assert_eq!(game.probe_endgame(table), Some(Dtm::Draw));
+
+		assert_eq!(Game::new().probe_endgame(table), None);
+	}
+
+	#[test]
+	fn test_krvk_full_board() {
+		let mut generator = Generator::standard();
+		let table = generator.generate("KRvK").unwrap();
+
+		// the longest mate with a rook takes 16 moves
+		assert_eq!(table.longest_mate(), 32);
+
+		let game = Game::from_fen("8/8/8/8/8/2k5/1R6/K7 w - - 0 1").unwrap();
+		assert_eq!(game.probe_endgame(table), Some(Dtm::Win(31)));
+
+		let game = Game::from_fen("8/8/8/8/8/8/1Rk5/K7 b - - 0 1").unwrap();
+		assert_eq!(game.probe_endgame(table), Some(Dtm::Loss(32)));
+
+		// the same position turned around
+		let game = Game::from_fen("7K/6R1/5k2/8/8/8/8/8 w - - 0 1").unwrap();
+		assert_eq!(game.probe_endgame(table), Some(Dtm::Win(31)));
+	}
+
+	#[test]
+	fn test_kpvk() {
+		let mut generator = generator(4, 4);
+		let table = generator.generate("KPvK").unwrap();
+
+		// a pawn only lets the files be mirrored, so the white king needs half the board
+		assert_eq!(table.len(), 8 * 16 * 16 * 2);
+
+		// the pawn promotes before the black king gets to it
+		let game = Game::from_fen("4/1P2/2K1/k3 w - - 0 1").unwrap();
+		assert!(matches!(game.probe_endgame(table), Some(Dtm::Win(_))));
+
+		// every promotion and capture needs a smaller table
+		for material in &["KQvK", "KRvK", "KBvK", "KNvK", "KvK"] {
+			assert!(generator.table(material).is_some());
+		}
+
+		check_table(&generator, "KPvK");
+	}
+
+	#[test]
+	fn test_fairy_pieces() {
+		let mut generator = Generator::new(Arc::new(Amazons), 4, 4).unwrap();
+		let table = generator.generate("KMvK").unwrap();
+		let game = Game::from_fen_with_variant("k3/4/1K1M/4 w - - 0 1", Arc::new(Amazons)).unwrap();
+
+		assert_eq!(game.probe_endgame(table), Some(Dtm::Win(1)));
+		check_table(&generator, "KMvK");
+	}
+
+	#[test]
+	fn test_write_and_read() {
+		let mut generator = generator(3, 4);
+		let table = generator.generate("KQvK").unwrap().clone();
+
+		let bytes = table.to_bytes();
+		assert_eq!(EndgameTable::from_bytes(&bytes), Ok(table));
+		assert!(EndgameTable::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+		assert!(EndgameTable::from_bytes(b"RDTM").is_err());
+	}
+}
@@ -0,0 +1,255 @@
+use crate::castling::CastlingSide;
+use crate::file::File;
+use crate::game::Game;
+use crate::moves::{Move, MoveKind};
+use crate::name::Name;
+use crate::rank::Rank;
+use crate::square::Square;
+
+use std::str::FromStr;
+
+impl Game {
+	// standard algebraic notation, e.g. "Nbd7", "exd5", "e8=Q+" or "O-O"
+	pub fn to_san(&self, mv: Move) -> String {
+		let mut san = match mv.kind {
+			MoveKind::Castle(CastlingSide::King) => String::from("O-O"),
+			MoveKind::Castle(CastlingSide::Queen) => String::from("O-O-O"),
+			// pawn drops are written without the letter
+			MoveKind::Drop(Name::Pawn) => format!("@{}", mv.to),
+			MoveKind::Drop(name) => format!("{}@{}", char::from(name), mv.to),
+			_ => self.san_without_suffix(mv),
+		};
+
+		let mut game = self.clone();
+		game.play(mv);
+
+		if game.is_checkmate() {
+			san.push('#');
+		} else if game.is_check() {
+			san.push('+');
+		}
+
+		san
+	}
+
+	// also accepts "0-0", a missing '=' before the promotion and any
+	// trailing check or annotation symbols
+	pub fn parse_san(&self, san: &str) -> Result<Move, String> {
+		let invalid = || format!("'{}' is not a move in algebraic notation", san);
+
+		let trimmed = san.trim_end_matches(|c| "+#!?".contains(c));
+
+		let side = match trimmed {
+			"O-O" | "0-0" => Some(CastlingSide::King),
+			"O-O-O" | "0-0-0" => Some(CastlingSide::Queen),
+			_ => None,
+		};
+
+		if let Some(side) = side {
+			return self
+				.legal_moves()
+				.into_iter()
+				.find(|mv| mv.kind == MoveKind::Castle(side))
+				.ok_or(format!("{} is not a legal move", san));
+		}
+
+		if let Some((letter, square)) = trimmed.split_once('@') {
+			let name = match letter {
+				"" => Name::Pawn,
+				letter if letter.len() == 1 => self.name_from_letter(letter.chars().next().unwrap()).ok_or_else(invalid)?,
+				_ => return Err(invalid()),
+			};
+			let to = Square::from_str(square).map_err(|_| invalid())?;
+
+			return self
+				.legal_moves()
+				.into_iter()
+				.find(|mv| mv.kind == MoveKind::Drop(name) && mv.to == to)
+				.ok_or(format!("{} is not a legal move", san));
+		}
+
+		let (name, rest) = match trimmed.chars().next() {
+			Some(c) if c.is_ascii_uppercase() => (self.name_from_letter(c).ok_or_else(invalid)?, &trimmed[1..]),
+			Some(_) => (Name::Pawn, trimmed),
+			None => return Err(invalid()),
+		};
+
+		let (rest, promotion) = match rest.char_indices().last() {
+			Some((i, c)) if name == Name::Pawn && c.is_ascii_alphabetic() => {
+				let promotion = self.name_from_letter(c).ok_or_else(invalid)?;
+				(rest[..i].trim_end_matches('='), Some(promotion))
+			},
+			_ => (rest, None),
+		};
+
+		let (prefix, to) = self.split_target(rest).ok_or_else(invalid)?;
+		let prefix = prefix.strip_suffix('x').unwrap_or(prefix);
+
+		// the piece can be disambiguated by its file, its rank or both
+		let letters = prefix.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(prefix.len());
+		let file = match &prefix[..letters] {
+			"" => None,
+			letters => Some(File::from_str(letters).map_err(|_| invalid())?),
+		};
+		let rank = match &prefix[letters..] {
+			"" => None,
+			digits => Some(Rank::from_str(digits).map_err(|_| invalid())?),
+		};
+
+		let candidates: Vec<Move> = self
+			.legal_moves()
+			.into_iter()
+			.filter(|mv| !mv.is_castle() && !mv.is_drop() && mv.to == to && mv.promotion == promotion)
+			.filter(|mv| self.board.get(mv.from).piece.map(|piece| piece.name) == Some(name))
+			.filter(|mv| file.is_none_or(|file| mv.from.file == file))
+			.filter(|mv| rank.is_none_or(|rank| mv.from.rank == rank))
+			.collect();
+
+		match candidates.len() {
+			0 => Err(format!("{} is not a legal move", san)),
+			1 => Ok(candidates[0]),
+			_ => Err(format!("{} is ambiguous", san)),
+		}
+	}
+
+	fn san_without_suffix(&self, mv: Move) -> String {
+		let piece = self.board.get(mv.from).piece.unwrap();
+		let capture = mv.is_en_passant() || self.board.get(mv.to).piece.is_some();
+		let file = |square: Square| square.file.to_string().to_ascii_lowercase();
+
+		let mut san = String::new();
+
+		if piece.name == Name::Pawn {
+			if capture {
+				san.push_str(&file(mv.from));
+			}
+		} else {
+			san.push(char::from(piece.name));
+
+			let others: Vec<Move> = self
+				.legal_moves()
+				.into_iter()
+				.filter(|other| !other.is_castle() && !other.is_drop() && other.to == mv.to && other.from != mv.from)
+				.filter(|other| self.board.get(other.from).piece.map(|piece| piece.name) == Some(piece.name))
+				.collect();
+
+			if !others.is_empty() {
+				if others.iter().all(|other| other.from.file != mv.from.file) {
+					san.push_str(&file(mv.from));
+				} else if others.iter().all(|other| other.from.rank != mv.from.rank) {
+					san.push_str(&mv.from.rank.to_string());
+				} else {
+					san.push_str(&mv.from.to_string());
+				}
+			}
+		}
+
+		if capture {
+			san.push('x');
+		}
+
+		san.push_str(&mv.to.to_string());
+
+		if let Some(name) = mv.promotion {
+			san.push('=');
+			san.push(char::from(name));
+		}
+
+		san
+	}
+
+	// the target square is at the end, its file can be more than one letter
+	// on wide boards so the longest file that fits on the board is taken
+	fn split_target<'a>(&self, value: &'a str) -> Option<(&'a str, Square)> {
+		let digits = value.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+		let rank = Rank::from_str(&value[digits..]).ok()?;
+
+		let letters = value[..digits].len() - value[..digits].trim_end_matches(|c: char| c.is_ascii_alphabetic()).len();
+
+		(1..=letters.min(2))
+			.rev()
+			.filter_map(|length| {
+				let start = digits - length;
+				let file = File::from_str(&value[start..digits]).ok()?;
+				let square = Square::new(rank, file);
+
+				if self.board.contains(square.rank.0 as isize, square.file.0 as isize) {
+					Some((&value[..start], square))
+				} else {
+					None
+				}
+			})
+			.next()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn play(game: &mut Game, moves: &[&str]) {
+		for san in moves {
+			let mv = game.parse_san(san).unwrap();
+			assert_eq!(&game.to_san(mv), san);
+			game.make_move(mv).unwrap();
+		}
+	}
+
+	#[test]
+	fn test_round_trip() {
+		let mut game = Game::new();
+
+		play(&mut game, &["e4", "e5", "Nf3", "Nc6", "Bb5", "a6", "Bxc6", "dxc6", "O-O", "f6"]);
+		assert_eq!(game.to_fen(), "r1bqkbnr/1pp3pp/p1p2p2/4p3/4P3/5N2/PPPP1PPP/RNBQ1RK1 w kq - 0 6");
+	}
+
+	#[test]
+	fn test_check_and_mate() {
+		let mut game = Game::new();
+
+		play(&mut game, &["f3", "e5", "g4", "Qh4#"]);
+		assert!(game.is_checkmate());
+
+		let game = Game::from_fen("4k3/8/8/8/8/8/8/R3K3 w Q - 0 1").unwrap();
+		assert_eq!(game.to_san(game.parse_san("Ra8").unwrap()), "Ra8+");
+		assert_eq!(game.to_san(game.parse_san("0-0-0").unwrap()), "O-O-O");
+	}
+
+	#[test]
+	fn test_disambiguation() {
+		let game = Game::from_fen("4k3/8/8/8/R6R/8/1N3N2/K2R4 w - - 0 1").unwrap();
+
+		assert!(game.parse_san("Rd4").is_err());
+		assert_eq!(game.to_san(game.parse_san("Rad4").unwrap()), "Rad4");
+		assert_eq!(game.to_san(game.parse_san("Nfd3").unwrap()), "Nfd3");
+		assert!(game.parse_san("Nd3").is_err());
+		assert_eq!(game.to_san(game.parse_san("Rdd4").unwrap()), "Rdd4");
+
+		let game = Game::from_fen("7k/8/8/8/8/1Q3Q2/8/K4Q2 w - - 0 1").unwrap();
+		assert_eq!(game.to_san(game.parse_san("Qf3d1").unwrap()), "Qf3d1");
+		assert_eq!(game.to_san(game.parse_san("Qbd1").unwrap()), "Qbd1");
+	}
+
+	#[test]
+	fn test_pawn_moves() {
+		let game = Game::from_fen("k7/4P3/8/2pP4/8/8/8/K7 w - c6 0 1").unwrap();
+
+		let mv = game.parse_san("dxc6").unwrap();
+		assert!(mv.is_en_passant());
+		assert_eq!(game.to_san(mv), "dxc6");
+
+		let mv = game.parse_san("e8N").unwrap();
+		assert_eq!(mv.promotion(), Some(Name::Knight));
+		assert_eq!(game.to_san(mv), "e8=N");
+		assert_eq!(game.to_san(game.parse_san("e8=Q+").unwrap()), "e8=Q+");
+		assert!(game.parse_san("e8").is_err());
+	}
+
+	#[test]
+	fn test_large_board() {
+		let game = Game::from_fen("k9/10/10/10/10/10/10/10/P9/K9 w - - 0 1").unwrap();
+
+		assert_eq!(game.to_san(game.parse_san("a4").unwrap()), "a4");
+		assert!(game.parse_san("k1").is_err());
+	}
+}
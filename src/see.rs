@@ -0,0 +1,159 @@
+use crate::board::Board;
+use crate::color::Color;
+use crate::game::Game;
+use crate::moves::{Move, MoveKind};
+use crate::name::Name;
+use crate::piece::Piece;
+use crate::square::Square;
+
+// the king goes last in an exchange, capturing it ends the exchange
+// in favour of whoever did, so the side with the king stops before that
+const KING_VALUE: i32 = 100_000;
+
+fn exchange_value(piece: Piece) -> i32 {
+	match piece.name {
+		Name::King => KING_VALUE,
+		_ => piece.value() as i32,
+	}
+}
+
+impl Board {
+	// the material the color wins by capturing on the square with its least
+	// valuable attacker, once both sides have recaptured for as long as it pays off
+	pub fn see(&self, square: Square, color: Color) -> i32 {
+		match self.least_valuable_attacker(square, color) {
+			Some(attacker) => self.exchange(attacker, square),
+			None => 0,
+		}
+	}
+
+	// the material won by moving the piece on `from` to `to`, in centipawns.
+	// Taking the pieces off the board one at a time reveals the pieces behind
+	// them, so batteries and x-rays through the square are counted
+	pub fn exchange(&self, from: Square, to: Square) -> i32 {
+		let mut board = self.clone();
+		let mut gains = Vec::new();
+
+		let mut attacker = board.get(from).piece.unwrap();
+		let mut from = from;
+		gains.push(board.get(to).piece.map_or(0, exchange_value));
+
+		loop {
+			board.capture_piece(from);
+			board.capture_piece(to);
+			board.get_mut(to).piece = Some(attacker);
+
+			let color = attacker.color.opposite();
+			from = match board.least_valuable_attacker(to, color) {
+				Some(square) => square,
+				None => break,
+			};
+
+			// what the next side wins by taking, if it does
+			gains.push(exchange_value(attacker) - gains.last().unwrap());
+			attacker = board.get(from).piece.unwrap();
+		}
+
+		// every side can stop recapturing when it would lose by going on
+		while gains.len() > 1 {
+			let last = gains.pop().unwrap();
+			let previous = gains.last_mut().unwrap();
+			*previous = -(-*previous).max(last);
+		}
+
+		gains[0]
+	}
+
+	fn least_valuable_attacker(&self, square: Square, color: Color) -> Option<Square> {
+		self.can_capture(square, color.opposite())
+			.into_iter()
+			.min_by_key(|attacker| exchange_value(attacker.piece.unwrap()))
+	}
+}
+
+impl Game {
+	// the static exchange evaluation of a move, moves that don't capture
+	// are negative when the piece can be won on the square it goes to
+	pub fn see(&self, mv: Move) -> i32 {
+		match mv.kind {
+			MoveKind::Normal => self.board.exchange(mv.from, mv.to),
+			MoveKind::EnPassant => {
+				let mut board = self.board.clone();
+				let captured = Square::new(mv.from.rank, mv.to.file);
+
+				let pawn = exchange_value(board.get(captured).piece.unwrap());
+				board.capture_piece(captured);
+
+				pawn + board.exchange(mv.from, mv.to)
+			},
+			MoveKind::Castle(_) | MoveKind::Drop(_) => 0,
+		}
+	}
+
+	// whether the move doesn't lose material in the exchange that follows
+	pub fn is_safe_capture(&self, mv: Move) -> bool {
+		self.see(mv) >= 0
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn square(name: &str) -> Square {
+		Square::from_str(name).unwrap()
+	}
+
+	fn see(fen: &str, uci: &str) -> i32 {
+		let game = Game::from_fen(fen).unwrap();
+		game.see(game.parse_uci(uci).unwrap())
+	}
+
+	#[test]
+	fn test_simple_exchanges() {
+		// an undefended pawn
+		assert_eq!(see("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1", "e1e5"), 100);
+		// a pawn defended by a knight
+		assert_eq!(see("1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1", "d3e5"), -200);
+		// the rook behind the knight takes back, but the knight is lost for two pawns
+		assert_eq!(see("4k3/8/3p4/4p3/8/3N4/8/4RK2 w - - 0 1", "d3e5"), -100);
+		// a quiet move to a square the pawn attacks
+		assert_eq!(see("4k3/8/3p4/8/8/8/8/4QK2 w - - 0 1", "e1e5"), -900);
+		assert_eq!(see("4k3/8/8/8/8/8/8/4QK2 w - - 0 1", "e1e5"), 0);
+	}
+
+	#[test]
+	fn test_batteries() {
+		// the rook behind the first one wins the pawn defended by a rook
+		let fen = "4r1k1/8/8/4p3/8/8/4R3/4R1K1 w - - 0 1";
+		assert_eq!(see(fen, "e2e5"), 100);
+
+		// without the second rook it loses the exchange
+		assert_eq!(see("4r1k1/8/8/4p3/8/8/4R3/6K1 w - - 0 1", "e2e5"), -400);
+
+		// the queen behind the bishop takes back the knight
+		let game = Game::from_fen("6k1/8/5p2/3n4/8/2B5/1Q6/6K1 w - - 0 1").unwrap();
+		assert_eq!(game.board().see(square("f6"), Color::White), 100);
+
+		let game = Game::from_fen("6k1/8/5p2/3n4/8/2B5/8/6K1 w - - 0 1").unwrap();
+		assert_eq!(game.board().see(square("f6"), Color::White), -200);
+		assert_eq!(game.board().see(square("a1"), Color::White), 0);
+	}
+
+	#[test]
+	fn test_king_recaptures_last() {
+		// the king can't take back on a square the rook still covers
+		assert_eq!(see("2R5/8/8/8/8/1k6/2p5/K1R5 w - - 0 1", "c1c2"), 100);
+		// and does when it's alone
+		assert_eq!(see("8/8/8/8/8/1k6/2p5/K1R5 w - - 0 1", "c1c2"), -400);
+	}
+
+	#[test]
+	fn test_en_passant() {
+		let game = Game::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+		let mv = game.parse_uci("e5d6").unwrap();
+
+		assert_eq!(game.see(mv), 100);
+		assert!(game.is_safe_capture(mv));
+	}
+}
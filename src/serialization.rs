@@ -0,0 +1,294 @@
+// the types with a notation of their own are written in it,
+// so that e.g. a square is "e4" and a piece its FEN letter
+
+use crate::board::Board;
+use crate::color::Color;
+use crate::file::File;
+use crate::game::Game;
+use crate::name::Name;
+use crate::piece::Piece;
+use crate::rank::Rank;
+use crate::square::Square;
+use crate::variant::{self, Variant};
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use std::convert::TryFrom;
+use std::str::FromStr;
+use std::sync::Arc;
+
+fn read_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+	String::deserialize(deserializer)
+}
+
+impl Serialize for Square {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_str(&self.to_string())
+	}
+}
+
+impl<'de> Deserialize<'de> for Square {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		let value = read_string(deserializer)?;
+		Square::from_str(&value).map_err(|_| D::Error::custom(format!("Invalid square '{}'", value)))
+	}
+}
+
+impl Serialize for Rank {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_str(&self.to_string())
+	}
+}
+
+impl<'de> Deserialize<'de> for Rank {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		let value = read_string(deserializer)?;
+		Rank::from_str(&value).map_err(|_| D::Error::custom(format!("Invalid rank '{}'", value)))
+	}
+}
+
+// lowercase like the file of a square
+impl Serialize for File {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_str(&self.to_string().to_ascii_lowercase())
+	}
+}
+
+impl<'de> Deserialize<'de> for File {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		let value = read_string(deserializer)?;
+		File::from_str(&value).map_err(|_| D::Error::custom(format!("Invalid file '{}'", value)))
+	}
+}
+
+impl Serialize for Name {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_str(&char::from(*self).to_string())
+	}
+}
+
+impl<'de> Deserialize<'de> for Name {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		let value = read_string(deserializer)?;
+		let mut chars = value.chars();
+
+		match (chars.next(), chars.next()) {
+			(Some(letter), None) => Name::try_from(letter).ok(),
+			_ => None,
+		}
+		.ok_or_else(|| D::Error::custom(format!("Unknown piece '{}'", value)))
+	}
+}
+
+// uppercase for white and lowercase for black,
+// followed by a '~' when the piece was promoted
+impl Serialize for Piece {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		let letter = match self.color {
+			Color::White => char::from(self.name),
+			Color::Black => char::from(self.name).to_ascii_lowercase(),
+		};
+
+		let marker = if self.promoted { "~" } else { "" };
+		serializer.serialize_str(&format!("{}{}", letter, marker))
+	}
+}
+
+impl<'de> Deserialize<'de> for Piece {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		let value = read_string(deserializer)?;
+		let unknown = || D::Error::custom(format!("Unknown piece '{}'", value));
+
+		let mut chars = value.chars();
+		let letter = chars.next().ok_or_else(unknown)?;
+		let promoted = match chars.as_str() {
+			"" => false,
+			"~" => true,
+			_ => return Err(unknown()),
+		};
+
+		let color = if letter.is_ascii_uppercase() { Color::White } else { Color::Black };
+		let mut piece = Piece::new(color, Name::try_from(letter).map_err(|_| unknown())?);
+		piece.promoted = promoted;
+
+		Ok(piece)
+	}
+}
+
+// the placement field of a FEN
+impl Serialize for Board {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_str(&self.placement())
+	}
+}
+
+impl<'de> Deserialize<'de> for Board {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		Board::from_placement(&read_string(deserializer)?).map_err(D::Error::custom)
+	}
+}
+
+// a game is written as its variant, the FEN it was played from
+// and the moves that were made, in UCI notation
+#[derive(Serialize, Deserialize)]
+struct GameRecord {
+	variant: String,
+	fen: String,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	moves: Vec<String>,
+}
+
+impl GameRecord {
+	fn replay(&self, variant: Arc<dyn Variant>) -> Result<Game, String> {
+		let mut game = Game::from_fen_with_variant(&self.fen, variant)?;
+
+		for uci in self.moves.iter() {
+			let mv = game.parse_uci(uci)?;
+			game.make_move(mv)?;
+		}
+
+		Ok(game)
+	}
+}
+
+impl Serialize for Game {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		let record = GameRecord {
+			variant: self.variant().name().to_string(),
+			fen: self.start_fen(),
+			moves: self.moves_played().iter().map(|mv| self.to_uci(*mv)).collect(),
+		};
+
+		record.serialize(serializer)
+	}
+}
+
+impl<'de> Deserialize<'de> for Game {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		let record = GameRecord::deserialize(deserializer)?;
+
+		let variant = variant::from_name(&record.variant)
+			.ok_or_else(|| D::Error::custom(format!("Unknown variant '{}'", record.variant)))?;
+
+		record.replay(variant).map_err(D::Error::custom)
+	}
+}
+
+impl Game {
+	// reads a game of a variant that doesn't come with the crate,
+	// which has to have the name written in the record
+	pub fn deserialize_with_variant<'de, D: Deserializer<'de>>(
+		deserializer: D,
+		variant: Arc<dyn Variant>,
+	) -> Result<Game, D::Error> {
+		let record = GameRecord::deserialize(deserializer)?;
+
+		if record.variant != variant.name() {
+			return Err(D::Error::custom(format!(
+				"The game is {}, not {}",
+				record.variant,
+				variant.name()
+			)));
+		}
+
+		record.replay(variant).map_err(D::Error::custom)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::moves::Move;
+	use crate::variant::Crazyhouse;
+
+	#[test]
+	fn test_notation() {
+		let e4 = Square::from_str("e4").unwrap();
+		assert_eq!(serde_json::to_string(&e4).unwrap(), "\"e4\"");
+		assert_eq!(serde_json::from_str::<Square>("\"j10\"").unwrap(), Square::from_str("j10").unwrap());
+		assert!(serde_json::from_str::<Square>("\"4e\"").is_err());
+
+		assert_eq!(serde_json::to_string(&Piece::new(Color::Black, Name::Queen)).unwrap(), "\"q\"");
+		assert_eq!(serde_json::from_str::<Piece>("\"N\"").unwrap(), Piece::new(Color::White, Name::Knight));
+		assert!(serde_json::from_str::<Piece>("\"x\"").is_err());
+
+		assert_eq!(serde_json::to_string(&Color::White).unwrap(), "\"white\"");
+		assert_eq!(serde_json::to_string(&(Rank(3), File(4))).unwrap(), "[\"4\",\"e\"]");
+	}
+
+	#[test]
+	fn test_move() {
+		let game = Game::from_fen("4k3/1P6/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+
+		let promotion = game.parse_uci("b7b8n").unwrap();
+		let json = serde_json::to_string(&promotion).unwrap();
+		assert_eq!(json, r#"{"from":"b7","to":"b8","promotion":"N","kind":"normal"}"#);
+		assert_eq!(serde_json::from_str::<Move>(&json).unwrap(), promotion);
+
+		let castle = game.parse_uci("e1g1").unwrap();
+		let json = serde_json::to_string(&castle).unwrap();
+		assert_eq!(json, r#"{"from":"e1","to":"h1","promotion":null,"kind":{"castle":"king"}}"#);
+		assert_eq!(serde_json::from_str::<Move>(&json).unwrap(), castle);
+	}
+
+	#[test]
+	fn test_game() {
+		let mut game = Game::with_variant(Crazyhouse);
+		for uci in &["e2e4", "d7d5", "e4d5"] {
+			game.make_move(game.parse_uci(uci).unwrap()).unwrap();
+		}
+
+		let json = serde_json::to_string(&game).unwrap();
+		assert_eq!(
+			json,
+			r#"{"variant":"crazyhouse","fen":"rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR[] w KQkq - 0 1","moves":["e2e4","d7d5","e4d5"]}"#
+		);
+
+		let read: Game = serde_json::from_str(&json).unwrap();
+		assert_eq!(read.to_fen(), game.to_fen());
+		assert_eq!(read.variant().name(), "crazyhouse");
+		assert_eq!(read.moves_played(), game.moves_played());
+		assert_eq!(read.start_fen(), game.start_fen());
+
+		let json = r#"{"variant":"standard","fen":"rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1","moves":["e2e4","e7e5"]}"#;
+		let read: Game = serde_json::from_str(json).unwrap();
+		assert_eq!(read.to_fen(), "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2");
+
+		assert!(serde_json::from_str::<Game>(r#"{"variant":"standard","fen":"8/8 w - -","moves":["e2e5"]}"#).is_err());
+		assert!(serde_json::from_str::<Game>(r#"{"variant":"chaturanga","fen":"8/8/8/8/8/8/8/8 w - -"}"#).is_err());
+
+		let board: Board = serde_json::from_str("\"rnbqk/ppppp/5/PPPPP/RNBQK\"").unwrap();
+		assert_eq!(board.width(), 5);
+		assert_eq!(serde_json::to_string(&board).unwrap(), "\"rnbqk/ppppp/5/PPPPP/RNBQK\"");
+	}
+
+	struct NoPromotions;
+
+	impl Variant for NoPromotions {
+		fn name(&self) -> &str {
+			"nopromotions"
+		}
+
+		fn promotions(&self) -> Vec<Name> {
+			Vec::new()
+		}
+	}
+
+	#[test]
+	fn test_custom_variant_game() {
+		let mut game = Game::from_variant_fen(NoPromotions, "4k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+		game.make_move(game.parse_uci("e1d2").unwrap()).unwrap();
+
+		let json = serde_json::to_string(&game).unwrap();
+		assert!(serde_json::from_str::<Game>(&json).is_err());
+
+		let read = Game::deserialize_with_variant(&mut serde_json::Deserializer::from_str(&json), Arc::new(NoPromotions)).unwrap();
+		assert_eq!(read.variant().name(), "nopromotions");
+		assert_eq!(read.moves_played(), game.moves_played());
+		assert_eq!(read.to_fen(), game.to_fen());
+
+		let standard = Game::deserialize_with_variant(&mut serde_json::Deserializer::from_str(&json), Arc::new(Crazyhouse));
+		assert!(standard.is_err());
+	}
+}
@@ -1,7 +1,7 @@
 use crate::rank::Rank;
 use crate::file::File;
 use crate::piece::Piece;
-use std::convert::TryFrom;
+use std::str::FromStr;
 
 #[derive(Copy, Clone, PartialEq)]
 pub struct Square {
@@ -13,25 +13,31 @@ pub struct Square {
 impl Square {
 	pub fn new(rank: Rank, file: File) -> Square {
 		Square {
-			rank: rank,
-			file: file,
+			rank,
+			file,
 			piece: None,
 		}
 	}
 
+	// the same as FromStr, callers don't have to import the trait
+	#[allow(clippy::should_implement_trait, clippy::result_unit_err)]
 	pub fn from_str(square: &str) -> Result<Square, ()> {
-		if square.len() > 2 {
-			return Err(());
+		match Square::split_prefix(square) {
+			Some((square, "")) => Ok(square),
+			_ => Err(()),
 		}
+	}
 
-		let rank = Rank::try_from(square.chars().nth(1).unwrap())?;
-		let file = File::try_from(square.chars().nth(0).unwrap())?;
+	pub fn rank(&self) -> Rank {
+		self.rank
+	}
 
-		Ok(Square {
-			rank: rank,
-			file: file,
-			piece: None,
-		})
+	pub fn file(&self) -> File {
+		self.file
+	}
+
+	pub fn piece(&self) -> Option<Piece> {
+		self.piece
 	}
 
 	pub fn is_empty(&self) -> bool {
@@ -39,8 +45,66 @@ impl Square {
 	}
 }
 
+impl Square {
+	// reads a square from the start of the string and returns the rest,
+	// the file and rank can both be more than one character on large boards
+	pub(crate) fn split_prefix(value: &str) -> Option<(Square, &str)> {
+		let letters = value.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(value.len());
+		let digits = value[letters..]
+			.find(|c: char| !c.is_ascii_digit())
+			.map_or(value.len(), |i| letters + i);
+
+		let file = File::from_str(&value[..letters]).ok()?;
+		let rank = Rank::from_str(&value[letters..digits]).ok()?;
+
+		Some((Square::new(rank, file), &value[digits..]))
+	}
+}
+
+impl FromStr for Square {
+	type Err = ();
+
+	fn from_str(square: &str) -> Result<Self, Self::Err> {
+		Square::from_str(square)
+	}
+}
+
+// the lowercase algebraic notation used by FEN, SAN and UCI
+impl std::fmt::Display for Square {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}{}", self.file.to_string().to_ascii_lowercase(), self.rank)
+	}
+}
+
 impl std::fmt::Debug for Square {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-		write!(f, "{}{}", char::from(self.file), char::from(self.rank))
+		write!(f, "{}{}", self.file, self.rank)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_from_str() {
+		let square = Square::from_str("e4").unwrap();
+
+		assert_eq!(square.file, File(4));
+		assert_eq!(square.rank, Rank(3));
+		assert_eq!(Square::from_str("E4"), Ok(square));
+		assert_eq!(square.to_string(), "e4");
+	}
+
+	#[test]
+	fn test_from_str_large_board() {
+		let square = Square::from_str("j10").unwrap();
+
+		assert_eq!(square.file, File(9));
+		assert_eq!(square.rank, Rank(9));
+		assert_eq!(format!("{:?}", square), "J10");
+		assert!(Square::from_str("e").is_err());
+		assert!(Square::from_str("4e").is_err());
+		assert!(Square::from_str("e4e").is_err());
 	}
 }
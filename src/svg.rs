@@ -0,0 +1,502 @@
+use crate::board::Board;
+use crate::color::Color;
+use crate::file::File;
+use crate::game::Game;
+use crate::name::Name;
+use crate::piece::Piece;
+use crate::rank::Rank;
+use crate::square::Square;
+
+use std::fmt::Write;
+
+// all piece shapes are drawn in a 45x45 box and scaled to the square size
+const PIECE_BOX: f64 = 45.0;
+
+pub struct Svg<'a> {
+	board: &'a Board,
+	square_size: usize,
+	light: String,
+	dark: String,
+	orientation: Color,
+	coordinates: bool,
+	highlights: Vec<(Square, String)>,
+	dots: Vec<(Square, String)>,
+	arrows: Vec<(Square, Square, String)>,
+}
+
+impl<'a> Svg<'a> {
+	pub fn new(board: &'a Board) -> Svg<'a> {
+		Svg {
+			board,
+			square_size: 45,
+			light: "#f0d9b5".to_string(),
+			dark: "#b58863".to_string(),
+			orientation: Color::White,
+			coordinates: true,
+			highlights: Vec::new(),
+			dots: Vec::new(),
+			arrows: Vec::new(),
+		}
+	}
+
+	// renders the board of the game with its last move highlighted
+	pub fn from_game(game: &'a Game) -> Svg<'a> {
+		let svg = Svg::new(game.board());
+
+		match game.last_move() {
+			Some(mv) => svg.highlight(mv.from(), "#cdd26a").highlight(mv.to(), "#cdd26a"),
+			None => svg,
+		}
+	}
+
+	pub fn square_size(mut self, size: usize) -> Svg<'a> {
+		self.square_size = size;
+		self
+	}
+
+	pub fn colors(mut self, light: &str, dark: &str) -> Svg<'a> {
+		self.light = escape(light);
+		self.dark = escape(dark);
+		self
+	}
+
+	pub fn orientation(mut self, color: Color) -> Svg<'a> {
+		self.orientation = color;
+		self
+	}
+
+	pub fn coordinates(mut self, coordinates: bool) -> Svg<'a> {
+		self.coordinates = coordinates;
+		self
+	}
+
+	// squares off the board are left out
+	pub fn highlight(mut self, square: Square, color: &str) -> Svg<'a> {
+		if self.on_board(square) {
+			self.highlights.push((square, escape(color)));
+		}
+		self
+	}
+
+	pub fn arrow(mut self, from: Square, to: Square, color: &str) -> Svg<'a> {
+		if self.on_board(from) && self.on_board(to) {
+			self.arrows.push((from, to, escape(color)));
+		}
+		self
+	}
+
+	// highlights the square and marks every square its piece can move to
+	pub fn legal_moves(mut self, square: Square, color: &str) -> Svg<'a> {
+		if !self.on_board(square) || self.board.get(square).is_empty() {
+			return self;
+		}
+
+		for target in self.board.legal_moves(square) {
+			self.dots.push((target, escape(color)));
+		}
+
+		self.highlight(square, color)
+	}
+
+	pub fn render(&self) -> String {
+		let size = self.square_size as f64;
+		let margin = if self.coordinates { size / 2.0 } else { 0.0 };
+		let width = size * self.board.width() as f64 + 2.0 * margin;
+		let height = size * self.board.height() as f64 + 2.0 * margin;
+
+		let mut svg = String::new();
+
+		write!(
+			svg,
+			"<svg xmlns=\"http://www.w3.org/2000/svg\" xmlns:xlink=\"http://www.w3.org/1999/xlink\" \
+			 version=\"1.1\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">",
+			width, height, width, height,
+		)
+		.unwrap();
+
+		svg.push_str("<defs>");
+		for color in &[Color::White, Color::Black] {
+			for name in &Name::all_kinds() {
+				write!(
+					svg,
+					"<g id=\"{}\" fill=\"{}\" stroke=\"{}\" color=\"{}\" stroke-width=\"1.5\" stroke-linejoin=\"round\">{}</g>",
+					piece_id(Piece::new(*color, *name)),
+					fill_color(*color),
+					stroke_color(*color),
+					stroke_color(*color),
+					piece_shape(*name),
+				)
+				.unwrap();
+			}
+		}
+		svg.push_str("</defs>");
+
+		if self.coordinates {
+			write!(
+				svg,
+				"<rect x=\"0\" y=\"0\" width=\"{}\" height=\"{}\" fill=\"#212121\"/>",
+				width, height,
+			)
+			.unwrap();
+		}
+
+		for rank in 0..self.board.height() {
+			for file in 0..self.board.width() {
+				let square = Square::new(Rank(rank), File(file));
+				let (x, y) = self.position(square, margin);
+				let color = if (rank + file) % 2 == 0 { &self.dark } else { &self.light };
+
+				write!(
+					svg,
+					"<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+					x, y, size, size, color,
+				)
+				.unwrap();
+			}
+		}
+
+		for (square, color) in &self.highlights {
+			let (x, y) = self.position(*square, margin);
+
+			write!(
+				svg,
+				"<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" fill-opacity=\"0.5\"/>",
+				x, y, size, size, color,
+			)
+			.unwrap();
+		}
+
+		for rank in 0..self.board.height() {
+			for file in 0..self.board.width() {
+				let square = self.board.get(Square::new(Rank(rank), File(file)));
+
+				if let Some(piece) = square.piece {
+					let (x, y) = self.position(*square, margin);
+
+					write!(
+						svg,
+						"<use xlink:href=\"#{}\" transform=\"translate({}, {}) scale({})\"/>",
+						piece_id(piece),
+						x,
+						y,
+						size / PIECE_BOX,
+					)
+					.unwrap();
+				}
+			}
+		}
+
+		for (square, color) in &self.dots {
+			let (x, y) = self.center(*square, margin);
+
+			write!(
+				svg,
+				"<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\" fill-opacity=\"0.6\"/>",
+				x,
+				y,
+				size / 6.0,
+				color,
+			)
+			.unwrap();
+		}
+
+		for (from, to, color) in &self.arrows {
+			self.render_arrow(&mut svg, *from, *to, color, margin);
+		}
+
+		if self.coordinates {
+			self.render_coordinates(&mut svg, margin);
+		}
+
+		svg.push_str("</svg>");
+		svg
+	}
+
+	fn on_board(&self, square: Square) -> bool {
+		self.board.contains(square.rank.into(), square.file.into())
+	}
+
+	// the top left corner of a square in the picture
+	fn position(&self, square: Square, margin: f64) -> (f64, f64) {
+		let size = self.square_size as f64;
+		let rank: usize = square.rank.into();
+		let file: usize = square.file.into();
+
+		let (column, row) = match self.orientation {
+			Color::White => (file, self.board.height() - 1 - rank),
+			Color::Black => (self.board.width() - 1 - file, rank),
+		};
+
+		(margin + column as f64 * size, margin + row as f64 * size)
+	}
+
+	fn center(&self, square: Square, margin: f64) -> (f64, f64) {
+		let (x, y) = self.position(square, margin);
+		let half = self.square_size as f64 / 2.0;
+
+		(x + half, y + half)
+	}
+
+	fn render_arrow(&self, svg: &mut String, from: Square, to: Square, color: &str, margin: f64) {
+		let size = self.square_size as f64;
+		let (x1, y1) = self.center(from, margin);
+		let (x2, y2) = self.center(to, margin);
+
+		let length = ((x2 - x1).powi(2) + (y2 - y1).powi(2)).sqrt();
+		if length == 0.0 {
+			return;
+		}
+
+		// unit vectors along and across the arrow
+		let (dx, dy) = ((x2 - x1) / length, (y2 - y1) / length);
+		let (nx, ny) = (-dy, dx);
+
+		let head_length = size * 0.45;
+		let head_width = size * 0.3;
+		let (bx, by) = (x2 - dx * head_length, y2 - dy * head_length);
+
+		write!(
+			svg,
+			"<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\" stroke-width=\"{}\" \
+			 stroke-linecap=\"butt\" opacity=\"0.8\"/>",
+			x1,
+			y1,
+			bx,
+			by,
+			color,
+			size * 0.2,
+		)
+		.unwrap();
+
+		write!(
+			svg,
+			"<polygon points=\"{},{} {},{} {},{}\" fill=\"{}\" opacity=\"0.8\"/>",
+			x2,
+			y2,
+			bx + nx * head_width,
+			by + ny * head_width,
+			bx - nx * head_width,
+			by - ny * head_width,
+			color,
+		)
+		.unwrap();
+	}
+
+	fn render_coordinates(&self, svg: &mut String, margin: f64) {
+		let font_size = self.square_size as f64 / 3.0;
+
+		for file in 0..self.board.width() {
+			let (x, _) = self.center(Square::new(Rank(0), File(file)), margin);
+			let label = File(file);
+
+			for y in &[margin / 2.0, margin * 1.5 + self.square_size as f64 * self.board.height() as f64] {
+				write!(
+					svg,
+					"<text x=\"{}\" y=\"{}\" font-size=\"{}\" fill=\"#e5e5e5\" text-anchor=\"middle\" \
+					 dominant-baseline=\"central\" font-family=\"sans-serif\">{}</text>",
+					x, y, font_size, label,
+				)
+				.unwrap();
+			}
+		}
+
+		for rank in 0..self.board.height() {
+			let (_, y) = self.center(Square::new(Rank(rank), File(0)), margin);
+			let label = Rank(rank);
+
+			for x in &[margin / 2.0, margin * 1.5 + self.square_size as f64 * self.board.width() as f64] {
+				write!(
+					svg,
+					"<text x=\"{}\" y=\"{}\" font-size=\"{}\" fill=\"#e5e5e5\" text-anchor=\"middle\" \
+					 dominant-baseline=\"central\" font-family=\"sans-serif\">{}</text>",
+					x, y, font_size, label,
+				)
+				.unwrap();
+			}
+		}
+	}
+}
+
+fn piece_id(piece: Piece) -> String {
+	let color = match piece.color {
+		Color::White => "white",
+		Color::Black => "black",
+	};
+
+	let name = match piece.name {
+		Name::King => "king".to_string(),
+		Name::Queen => "queen".to_string(),
+		Name::Rook => "rook".to_string(),
+		Name::Bishop => "bishop".to_string(),
+		Name::Knight => "knight".to_string(),
+		Name::Pawn => "pawn".to_string(),
+		Name::Custom(kind) => kind.definition().letter().to_ascii_lowercase().to_string(),
+	};
+
+	format!("{}-{}", color, name)
+}
+
+// the colors are given by the caller and end up in attribute values
+fn escape(value: &str) -> String {
+	let mut escaped = String::new();
+
+	for c in value.chars() {
+		match c {
+			'&' => escaped.push_str("&amp;"),
+			'<' => escaped.push_str("&lt;"),
+			'>' => escaped.push_str("&gt;"),
+			'"' => escaped.push_str("&quot;"),
+			'\'' => escaped.push_str("&apos;"),
+			c => escaped.push(c),
+		}
+	}
+
+	escaped
+}
+
+fn fill_color(color: Color) -> &'static str {
+	match color {
+		Color::White => "#ffffff",
+		Color::Black => "#000000",
+	}
+}
+
+fn stroke_color(color: Color) -> &'static str {
+	match color {
+		Color::White => "#000000",
+		Color::Black => "#ffffff",
+	}
+}
+
+fn piece_shape(name: Name) -> String {
+	let shape = match name {
+		Name::King => concat!(
+			"<path d=\"M11 39h23v-3H11z\"/>",
+			"<path d=\"M13 36c-3-6-4-12 2-14 3-1 6 1 7.5 4 1.5-3 4.5-5 7.5-4 6 2 5 8 2 14z\"/>",
+			"<path d=\"M21 8h3v3h3v3h-3v6h-3v-6h-3v-3h3z\"/>",
+		),
+		Name::Queen => concat!(
+			"<path d=\"M11 39h23v-3H11z\"/>",
+			"<path d=\"M12 36L9 15l7 12-0.5-15 4.5 15 2.5-17 2.5 17 4.5-15-0.5 15 7-12-3 21z\"/>",
+			"<circle cx=\"9\" cy=\"13\" r=\"2\"/>",
+			"<circle cx=\"15.5\" cy=\"10\" r=\"2\"/>",
+			"<circle cx=\"22.5\" cy=\"8\" r=\"2\"/>",
+			"<circle cx=\"29.5\" cy=\"10\" r=\"2\"/>",
+			"<circle cx=\"36\" cy=\"13\" r=\"2\"/>",
+		),
+		Name::Rook => concat!(
+			"<path d=\"M9 39h27v-3H9z\"/>",
+			"<path d=\"M12 36v-4h21v4z\"/>",
+			"<path d=\"M14 32l1-15h15l1 15z\"/>",
+			"<path d=\"M11 17V9h4v3h5V9h5v3h5V9h4v8z\"/>",
+		),
+		Name::Bishop => concat!(
+			"<path d=\"M11 39h23v-3H11z\"/>",
+			"<path d=\"M15 36c1-3 2-6 3-8h9c1 2 2 5 3 8z\"/>",
+			"<path d=\"M17 28c-4-5-3-10 5.5-16 8.5 6 9.5 11 5.5 16z\"/>",
+			"<circle cx=\"22.5\" cy=\"9\" r=\"2.5\"/>",
+			"<path d=\"M22.5 16v7M19 19.5h7\" fill=\"none\"/>",
+		),
+		Name::Knight => concat!(
+			"<path d=\"M12 39h22v-3H12z\"/>",
+			"<path d=\"M15 36c0-7 8-10 7-17-2 3-4 4-6 4l-4 3-3-2c1-4 4-8 7-10l-1-4 3 1 1-3c4 0 8 5 10 9 \
+			 3 6 4 12 4 19z\"/>",
+			"<circle cx=\"17\" cy=\"15\" r=\"1\"/>",
+		),
+		Name::Pawn => concat!(
+			"<path d=\"M22.5 9a4 4 0 0 0-3.2 6.4 6 6 0 0 0 0 8.6c-3.2 1.3-5.8 4.9-5.8 12h18c0-7.1-2.6-10.7-5.8-12a6 6 0 0 0 0-8.6A4 4 0 0 0 22.5 9z\"/>",
+			"<path d=\"M12 39h21v-3H12z\"/>",
+		),
+		// pieces without a drawing of their own are shown with their letter
+		Name::Custom(kind) => {
+			return format!(
+				"<circle cx=\"22.5\" cy=\"22.5\" r=\"16\"/>\
+				 <text x=\"22.5\" y=\"23.5\" font-size=\"20\" stroke=\"none\" fill=\"currentColor\" \
+				 text-anchor=\"middle\" dominant-baseline=\"central\" font-family=\"sans-serif\">{}</text>",
+				kind.definition().letter()
+			)
+		},
+	};
+
+	shape.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_render_initial_board() {
+		let board = Board::new();
+		let svg = Svg::new(&board).render();
+
+		assert!(svg.starts_with("<svg"));
+		assert!(svg.ends_with("</svg>"));
+		assert_eq!(svg.matches("<use ").count(), 32);
+		assert_eq!(svg.matches("xlink:href=\"#white-pawn\"").count(), 8);
+		assert_eq!(svg.matches("xlink:href=\"#black-king\"").count(), 1);
+	}
+
+	#[test]
+	fn test_render_custom_piece() {
+		let kind = crate::fairy::PieceKind::archbishop();
+		let mut board = Board::new();
+		board.get_mut(Square::from_str("C1").unwrap()).piece = Some(Piece::new(Color::White, Name::Custom(kind)));
+
+		let svg = Svg::new(&board).render();
+
+		assert!(svg.contains("<g id=\"white-a\""));
+		assert_eq!(svg.matches("xlink:href=\"#white-a\"").count(), 1);
+	}
+
+	#[test]
+	fn test_orientation() {
+		let board = Board::new();
+		let a1 = Square::from_str("A1").unwrap();
+
+		let white = Svg::new(&board).coordinates(false).highlight(a1, "red").render();
+		let black = Svg::new(&board)
+			.coordinates(false)
+			.orientation(Color::Black)
+			.highlight(a1, "red")
+			.render();
+
+		assert!(white.contains("<rect x=\"0\" y=\"315\" width=\"45\" height=\"45\" fill=\"red\""));
+		assert!(black.contains("<rect x=\"315\" y=\"0\" width=\"45\" height=\"45\" fill=\"red\""));
+	}
+
+	#[test]
+	fn test_legal_moves_overlay() {
+		let board = Board::new();
+		let knight = Square::from_str("B1").unwrap();
+
+		let svg = Svg::new(&board).legal_moves(knight, "#15781b").render();
+
+		assert_eq!(svg.matches("fill-opacity=\"0.6\"").count(), 2);
+	}
+
+	#[test]
+	fn test_escape_colors() {
+		let board = Board::new();
+		let a1 = Square::from_str("A1").unwrap();
+
+		let svg = Svg::new(&board).colors("\"/><script>alert(1)</script>", "#b58863").highlight(a1, "red' onload='x").render();
+
+		assert!(!svg.contains("<script>"));
+		assert!(svg.contains("fill=\"&quot;/&gt;&lt;script&gt;alert(1)&lt;/script&gt;\""));
+		assert!(svg.contains("fill=\"red&apos; onload=&apos;x\""));
+	}
+
+	#[test]
+	fn test_off_board_squares() {
+		let board = Board::new();
+		let a1 = Square::from_str("A1").unwrap();
+		let i9 = Square::new(Rank(8), File(8));
+
+		let svg = Svg::new(&board).highlight(i9, "red").arrow(a1, i9, "green").legal_moves(i9, "blue").render();
+
+		assert!(!svg.contains("red"));
+		assert!(!svg.contains("green"));
+		assert!(!svg.contains("blue"));
+	}
+}
@@ -0,0 +1,1294 @@
+use crate::color::Color;
+use crate::game::Game;
+use crate::moves::Move;
+use crate::name::Name;
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+const WDL_MAGIC: [u8; 4] = [0x71, 0xe8, 0x23, 0x5d];
+const DTZ_MAGIC: [u8; 4] = [0xd7, 0x66, 0x0c, 0xa5];
+
+// the flags in front of the compressed data of a table
+const STM: u8 = 1;
+const MAPPED: u8 = 2;
+const WIN_PLIES: u8 = 4;
+const LOSS_PLIES: u8 = 8;
+const WIDE: u8 = 16;
+const SINGLE_VALUE: u8 = 128;
+
+// the result with perfect play, cursed wins and blessed losses
+// are only won or lost when the 50 move rule is ignored
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Wdl {
+	Loss,
+	BlessedLoss,
+	Draw,
+	CursedWin,
+	Win,
+}
+
+impl Wdl {
+	fn from_value(value: i32) -> Wdl {
+		match value {
+			-2 => Wdl::Loss,
+			-1 => Wdl::BlessedLoss,
+			0 => Wdl::Draw,
+			1 => Wdl::CursedWin,
+			_ => Wdl::Win,
+		}
+	}
+
+	fn value(self) -> i32 {
+		match self {
+			Wdl::Loss => -2,
+			Wdl::BlessedLoss => -1,
+			Wdl::Draw => 0,
+			Wdl::CursedWin => 1,
+			Wdl::Win => 2,
+		}
+	}
+
+	// the result for the other side
+	pub fn opposite(self) -> Wdl {
+		Wdl::from_value(-self.value())
+	}
+}
+
+// what a probe found out besides the result
+#[derive(Copy, Clone, PartialEq)]
+enum State {
+	Ok,
+	// the best move resets the 50 move counter, so the DTZ table can't be trusted
+	ZeroingBestMove,
+	// the DTZ table only has the positions with the other side to move
+	ChangeSide,
+}
+
+// a directory of Syzygy tables (.rtbw for WDL and .rtbz for DTZ),
+// the tables are read the first time they are needed
+pub struct Tablebase {
+	directory: PathBuf,
+	tables: Mutex<HashMap<String, Arc<Table>>>,
+}
+
+impl Tablebase {
+	pub fn open<P: AsRef<Path>>(directory: P) -> Result<Tablebase, String> {
+		let directory = directory.as_ref().to_path_buf();
+
+		if !directory.is_dir() {
+			return Err(format!("{} is not a directory", directory.display()));
+		}
+
+		Ok(Tablebase {
+			directory,
+			tables: Mutex::new(HashMap::new()),
+		})
+	}
+
+	pub fn probe_wdl(&self, game: &Game) -> Result<Wdl, String> {
+		check_position(game)?;
+
+		let (wdl, _) = self.search(game, false)?;
+		Ok(Wdl::from_value(wdl))
+	}
+
+	// the distance to the next capture or pawn move (which resets the 50 move
+	// counter) in plies, positive when winning and negative when losing,
+	// with 100 added for cursed wins and blessed losses. It can be off by one,
+	// a position that is 5 plies from zeroing can give 6 but never 4
+	pub fn probe_dtz(&self, game: &Game) -> Result<i32, String> {
+		check_position(game)?;
+
+		self.dtz(game)
+	}
+
+	// every legal move with the DTZ for the side that plays it
+	pub fn root_moves(&self, game: &Game) -> Result<Vec<(Move, i32)>, String> {
+		check_position(game)?;
+
+		let mut moves = Vec::new();
+
+		for mv in game.legal_moves() {
+			let zeroing = is_zeroing(game, mv);
+			let mut after = game.clone();
+			after.play(mv);
+
+			let mut dtz = if zeroing {
+				let (wdl, _) = self.search(&after, false)?;
+				dtz_before_zeroing(-wdl)
+			} else {
+				let dtz = -self.dtz(&after)?;
+				dtz + dtz.signum()
+			};
+
+			if dtz == 2 && after.is_checkmate() {
+				dtz = 1;
+			}
+
+			moves.push((mv, dtz));
+		}
+
+		Ok(moves)
+	}
+
+	// keeps the result of the position: the winning side goes for the
+	// quickest zeroing move and the losing side holds out as long as possible
+	pub fn best_move(&self, game: &Game) -> Result<Option<Move>, String> {
+		let rank = |dtz: i32| match dtz {
+			0 => 0,
+			dtz if dtz > 0 => 2000 - dtz,
+			dtz => -2000 - dtz,
+		};
+
+		let moves = self.root_moves(game)?;
+		let best = moves.iter().map(|(_, dtz)| rank(*dtz)).max();
+
+		Ok(moves.into_iter().find(|(_, dtz)| Some(rank(*dtz)) == best).map(|(mv, _)| mv))
+	}
+
+	// the captures (and pawn moves for DTZ) are searched first, since the
+	// tables may hold any value for positions where one of them is best
+	fn search(&self, game: &Game, zeroing_moves: bool) -> Result<(i32, State), String> {
+		let moves = game.legal_moves();
+
+		// mate and stalemate don't need the tables, a mated side is zero plies from the end
+		if moves.is_empty() {
+			if game.is_check() {
+				return Ok((-2, State::ZeroingBestMove));
+			}
+
+			return Ok((0, State::Ok));
+		}
+
+		let mut best = -2;
+		let mut searched = 0;
+
+		for mv in moves.iter() {
+			let zeroing = game.is_capture(*mv) || (zeroing_moves && is_pawn_move(game, *mv));
+			if !zeroing {
+				continue;
+			}
+
+			searched += 1;
+
+			let mut after = game.clone();
+			after.play(*mv);
+			let (value, _) = self.search(&after, false)?;
+			let value = -value;
+
+			if value > best {
+				best = value;
+
+				if value == 2 {
+					return Ok((value, State::ZeroingBestMove));
+				}
+			}
+		}
+
+		let no_more_moves = searched > 0 && searched == moves.len();
+		let value = if no_more_moves {
+			best
+		} else {
+			self.probe_table(game, true, 0)?.0
+		};
+
+		if best >= value {
+			let state = if best > 0 || no_more_moves {
+				State::ZeroingBestMove
+			} else {
+				State::Ok
+			};
+
+			return Ok((best, state));
+		}
+
+		Ok((value, State::Ok))
+	}
+
+	fn dtz(&self, game: &Game) -> Result<i32, String> {
+		let (wdl, state) = self.search(game, true)?;
+
+		// draws aren't stored in the DTZ tables
+		if wdl == 0 {
+			return Ok(0);
+		}
+
+		if state == State::ZeroingBestMove {
+			return Ok(dtz_before_zeroing(wdl));
+		}
+
+		let (dtz, state) = self.probe_table(game, false, wdl)?;
+		if state != State::ChangeSide {
+			let cursed = if wdl.abs() == 1 { 100 } else { 0 };
+			return Ok((dtz + cursed) * wdl.signum());
+		}
+
+		// the table has the other side to move, so look one move ahead
+		let mut best = None;
+
+		for mv in game.legal_moves() {
+			let zeroing = is_zeroing(game, mv);
+			let mut after = game.clone();
+			after.play(mv);
+
+			let mut dtz = if zeroing {
+				let (wdl, _) = self.search(&after, false)?;
+				-dtz_before_zeroing(wdl)
+			} else {
+				-self.dtz(&after)?
+			};
+
+			if dtz == 1 && after.is_checkmate() {
+				best = Some(1);
+			}
+
+			if !zeroing {
+				dtz += dtz.signum();
+			}
+
+			if dtz.signum() == wdl.signum() && best.is_none_or(|best| dtz < best) {
+				best = Some(dtz);
+			}
+		}
+
+		// no moves left means the side to move is mated
+		Ok(best.unwrap_or(-1))
+	}
+
+	// the value straight from a WDL (-2 to 2) or a DTZ table
+	fn probe_table(&self, game: &Game, wdl_table: bool, wdl: i32) -> Result<(i32, State), String> {
+		let white = material(game, Color::White);
+		let black = material(game, Color::Black);
+
+		// there are no tables for two bare kings
+		if white == "K" && black == "K" {
+			return Ok((0, State::Ok));
+		}
+
+		let suffix = if wdl_table { "rtbw" } else { "rtbz" };
+		let straight = format!("{}v{}", white, black);
+		let swapped = format!("{}v{}", black, white);
+
+		let (table, swap) = match self.table(&straight, suffix)? {
+			Some(table) => (table, false),
+			None => match self.table(&swapped, suffix)? {
+				Some(table) => (table, true),
+				None => return Err(format!("There is no table for {}", straight)),
+			},
+		};
+
+		// the table is read from the point of view of the side named first,
+		// which for a symmetric table is the side to move
+		let flip = swap || (table.symmetric && game.turn() == Color::Black);
+		let side = usize::from(flip != (game.turn() == Color::Black));
+
+		let pieces = board_pieces(game);
+		let flip_color = if flip { 8 } else { 0 };
+		let flip_square = if flip { 56 } else { 0 };
+		let squares_of = |code: u8| -> Vec<usize> {
+			pieces.iter().filter(|(_, piece)| *piece == code ^ flip_color).map(|(square, _)| square ^ flip_square).collect()
+		};
+
+		let mut squares = Vec::new();
+		let mut file = 0;
+
+		// the table is split up by the file of the leading pawn
+		if table.has_pawns {
+			squares = squares_of(table.subtables[0][0].pieces[0]);
+
+			let indices = indices();
+			let lead = (0..squares.len()).max_by_key(|i| indices.map_pawns[squares[*i]]).unwrap();
+			squares.swap(0, lead);
+
+			file = (squares[0] % 8).min(7 - squares[0] % 8);
+		}
+
+		// symmetric tables without pawns look the same for either side to move
+		let flags = table.subtables[file][0].pairs.flags;
+		let either_side = table.symmetric && !table.has_pawns;
+		if !wdl_table && !either_side && flags & STM != side as u8 {
+			return Ok((0, State::ChangeSide));
+		}
+
+		let subtable = &table.subtables[file][if wdl_table { side } else { 0 }];
+
+		while squares.len() < subtable.pieces.len() {
+			let found = squares_of(subtable.pieces[squares.len()]);
+			if found.is_empty() {
+				return Err(format!("The table for {} doesn't match the position", straight));
+			}
+
+			squares.extend(found);
+		}
+
+		let index = table.index(subtable, &mut squares);
+		let value = i32::from(table.decompress(&subtable.pairs, index)?);
+
+		if wdl_table {
+			return Ok((value - 2, State::Ok));
+		}
+
+		Ok((table.map_dtz(subtable, value, wdl)?, State::Ok))
+	}
+
+	fn table(&self, name: &str, suffix: &str) -> Result<Option<Arc<Table>>, String> {
+		let file_name = format!("{}.{}", name, suffix);
+		let mut tables = self.tables.lock().unwrap();
+
+		if let Some(table) = tables.get(&file_name) {
+			return Ok(Some(table.clone()));
+		}
+
+		let path = self.directory.join(&file_name);
+		if !path.is_file() {
+			return Ok(None);
+		}
+
+		let bytes = std::fs::read(&path).map_err(|error| error.to_string())?;
+		let table = Arc::new(Table::parse(bytes, name, suffix == "rtbw")?);
+		tables.insert(file_name, table.clone());
+
+		Ok(Some(table))
+	}
+}
+
+// the tables only cover standard chess without castling
+fn check_position(game: &Game) -> Result<(), String> {
+	if game.variant().name() != "standard" || game.board().width() != 8 || game.board().height() != 8 {
+		return Err("Syzygy tables are only made for standard chess".to_string());
+	}
+
+	if !game.castling_rights().is_empty() {
+		return Err("Syzygy tables don't have positions where castling is possible".to_string());
+	}
+
+	Ok(())
+}
+
+fn is_pawn_move(game: &Game, mv: Move) -> bool {
+	game.board().get(mv.from).piece.is_some_and(|piece| piece.name == Name::Pawn)
+}
+
+fn is_zeroing(game: &Game, mv: Move) -> bool {
+	game.is_capture(mv) || is_pawn_move(game, mv)
+}
+
+fn dtz_before_zeroing(wdl: i32) -> i32 {
+	match wdl {
+		2 => 1,
+		1 => 101,
+		-1 => -101,
+		-2 => -1,
+		_ => 0,
+	}
+}
+
+const PIECE_LETTERS: [(Name, char); 6] = [
+	(Name::King, 'K'),
+	(Name::Queen, 'Q'),
+	(Name::Rook, 'R'),
+	(Name::Bishop, 'B'),
+	(Name::Knight, 'N'),
+	(Name::Pawn, 'P'),
+];
+
+// the pieces of one side as they are written in table names, e.g. KRP
+fn material(game: &Game, color: Color) -> String {
+	let mut material = String::new();
+
+	for (name, letter) in PIECE_LETTERS.iter() {
+		for square in game.board().squares() {
+			if square.piece.is_some_and(|piece| piece.color == color && piece.name == *name) {
+				material.push(*letter);
+			}
+		}
+	}
+
+	material
+}
+
+// the square (0 for a1 to 63 for h8) and the code of every piece,
+// codes are 1 to 6 for a white pawn to king and 9 to 14 for black
+fn board_pieces(game: &Game) -> Vec<(usize, u8)> {
+	let mut pieces = Vec::new();
+
+	for square in game.board().squares() {
+		let piece = match square.piece {
+			Some(piece) => piece,
+			None => continue,
+		};
+
+		let code = match piece.name {
+			Name::Pawn => 1,
+			Name::Knight => 2,
+			Name::Bishop => 3,
+			Name::Rook => 4,
+			Name::Queen => 5,
+			Name::King => 6,
+			Name::Custom(_) => continue,
+		};
+
+		let color = match piece.color {
+			Color::White => 0,
+			Color::Black => 8,
+		};
+
+		pieces.push((usize::from(square.rank) * 8 + usize::from(square.file), code | color));
+	}
+
+	pieces.sort();
+	pieces
+}
+
+// counts of a piece code, for the material in a table name
+fn piece_code(letter: char) -> Option<u8> {
+	PIECE_LETTERS.iter().position(|(_, l)| *l == letter).map(|i| 6 - i as u8)
+}
+
+// the tables that positions are turned into indices with
+struct Indices {
+	binomial: [[u64; 64]; 7],
+	map_pawns: [usize; 64],
+	lead_pawn_index: [[u64; 64]; 6],
+	lead_pawns_size: [[u64; 4]; 6],
+	map_b1h1h7: [u64; 64],
+	map_a1d1d4: [usize; 64],
+	map_kk: [[u64; 64]; 10],
+}
+
+// above (positive) or below (negative) the a1-h8 diagonal
+fn off_diagonal(square: usize) -> isize {
+	(square / 8) as isize - (square % 8) as isize
+}
+
+#[allow(clippy::needless_range_loop)]
+fn indices() -> &'static Indices {
+	static INDICES: OnceLock<Indices> = OnceLock::new();
+
+	INDICES.get_or_init(|| {
+		let mut binomial = [[0; 64]; 7];
+		binomial[0][0] = 1;
+		for n in 1..64 {
+			for k in 0..7.min(n + 1) {
+				binomial[k][n] =
+					if k > 0 { binomial[k - 1][n - 1] } else { 0 } + if k < n { binomial[k][n - 1] } else { 0 };
+			}
+		}
+
+		let mut map_b1h1h7 = [0; 64];
+		let mut code = 0;
+		for square in 0..64 {
+			if off_diagonal(square) < 0 {
+				map_b1h1h7[square] = code;
+				code += 1;
+			}
+		}
+
+		// the a1-d1-d4 triangle, the squares below the diagonal first
+		let mut map_a1d1d4 = [0; 64];
+		let mut code = 0;
+		for below in &[true, false] {
+			for square in 0..28 {
+				if square % 8 <= 3 && (off_diagonal(square) < 0) == *below && off_diagonal(square) <= 0 {
+					map_a1d1d4[square] = code;
+					code += 1;
+				}
+			}
+		}
+
+		// the 462 ways to place two kings with the first one in the triangle,
+		// with both kings on the diagonal coming last
+		let mut map_kk = [[0; 64]; 10];
+		let mut on_diagonal = Vec::new();
+		let mut code = 0;
+		for index in 0..10 {
+			for first in 0..28 {
+				if map_a1d1d4[first] != index || (index == 0 && first != 1) {
+					continue;
+				}
+
+				for second in 0..64 {
+					let distance = |a: usize, b: usize| (a as isize - b as isize).abs();
+					if distance(first / 8, second / 8) <= 1 && distance(first % 8, second % 8) <= 1 {
+						continue;
+					}
+
+					if off_diagonal(first) == 0 && off_diagonal(second) > 0 {
+						continue;
+					}
+
+					if off_diagonal(first) == 0 && off_diagonal(second) == 0 {
+						on_diagonal.push((index, second));
+					} else {
+						map_kk[index][second] = code;
+						code += 1;
+					}
+				}
+			}
+		}
+
+		for (index, second) in on_diagonal {
+			map_kk[index][second] = code;
+			code += 1;
+		}
+
+		// the pawns nearest to the edge and the first rank come first
+		let mut map_pawns = [0; 64];
+		let mut lead_pawn_index = [[0; 64]; 6];
+		let mut lead_pawns_size = [[0; 4]; 6];
+		let mut available: usize = 47;
+		for count in 1..6 {
+			for file in 0..4 {
+				let mut index = 0;
+
+				for rank in 1..7 {
+					let square = rank * 8 + file;
+
+					if count == 1 {
+						map_pawns[square] = available;
+						map_pawns[square ^ 7] = available - 1;
+						available = available.saturating_sub(2);
+					}
+
+					lead_pawn_index[count][square] = index;
+					index += binomial[count - 1][map_pawns[square]];
+				}
+
+				lead_pawns_size[count][file] = index;
+			}
+		}
+
+		Indices {
+			binomial,
+			map_pawns,
+			lead_pawn_index,
+			lead_pawns_size,
+			map_b1h1h7,
+			map_a1d1d4,
+			map_kk,
+		}
+	})
+}
+
+// the canonical Huffman code the values of a table are compressed with,
+// the positions are byte offsets into the table file
+struct Pairs {
+	flags: u8,
+	// the value of every position when SINGLE_VALUE is set
+	min_length: usize,
+	block_size: usize,
+	span: u64,
+	blocks: usize,
+	lowest_symbols: usize,
+	base: Vec<u64>,
+	symbol_lengths: Vec<u8>,
+	tree: usize,
+	sparse_index: usize,
+	sparse_index_size: usize,
+	block_lengths: usize,
+	block_lengths_size: usize,
+	data: usize,
+}
+
+struct Subtable {
+	pieces: Vec<u8>,
+	group_lengths: Vec<usize>,
+	// the factor of every group, the last one is the number of positions
+	group_factors: Vec<u64>,
+	pairs: Pairs,
+	map_index: [usize; 4],
+}
+
+struct Table {
+	bytes: Vec<u8>,
+	has_pawns: bool,
+	symmetric: bool,
+	unique_pieces: bool,
+	pawns: [usize; 2],
+	// per file of the leading pawn and per side to move
+	subtables: Vec<Vec<Subtable>>,
+	map: usize,
+}
+
+fn read_u16(bytes: &[u8], at: usize) -> Result<u16, String> {
+	match bytes.get(at..at + 2) {
+		Some(b) => Ok(u16::from_le_bytes([b[0], b[1]])),
+		None => Err("The table is cut off".to_string()),
+	}
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> Result<u32, String> {
+	match bytes.get(at..at + 4) {
+		Some(b) => Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]])),
+		None => Err("The table is cut off".to_string()),
+	}
+}
+
+fn read_u8(bytes: &[u8], at: usize) -> Result<u8, String> {
+	bytes.get(at).copied().ok_or("The table is cut off".to_string())
+}
+
+impl Table {
+	fn parse(bytes: Vec<u8>, name: &str, wdl: bool) -> Result<Table, String> {
+		let magic = if wdl { WDL_MAGIC } else { DTZ_MAGIC };
+		if bytes.get(0..4) != Some(&magic[..]) {
+			return Err(format!("{} is not a Syzygy table", name));
+		}
+
+		let (white, black) = name.split_once('v').ok_or(format!("{} is not a table name", name))?;
+		let mut counts = [0; 16];
+		for (letters, color) in &[(white, 0), (black, 8)] {
+			for letter in letters.chars() {
+				let code = piece_code(letter).ok_or(format!("{} is not a table name", name))?;
+				counts[usize::from(code + color)] += 1;
+			}
+		}
+
+		let has_pawns = counts[1] + counts[9] > 0;
+		let unique_pieces = (2..6).chain(10..14).any(|code| counts[code] == 1);
+
+		// the side with fewer pawns leads, because that compresses better
+		let white_leads = counts[9] == 0 || (counts[1] > 0 && counts[9] >= counts[1]);
+		let pawns = if white_leads { [counts[1], counts[9]] } else { [counts[9], counts[1]] };
+
+		let mut table = Table {
+			bytes: Vec::new(),
+			has_pawns,
+			symmetric: white == black,
+			unique_pieces,
+			pawns,
+			subtables: Vec::new(),
+			map: 0,
+		};
+
+		let piece_count = white.len() + black.len();
+		let sides = if wdl && !table.symmetric { 2 } else { 1 };
+		let files = if has_pawns { 4 } else { 1 };
+		let both_pawns = has_pawns && pawns[1] > 0;
+
+		let mut at = 5;
+		let mut layouts = Vec::new();
+
+		for file in 0..files {
+			let order = read_u8(&bytes, at)?;
+			let order_pawns = if both_pawns { read_u8(&bytes, at + 1)? } else { 0xff };
+			at += 1 + usize::from(both_pawns);
+
+			let mut file_layouts = Vec::new();
+			for side in 0..sides {
+				let nibble = |byte: u8| if side == 0 { byte & 0xf } else { byte >> 4 };
+				let pieces = (0..piece_count).map(|i| bytes.get(at + i).map(|byte| nibble(*byte))).collect::<Option<Vec<u8>>>();
+				let pieces = pieces.ok_or("The table is cut off".to_string())?;
+
+				let order = [usize::from(nibble(order)), usize::from(nibble(order_pawns))];
+				let (lengths, factors) = table.groups(&pieces, order, file);
+				file_layouts.push((pieces, lengths, factors));
+			}
+
+			at += piece_count;
+			layouts.push(file_layouts);
+		}
+
+		at += at & 1;
+
+		let mut pairs = Vec::new();
+		for file_layouts in layouts.iter() {
+			let mut file_pairs = Vec::new();
+			for (_, _, factors) in file_layouts.iter() {
+				file_pairs.push(Table::pairs(&bytes, &mut at, *factors.last().unwrap(), wdl)?);
+			}
+			pairs.push(file_pairs);
+		}
+
+		// DTZ values can be mapped through a list per result
+		let mut map_indices = vec![[0; 4]; files];
+		if !wdl {
+			table.map = at;
+
+			for (file, file_pairs) in pairs.iter().enumerate() {
+				let flags = file_pairs[0].flags;
+				if flags & MAPPED == 0 {
+					continue;
+				}
+
+				if flags & WIDE != 0 {
+					at += at & 1;
+					for map_index in map_indices[file].iter_mut() {
+						*map_index = (at - table.map) / 2 + 1;
+						at += 2 + 2 * usize::from(read_u16(&bytes, at)?);
+					}
+				} else {
+					for map_index in map_indices[file].iter_mut() {
+						*map_index = at - table.map + 1;
+						at += 1 + usize::from(read_u8(&bytes, at)?);
+					}
+				}
+			}
+
+			at += at & 1;
+		}
+
+		for file_pairs in pairs.iter_mut() {
+			for pairs in file_pairs.iter_mut() {
+				pairs.sparse_index = at;
+				at += pairs.sparse_index_size * 6;
+			}
+		}
+
+		for file_pairs in pairs.iter_mut() {
+			for pairs in file_pairs.iter_mut() {
+				pairs.block_lengths = at;
+				at += pairs.block_lengths_size * 2;
+			}
+		}
+
+		for file_pairs in pairs.iter_mut() {
+			for pairs in file_pairs.iter_mut() {
+				at = (at + 0x3f) & !0x3f;
+				pairs.data = at;
+				at += pairs.blocks * pairs.block_size;
+
+				if pairs.flags & SINGLE_VALUE == 0 && at > bytes.len() {
+					return Err("The table is cut off".to_string());
+				}
+			}
+		}
+
+		for ((file_layouts, file_pairs), map_index) in layouts.into_iter().zip(pairs).zip(map_indices) {
+			let subtables = file_layouts
+				.into_iter()
+				.zip(file_pairs)
+				.map(|((pieces, lengths, factors), pairs)| Subtable {
+					pieces,
+					group_lengths: lengths,
+					group_factors: factors,
+					pairs,
+					map_index,
+				})
+				.collect();
+
+			table.subtables.push(subtables);
+		}
+
+		table.bytes = bytes;
+		Ok(table)
+	}
+
+	// splits the pieces into groups that are encoded together, and works out
+	// what every group is multiplied by in the order the table gives
+	fn groups(&self, pieces: &[u8], order: [usize; 2], file: usize) -> (Vec<usize>, Vec<u64>) {
+		let indices = indices();
+		let mut first = if self.has_pawns {
+			0
+		} else if self.unique_pieces {
+			3
+		} else {
+			2
+		};
+
+		let mut lengths = vec![1];
+		for i in 1..pieces.len() {
+			first -= 1;
+			if first > 0 || pieces[i] == pieces[i - 1] {
+				*lengths.last_mut().unwrap() += 1;
+			} else {
+				lengths.push(1);
+			}
+		}
+
+		let both_pawns = self.has_pawns && self.pawns[1] > 0;
+		let mut next = if both_pawns { 2 } else { 1 };
+		let mut free = 64 - lengths[0] - if both_pawns { lengths[1] } else { 0 };
+		let mut factors = vec![0; lengths.len() + 1];
+		let mut factor: u64 = 1;
+		let mut k = 0;
+
+		while next < lengths.len() || k == order[0] || k == order[1] {
+			if k == order[0] {
+				factors[0] = factor;
+				factor *= if self.has_pawns {
+					indices.lead_pawns_size[lengths[0]][file]
+				} else if self.unique_pieces {
+					31332
+				} else {
+					462
+				};
+			} else if k == order[1] {
+				factors[1] = factor;
+				factor *= indices.binomial[lengths[1]][48 - lengths[0]];
+			} else {
+				factors[next] = factor;
+				factor *= indices.binomial[lengths[next]][free];
+				free -= lengths[next];
+				next += 1;
+			}
+
+			k += 1;
+		}
+
+		factors[lengths.len()] = factor;
+		(lengths, factors)
+	}
+
+	fn pairs(bytes: &[u8], at: &mut usize, size: u64, wdl: bool) -> Result<Pairs, String> {
+		let flags = read_u8(bytes, *at)?;
+
+		let mut pairs = Pairs {
+			flags,
+			min_length: 0,
+			block_size: 0,
+			span: 0,
+			blocks: 0,
+			lowest_symbols: 0,
+			base: Vec::new(),
+			symbol_lengths: Vec::new(),
+			tree: 0,
+			sparse_index: 0,
+			sparse_index_size: 0,
+			block_lengths: 0,
+			block_lengths_size: 0,
+			data: 0,
+		};
+
+		if flags & SINGLE_VALUE != 0 {
+			pairs.min_length = if wdl { usize::from(read_u8(bytes, *at + 1)?) } else { 0 };
+			*at += 2;
+			return Ok(pairs);
+		}
+
+		pairs.block_size = 1 << read_u8(bytes, *at + 1)?;
+		pairs.span = 1 << read_u8(bytes, *at + 2)?;
+		pairs.sparse_index_size = size.div_ceil(pairs.span) as usize;
+		let padding = usize::from(read_u8(bytes, *at + 3)?);
+		pairs.blocks = read_u32(bytes, *at + 4)? as usize;
+		pairs.block_lengths_size = pairs.blocks + padding;
+
+		let max_length = usize::from(read_u8(bytes, *at + 8)?);
+		pairs.min_length = usize::from(read_u8(bytes, *at + 9)?);
+		if pairs.min_length == 0 || max_length < pairs.min_length {
+			return Err("The table has an invalid code".to_string());
+		}
+
+		let lengths = max_length - pairs.min_length + 1;
+		let lowest_symbols = *at + 10;
+		pairs.lowest_symbols = lowest_symbols;
+		let lowest = |i: usize| read_u16(bytes, lowest_symbols + 2 * i).map(u64::from);
+
+		// the lowest code of every length, shifted all the way to the left
+		pairs.base = vec![0; lengths];
+		for i in (0..lengths - 1).rev() {
+			pairs.base[i] = pairs.base[i + 1].wrapping_add(lowest(i)?).wrapping_sub(lowest(i + 1)?) / 2;
+		}
+
+		for (i, base) in pairs.base.iter_mut().enumerate() {
+			*base = base.checked_shl((64 - i - pairs.min_length) as u32).unwrap_or(0);
+		}
+
+		let symbols_at = pairs.lowest_symbols + 2 * lengths;
+		let symbols = usize::from(read_u16(bytes, symbols_at)?);
+		pairs.tree = symbols_at + 2;
+		*at = pairs.tree + 3 * symbols + (symbols & 1);
+
+		if bytes.len() < *at {
+			return Err("The table is cut off".to_string());
+		}
+
+		// the number of values every symbol stands for, minus one
+		pairs.symbol_lengths = vec![0; symbols];
+		let mut visited = vec![false; symbols];
+		for symbol in 0..symbols {
+			if !visited[symbol] {
+				pairs.symbol_lengths[symbol] = symbol_length(bytes, &mut pairs, symbol, &mut visited)?;
+			}
+		}
+
+		Ok(pairs)
+	}
+
+	// the position of the pieces, in the order of the table, as an index
+	fn index(&self, subtable: &Subtable, squares: &mut [usize]) -> u64 {
+		let indices = indices();
+		let lengths = &subtable.group_lengths;
+
+		// the leading piece goes onto the a to d files, and without
+		// pawns also onto the first four ranks and below the diagonal
+		if squares[0] % 8 > 3 {
+			for square in squares.iter_mut() {
+				*square ^= 7;
+			}
+		}
+
+		let mut index;
+
+		if self.has_pawns {
+			let lead = lengths[0];
+			index = indices.lead_pawn_index[lead][squares[0]];
+
+			squares[1..lead].sort_by_key(|square| indices.map_pawns[*square]);
+			for (i, square) in squares.iter().enumerate().take(lead).skip(1) {
+				index += indices.binomial[i][indices.map_pawns[*square]];
+			}
+		} else {
+			if squares[0] / 8 > 3 {
+				for square in squares.iter_mut() {
+					*square ^= 56;
+				}
+			}
+
+			if let Some(i) = (0..lengths[0]).find(|i| off_diagonal(squares[*i]) != 0) {
+				if off_diagonal(squares[i]) > 0 {
+					for square in squares[i..].iter_mut() {
+						*square = (*square >> 3) | ((*square & 7) << 3);
+					}
+				}
+			}
+
+			if self.unique_pieces {
+				let adjust1 = (squares[1] > squares[0]) as u64;
+				let adjust2 = (squares[2] > squares[0]) as u64 + (squares[2] > squares[1]) as u64;
+				let [s0, s1, s2] = [squares[0] as u64, squares[1] as u64, squares[2] as u64];
+				let rank = |square: u64| square / 8;
+
+				index = if off_diagonal(squares[0]) != 0 {
+					(indices.map_a1d1d4[squares[0]] as u64 * 63 + (s1 - adjust1)) * 62 + s2 - adjust2
+				} else if off_diagonal(squares[1]) != 0 {
+					(6 * 63 + rank(s0) * 28 + indices.map_b1h1h7[squares[1]]) * 62 + s2 - adjust2
+				} else if off_diagonal(squares[2]) != 0 {
+					6 * 63 * 62 + 4 * 28 * 62 + rank(s0) * 7 * 28 + (rank(s1) - adjust1) * 28 + indices.map_b1h1h7[squares[2]]
+				} else {
+					6 * 63 * 62 + 4 * 28 * 62 + 4 * 7 * 28 + rank(s0) * 7 * 6 + (rank(s1) - adjust1) * 6 + (rank(s2) - adjust2)
+				};
+			} else {
+				index = indices.map_kk[indices.map_a1d1d4[squares[0]]][squares[1]];
+			}
+		}
+
+		index *= subtable.group_factors[0];
+
+		// the other groups are sets of squares, skipping the squares taken already
+		let mut start = lengths[0];
+		let mut other_pawns = self.has_pawns && self.pawns[1] > 0;
+
+		for (group, length) in lengths.iter().copied().enumerate().skip(1) {
+			squares[start..start + length].sort();
+
+			let mut n = 0;
+			for i in 0..length {
+				let square = squares[start + i];
+				let adjust = squares[..start].iter().filter(|taken| square > **taken).count();
+				let pawn_ranks = if other_pawns { 8 } else { 0 };
+				n += indices.binomial[i + 1][square - adjust - pawn_ranks];
+			}
+
+			other_pawns = false;
+			index += n * subtable.group_factors[group];
+			start += length;
+		}
+
+		index
+	}
+
+	fn decompress(&self, pairs: &Pairs, index: u64) -> Result<u16, String> {
+		if pairs.flags & SINGLE_VALUE != 0 {
+			return Ok(pairs.min_length as u16);
+		}
+
+		let bytes = &self.bytes[..];
+
+		// the sparse index points at a block and a value in it every `span` values
+		let k = (index / pairs.span) as usize;
+		if k >= pairs.sparse_index_size {
+			return Err("The position is outside the table".to_string());
+		}
+
+		let mut block = read_u32(bytes, pairs.sparse_index + 6 * k)? as usize;
+		let mut offset = i64::from(read_u16(bytes, pairs.sparse_index + 6 * k + 4)?);
+		offset += (index % pairs.span) as i64 - (pairs.span / 2) as i64;
+
+		let block_length = |block: usize| read_u16(bytes, pairs.block_lengths + 2 * block).map(i64::from);
+
+		while offset < 0 {
+			block = block.checked_sub(1).ok_or("The table is corrupted".to_string())?;
+			offset += block_length(block)? + 1;
+		}
+
+		while offset > block_length(block)? {
+			offset -= block_length(block)? + 1;
+			block += 1;
+		}
+
+		let mut at = pairs.data + block * pairs.block_size;
+		let read_be = |at: usize, n: usize| -> Result<u64, String> {
+			let b = bytes.get(at..at + n).ok_or("The table is cut off".to_string())?;
+			Ok(b.iter().fold(0, |value, byte| (value << 8) | u64::from(*byte)))
+		};
+
+		let mut buffer = read_be(at, 8)?;
+		let mut buffer_size = 64;
+		at += 8;
+
+		let mut symbol;
+		loop {
+			let mut length = 0;
+			while length < pairs.base.len() && buffer < pairs.base[length] {
+				length += 1;
+			}
+
+			if length == pairs.base.len() {
+				return Err("The table is corrupted".to_string());
+			}
+
+			let shift = 64 - length - pairs.min_length;
+			symbol = (buffer - pairs.base[length]).checked_shr(shift as u32).unwrap_or(0) as usize;
+			symbol += usize::from(read_u16(bytes, pairs.lowest_symbols + 2 * length)?);
+
+			let values = i64::from(*pairs.symbol_lengths.get(symbol).ok_or("The table is corrupted".to_string())?) + 1;
+			if offset < values {
+				break;
+			}
+
+			offset -= values;
+			length += pairs.min_length;
+			buffer = buffer.checked_shl(length as u32).unwrap_or(0);
+			buffer_size -= length as isize;
+
+			if buffer_size <= 32 {
+				buffer_size += 32;
+				buffer |= read_be(at, 4)? << (64 - buffer_size);
+				at += 4;
+			}
+		}
+
+		// symbols stand for pairs of symbols, find the one the value is in
+		while pairs.symbol_lengths[symbol] != 0 {
+			let (left, right) = children(bytes, pairs, symbol)?;
+			let values = i64::from(pairs.symbol_lengths[left]) + 1;
+
+			if offset < values {
+				symbol = left;
+			} else {
+				offset -= values;
+				symbol = right;
+			}
+		}
+
+		Ok(children(bytes, pairs, symbol)?.0 as u16)
+	}
+
+	// DTZ tables can store moves instead of plies and map the values
+	fn map_dtz(&self, subtable: &Subtable, value: i32, wdl: i32) -> Result<i32, String> {
+		let flags = subtable.pairs.flags;
+		let mut value = value;
+
+		if flags & MAPPED != 0 {
+			let list = [1, 3, 0, 2, 0][(wdl + 2) as usize];
+			let index = subtable.map_index[list] + value as usize;
+
+			value = if flags & WIDE != 0 {
+				i32::from(read_u16(&self.bytes, self.map + 2 * index)?)
+			} else {
+				i32::from(read_u8(&self.bytes, self.map + index)?)
+			};
+		}
+
+		let plies = match wdl {
+			2 => flags & WIN_PLIES != 0,
+			-2 => flags & LOSS_PLIES != 0,
+			_ => false,
+		};
+
+		if !plies {
+			value *= 2;
+		}
+
+		Ok(value + 1)
+	}
+}
+
+// the two symbols a symbol is made of, 12 bits each
+fn children(bytes: &[u8], pairs: &Pairs, symbol: usize) -> Result<(usize, usize), String> {
+	let at = pairs.tree + 3 * symbol;
+	let b = bytes.get(at..at + 3).ok_or("The table is cut off".to_string())?;
+
+	Ok(((usize::from(b[1] & 0xf) << 8) | usize::from(b[0]), (usize::from(b[2]) << 4) | usize::from(b[1] >> 4)))
+}
+
+fn symbol_length(bytes: &[u8], pairs: &mut Pairs, symbol: usize, visited: &mut [bool]) -> Result<u8, String> {
+	visited[symbol] = true;
+
+	let (left, right) = children(bytes, pairs, symbol)?;
+	if right == 0xfff {
+		return Ok(0);
+	}
+
+	for child in &[left, right] {
+		if *child >= visited.len() {
+			return Err("The table is corrupted".to_string());
+		}
+
+		if !visited[*child] {
+			pairs.symbol_lengths[*child] = symbol_length(bytes, pairs, *child, visited)?;
+		}
+	}
+
+	Ok(pairs.symbol_lengths[left].wrapping_add(pairs.symbol_lengths[right]).wrapping_add(1))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// a table where every position has the same value for each side to move
+	fn single_value_table(directory: &Path, name: &str, wdl: bool, values: &[(u8, u8)]) {
+		let mut bytes = if wdl { WDL_MAGIC.to_vec() } else { DTZ_MAGIC.to_vec() };
+		bytes.push(if values.len() == 2 { 1 } else { 0 });
+		bytes.push(0);
+
+		let pieces: Vec<u8> = name.replace('v', "").chars().map(|c| piece_code(c).unwrap()).collect();
+		let black = name.find('v').unwrap();
+		for (i, code) in pieces.iter().enumerate() {
+			let code = if i >= black { code | 8 } else { *code };
+			bytes.push(code | code << 4);
+		}
+
+		if bytes.len() % 2 == 1 {
+			bytes.push(0);
+		}
+
+		for (flags, value) in values {
+			bytes.push(SINGLE_VALUE | flags);
+			bytes.push(*value);
+		}
+
+		let suffix = if wdl { "rtbw" } else { "rtbz" };
+		std::fs::write(directory.join(format!("{}.{}", name, suffix)), bytes).unwrap();
+	}
+
+	// KRvK in the real format, written by testdata/syzygy/generate.py
+	fn fixtures() -> Tablebase {
+		Tablebase::open(Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/syzygy")).unwrap()
+	}
+
+	fn directory(name: &str) -> PathBuf {
+		let directory = std::env::temp_dir().join(format!("rip-syzygy-{}-{}", name, std::process::id()));
+		std::fs::create_dir_all(&directory).unwrap();
+		directory
+	}
+
+	#[test]
+	fn test_indices() {
+		let indices = indices();
+
+		let mut codes: Vec<u64> = indices.map_kk.iter().flat_map(|row| row.iter().copied()).filter(|code| *code > 0).collect();
+		codes.sort();
+		codes.dedup();
+		assert_eq!(codes.len(), 461);
+		assert_eq!(codes.last(), Some(&461));
+
+		assert_eq!(indices.map_pawns[8], 47);
+		assert_eq!(indices.map_pawns[15], 46);
+		assert_eq!(indices.map_pawns[52], 0);
+		assert_eq!(indices.lead_pawns_size[1], [6, 6, 6, 6]);
+		assert_eq!(indices.binomial[3][10], 120);
+	}
+
+	#[test]
+	fn test_without_tables() {
+		let tablebase = Tablebase::open(directory("empty")).unwrap();
+
+		let game = Game::from_fen("8/8/3k4/8/8/4K3/8/8 w - - 0 1").unwrap();
+		assert_eq!(tablebase.probe_wdl(&game), Ok(Wdl::Draw));
+		assert_eq!(tablebase.probe_dtz(&game), Ok(0));
+
+		// the rook is lost, the tables aren't needed
+		let game = Game::from_fen("8/8/8/8/5k2/8/6r1/7K w - - 0 1").unwrap();
+		assert_eq!(tablebase.probe_wdl(&game), Ok(Wdl::Draw));
+
+		let game = Game::from_fen("8/8/8/8/8/8/5k2/R6K w - - 0 1").unwrap();
+		assert_eq!(tablebase.probe_wdl(&game), Err("There is no table for KRvK".to_string()));
+		assert!(tablebase.probe_wdl(&Game::new()).is_err());
+		assert!(Tablebase::open("/this/directory/does/not/exist").is_err());
+	}
+
+	#[test]
+	fn test_single_value_tables() {
+		let directory = directory("kqvk");
+		single_value_table(&directory, "KQvK", true, &[(0, 4), (0, 0)]);
+		single_value_table(&directory, "KQvK", false, &[(0, 0)]);
+		let tablebase = Tablebase::open(&directory).unwrap();
+
+		let game = Game::from_fen("8/8/8/8/8/3Q4/8/K6k w - - 0 1").unwrap();
+		assert_eq!(tablebase.probe_wdl(&game), Ok(Wdl::Win));
+		assert_eq!(tablebase.probe_dtz(&game), Ok(1));
+
+		let game = Game::from_fen("8/8/8/8/8/3Q4/8/K6k b - - 0 1").unwrap();
+		assert_eq!(tablebase.probe_wdl(&game), Ok(Wdl::Loss));
+		assert_eq!(tablebase.probe_dtz(&game), Ok(-2));
+
+		// the same with the colors swapped
+		let game = Game::from_fen("8/8/8/8/8/3q4/8/k6K w - - 0 1").unwrap();
+		assert_eq!(tablebase.probe_wdl(&game), Ok(Wdl::Loss));
+
+		// taking the queen draws
+		let game = Game::from_fen("8/8/8/8/8/8/6Qk/K7 b - - 0 1").unwrap();
+		assert_eq!(tablebase.probe_wdl(&game), Ok(Wdl::Draw));
+		assert_eq!(tablebase.probe_dtz(&game), Ok(0));
+
+		// a mate in one beats every other move, and the queen isn't given away
+		let game = Game::from_fen("7k/8/6K1/8/8/8/Q7/8 w - - 0 1").unwrap();
+		let mv = tablebase.best_move(&game).unwrap().unwrap();
+		let mut after = game.clone();
+		after.make_move(mv).unwrap();
+		assert!(after.is_checkmate());
+
+		let moves = tablebase.root_moves(&game).unwrap();
+		let dtz = |uci: &str| moves.iter().find(|(mv, _)| game.to_uci(*mv) == uci).unwrap().1;
+		assert_eq!(dtz("a2a8"), 1);
+		assert_eq!(dtz("a2g8"), 0);
+		assert_eq!(dtz("a2f7"), 0);
+		assert_eq!(dtz("a2a3"), 3);
+
+		std::fs::remove_dir_all(&directory).unwrap();
+	}
+
+	#[test]
+	fn test_krvk() {
+		let tablebase = fixtures();
+
+		// the longest win of the endgame takes 16 moves
+		let game = Game::from_fen("8/8/8/8/8/2k5/1R6/K7 w - - 0 1").unwrap();
+		assert_eq!(tablebase.probe_wdl(&game), Ok(Wdl::Win));
+		assert_eq!(tablebase.probe_dtz(&game), Ok(31));
+
+		let game = Game::from_fen("8/8/8/8/8/8/1Rk5/K7 b - - 0 1").unwrap();
+		assert_eq!(tablebase.probe_wdl(&game), Ok(Wdl::Loss));
+		assert_eq!(tablebase.probe_dtz(&game), Ok(-32));
+
+		let game = Game::from_fen("8/8/8/8/8/k7/8/KR6 w - - 0 1").unwrap();
+		assert_eq!(tablebase.probe_dtz(&game), Ok(15));
+		let game = Game::from_fen("8/8/8/8/8/8/R7/2K1k3 b - - 0 1").unwrap();
+		assert_eq!(tablebase.probe_dtz(&game), Ok(-10));
+
+		// every move the table picks keeps the win and gets closer to mate
+		let mut game = Game::from_fen("8/8/8/8/8/2k5/1R6/K7 w - - 0 1").unwrap();
+		for dtz in (1..=31).rev() {
+			assert_eq!(tablebase.probe_dtz(&game).unwrap().abs(), dtz);
+			game.make_move(tablebase.best_move(&game).unwrap().unwrap()).unwrap();
+		}
+		assert!(game.is_checkmate());
+
+		let game = Game::from_fen("7k/8/6K1/8/8/8/8/R7 w - - 0 1").unwrap();
+		assert_eq!(tablebase.probe_dtz(&game), Ok(1));
+		assert_eq!(game.to_uci(tablebase.best_move(&game).unwrap().unwrap()), "a1a8");
+
+		// the rook is taken, or black is stalemated
+		let game = Game::from_fen("8/8/8/8/8/8/6kR/K7 b - - 0 1").unwrap();
+		assert_eq!(tablebase.probe_wdl(&game), Ok(Wdl::Draw));
+		let game = Game::from_fen("7k/8/7K/8/8/8/8/6R1 b - - 0 1").unwrap();
+		assert_eq!(tablebase.probe_wdl(&game), Ok(Wdl::Draw));
+		assert_eq!(tablebase.probe_dtz(&game), Ok(0));
+	}
+}
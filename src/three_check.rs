@@ -0,0 +1,81 @@
+use crate::color::Color;
+use crate::game::Game;
+use crate::moves::Move;
+use crate::name::Name;
+use crate::variant::{Outcome, Variant};
+
+// giving check three times wins
+pub struct ThreeCheck;
+
+impl Variant for ThreeCheck {
+	fn name(&self) -> &str {
+		"threecheck"
+	}
+
+	fn standard_legality(&self) -> bool {
+		true
+	}
+
+	fn starting_fen(&self) -> &str {
+		"rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 3+3 0 1"
+	}
+
+	fn counts_checks(&self) -> bool {
+		true
+	}
+
+	fn after_play(&self, game: &mut Game, _mv: Move, _captured: Option<Name>) {
+		if game.is_check() {
+			let remaining = &mut game.remaining_checks[game.turn.opposite().index()];
+			*remaining = remaining.saturating_sub(1);
+		}
+	}
+
+	fn outcome(&self, game: &Game) -> Option<Outcome> {
+		[Color::White, Color::Black]
+			.iter()
+			.find(|color| game.remaining_checks(**color) == 0)
+			.map(|color| Outcome::Win(*color))
+	}
+
+	// any piece besides the king can give the checks
+	fn has_mating_material(&self, game: &Game, color: Color) -> bool {
+		game.board()
+			.squares()
+			.any(|square| square.piece.is_some_and(|piece| piece.color == color && piece.name != Name::King))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_three_check() {
+		let game = Game::from_variant_fen(
+			ThreeCheck,
+			"r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 1+1",
+		)
+		.unwrap();
+		assert_eq!(game.perft(1), 48);
+		assert_eq!(game.perft(2), 2039);
+
+		let mut game = Game::with_variant(ThreeCheck);
+		assert_eq!(game.to_fen(), ThreeCheck.starting_fen());
+
+		for san in &["e4", "e5", "Bb5", "c6", "Bxc6", "Nf6", "Bxd7+", "Nbxd7", "Qh5", "Ke7", "Qxf7+", "Kd6", "Qd5+"] {
+			assert_eq!(game.winner(), None);
+			game.make_move(game.parse_san(san).unwrap()).unwrap();
+		}
+
+		assert_eq!(game.remaining_checks(Color::White), 0);
+		assert_eq!(game.remaining_checks(Color::Black), 3);
+		assert_eq!(game.winner(), Some(Color::White));
+		assert!(game.legal_moves().is_empty());
+		assert!(game.to_fen().contains(" 0+3 "));
+
+		// the checks given can also be written at the end
+		let game = Game::from_variant_fen(ThreeCheck, "4k3/8/8/8/8/8/8/4K3 w - - 0 1 +2+1").unwrap();
+		assert_eq!(game.to_fen(), "4k3/8/8/8/8/8/8/4K3 w - - 1+2 0 1");
+	}
+}
@@ -0,0 +1,491 @@
+use crate::color::Color;
+use crate::game::Game;
+use crate::moves::Move;
+
+// the tags every PGN starts with, in this order
+const SEVEN_TAG_ROSTER: [(&str, &str); 7] = [
+	("Event", "?"),
+	("Site", "?"),
+	("Date", "????.??.??"),
+	("Round", "?"),
+	("White", "?"),
+	("Black", "?"),
+	("Result", "*"),
+];
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct NodeId(usize);
+
+// a position in the tree, with the move that led there
+#[derive(Clone)]
+pub struct Node {
+	game: Game,
+	mv: Option<Move>,
+	parent: Option<NodeId>,
+	// the first child continues the main line, the others are variations
+	children: Vec<NodeId>,
+	comment: String,
+	nags: Vec<u8>,
+}
+
+impl Node {
+	pub fn game(&self) -> &Game {
+		&self.game
+	}
+
+	// the root has no move
+	pub fn mv(&self) -> Option<Move> {
+		self.mv
+	}
+
+	pub fn parent(&self) -> Option<NodeId> {
+		self.parent
+	}
+
+	pub fn children(&self) -> &[NodeId] {
+		&self.children
+	}
+
+	pub fn comment(&self) -> &str {
+		&self.comment
+	}
+
+	// numeric annotation glyphs, e.g. 1 for "!" and 2 for "?"
+	pub fn nags(&self) -> &[u8] {
+		&self.nags
+	}
+}
+
+// a game with its variations, annotations and PGN tags
+#[derive(Clone)]
+pub struct GameTree {
+	// deleted branches leave their slots empty so the ids stay valid
+	nodes: Vec<Option<Node>>,
+	current: NodeId,
+	headers: Vec<(String, String)>,
+}
+
+impl GameTree {
+	pub fn new(game: Game) -> GameTree {
+		let root = Node {
+			game,
+			mv: None,
+			parent: None,
+			children: Vec::new(),
+			comment: String::new(),
+			nags: Vec::new(),
+		};
+
+		GameTree {
+			nodes: vec![Some(root)],
+			current: NodeId(0),
+			headers: Vec::new(),
+		}
+	}
+
+	pub fn root(&self) -> NodeId {
+		NodeId(0)
+	}
+
+	pub fn current(&self) -> NodeId {
+		self.current
+	}
+
+	// the position at the current node
+	pub fn game(&self) -> &Game {
+		&self.node(self.current).game
+	}
+
+	// panics when the node was deleted
+	pub fn node(&self, id: NodeId) -> &Node {
+		self.nodes[id.0].as_ref().expect("the node was deleted")
+	}
+
+	pub fn contains(&self, id: NodeId) -> bool {
+		self.nodes.get(id.0).is_some_and(|node| node.is_some())
+	}
+
+	fn node_mut(&mut self, id: NodeId) -> Result<&mut Node, String> {
+		self.nodes.get_mut(id.0).and_then(|node| node.as_mut()).ok_or_else(|| "The node was deleted".to_string())
+	}
+
+	pub fn header(&self, name: &str) -> Option<&str> {
+		self.headers.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+	}
+
+	pub fn set_header(&mut self, name: &str, value: &str) {
+		match self.headers.iter_mut().find(|(key, _)| key == name) {
+			Some(header) => header.1 = value.to_string(),
+			None => self.headers.push((name.to_string(), value.to_string())),
+		}
+	}
+
+	// plays the move from the current node, following the child with the same
+	// move when there is one, or else starting a new variation
+	pub fn play(&mut self, mv: Move) -> Result<NodeId, String> {
+		let current = self.node(self.current);
+		let existing = current.children.iter().find(|child| self.node(**child).mv == Some(mv)).copied();
+
+		if let Some(child) = existing {
+			self.current = child;
+			return Ok(child);
+		}
+
+		let mut game = current.game.clone();
+		game.make_move(mv)?;
+
+		let id = NodeId(self.nodes.len());
+		self.nodes.push(Some(Node {
+			game,
+			mv: Some(mv),
+			parent: Some(self.current),
+			children: Vec::new(),
+			comment: String::new(),
+			nags: Vec::new(),
+		}));
+
+		let current = self.current;
+		self.node_mut(current)?.children.push(id);
+		self.current = id;
+
+		Ok(id)
+	}
+
+	pub fn play_san(&mut self, san: &str) -> Result<NodeId, String> {
+		let mv = self.game().parse_san(san)?;
+		self.play(mv)
+	}
+
+	// follows the main line, false at the end of it
+	pub fn forward(&mut self) -> bool {
+		match self.node(self.current).children.first() {
+			Some(child) => {
+				self.current = *child;
+				true
+			},
+			None => false,
+		}
+	}
+
+	// false at the root
+	pub fn back(&mut self) -> bool {
+		match self.node(self.current).parent {
+			Some(parent) => {
+				self.current = parent;
+				true
+			},
+			None => false,
+		}
+	}
+
+	pub fn go_to(&mut self, id: NodeId) -> Result<(), String> {
+		self.node_mut(id)?;
+		self.current = id;
+		Ok(())
+	}
+
+	pub fn go_to_start(&mut self) {
+		self.current = self.root();
+	}
+
+	// the end of the main line from the current node
+	pub fn go_to_end(&mut self) {
+		while self.forward() {}
+	}
+
+	// the nodes of the main line after the root
+	pub fn mainline(&self) -> Vec<NodeId> {
+		let mut line = Vec::new();
+		let mut id = self.root();
+
+		while let Some(child) = self.node(id).children.first() {
+			line.push(*child);
+			id = *child;
+		}
+
+		line
+	}
+
+	pub fn set_comment(&mut self, id: NodeId, comment: &str) -> Result<(), String> {
+		self.node_mut(id)?.comment = comment.to_string();
+		Ok(())
+	}
+
+	pub fn add_nag(&mut self, id: NodeId, nag: u8) -> Result<(), String> {
+		let node = self.node_mut(id)?;
+
+		if !node.nags.contains(&nag) {
+			node.nags.push(nag);
+		}
+
+		Ok(())
+	}
+
+	pub fn remove_nag(&mut self, id: NodeId, nag: u8) -> Result<(), String> {
+		self.node_mut(id)?.nags.retain(|found| *found != nag);
+		Ok(())
+	}
+
+	// makes the line through the node the main line,
+	// every variation it's in becomes the first of its siblings
+	pub fn promote_to_mainline(&mut self, id: NodeId) -> Result<(), String> {
+		self.node_mut(id)?;
+
+		let mut id = id;
+		while let Some(parent) = self.node(id).parent {
+			let children = &mut self.node_mut(parent)?.children;
+			let index = children.iter().position(|child| *child == id).unwrap();
+
+			let child = children.remove(index);
+			children.insert(0, child);
+
+			id = parent;
+		}
+
+		Ok(())
+	}
+
+	// moves the variation one place up among its siblings
+	pub fn promote(&mut self, id: NodeId) -> Result<(), String> {
+		let parent = self.node_mut(id)?.parent.ok_or_else(|| "The root can't be promoted".to_string())?;
+		let children = &mut self.node_mut(parent)?.children;
+		let index = children.iter().position(|child| *child == id).unwrap();
+
+		if index > 0 {
+			children.swap(index, index - 1);
+		}
+
+		Ok(())
+	}
+
+	// removes the node with everything after it, the current node
+	// moves back to the parent when it was in the deleted branch
+	pub fn delete(&mut self, id: NodeId) -> Result<(), String> {
+		let parent = self.node_mut(id)?.parent.ok_or_else(|| "The root can't be deleted".to_string())?;
+		self.node_mut(parent)?.children.retain(|child| *child != id);
+
+		let mut deleted = vec![id];
+		while let Some(next) = deleted.pop() {
+			if next == self.current {
+				self.current = parent;
+			}
+
+			if let Some(node) = self.nodes[next.0].take() {
+				deleted.extend(node.children);
+			}
+		}
+
+		Ok(())
+	}
+
+	pub fn to_pgn(&self) -> String {
+		let mut pgn = String::new();
+
+		let root = self.node(self.root());
+		let mut headers: Vec<(String, String)> = SEVEN_TAG_ROSTER
+			.iter()
+			.map(|(name, default)| (name.to_string(), self.header(name).unwrap_or(default).to_string()))
+			.collect();
+
+		let variant = root.game.variant();
+		if variant.name() != "standard" && self.header("Variant").is_none() {
+			headers.push(("Variant".to_string(), variant.name().to_string()));
+		}
+
+		let fen = root.game.to_fen();
+		if fen != variant.starting_fen() && self.header("FEN").is_none() {
+			headers.push(("SetUp".to_string(), "1".to_string()));
+			headers.push(("FEN".to_string(), fen));
+		}
+
+		for (name, value) in self.headers.iter() {
+			if !headers.iter().any(|(key, _)| key == name) {
+				headers.push((name.clone(), value.clone()));
+			}
+		}
+
+		for (name, value) in headers.iter() {
+			pgn.push_str(&format!("[{} \"{}\"]\n", name, value.replace('\\', "\\\\").replace('"', "\\\"")));
+		}
+		pgn.push('\n');
+
+		let mut tokens = Vec::new();
+		write_comment(&root.comment, &mut tokens);
+
+		self.write_children(self.root(), true, &mut tokens);
+		tokens.push(self.header("Result").unwrap_or("*").to_string());
+
+		// lines are kept under 80 characters
+		let mut line = String::new();
+		for token in tokens {
+			if !line.is_empty() && line.len() + token.len() + 1 > 79 {
+				pgn.push_str(&line);
+				pgn.push('\n');
+				line.clear();
+			}
+
+			if !line.is_empty() {
+				line.push(' ');
+			}
+			line.push_str(&token);
+		}
+
+		pgn.push_str(&line);
+		pgn.push('\n');
+
+		pgn
+	}
+
+	// the main line after the node with the variations of its first move,
+	// black moves need their number after anything that interrupts the line
+	fn write_children(&self, id: NodeId, number: bool, tokens: &mut Vec<String>) {
+		let children = &self.node(id).children;
+
+		let main = match children.first() {
+			Some(main) => *main,
+			None => return,
+		};
+
+		self.write_move(main, number, tokens);
+
+		for variation in children.iter().skip(1) {
+			let mut line = Vec::new();
+			self.write_move(*variation, true, &mut line);
+			self.write_children(*variation, !self.node(*variation).comment.is_empty(), &mut line);
+
+			line[0].insert(0, '(');
+			line.last_mut().unwrap().push(')');
+			tokens.extend(line);
+		}
+
+		let interrupted = children.len() > 1 || !self.node(main).comment.is_empty();
+		self.write_children(main, interrupted, tokens);
+	}
+
+	fn write_move(&self, id: NodeId, number: bool, tokens: &mut Vec<String>) {
+		let node = self.node(id);
+		let before = &self.node(node.parent.unwrap()).game;
+
+		match before.turn() {
+			Color::White => tokens.push(format!("{}.", before.fullmove_number())),
+			Color::Black if number => tokens.push(format!("{}...", before.fullmove_number())),
+			Color::Black => (),
+		}
+
+		let mut san = before.to_san(node.mv.unwrap());
+		for nag in node.nags.iter() {
+			san.push_str(&format!(" ${}", nag));
+		}
+		tokens.push(san);
+
+		write_comment(&node.comment, tokens);
+	}
+}
+
+// PGN can't escape a '}' in a comment, so it is left out
+fn write_comment(comment: &str, tokens: &mut Vec<String>) {
+	let comment = comment.replace('}', " ").split_whitespace().collect::<Vec<_>>().join(" ");
+	if !comment.is_empty() {
+		tokens.push(format!("{{{}}}", comment));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn play(tree: &mut GameTree, moves: &[&str]) -> Vec<NodeId> {
+		moves.iter().map(|san| tree.play_san(san).unwrap()).collect()
+	}
+
+	fn sans(tree: &GameTree, line: &[NodeId]) -> Vec<String> {
+		line.iter()
+			.map(|id| {
+				let node = tree.node(*id);
+				tree.node(node.parent().unwrap()).game().to_san(node.mv().unwrap())
+			})
+			.collect()
+	}
+
+	#[test]
+	fn test_variations() {
+		let mut tree = GameTree::new(Game::new());
+		let main = play(&mut tree, &["e4", "e5", "Nf3"]);
+
+		tree.go_to(main[0]).unwrap();
+		let sicilian = play(&mut tree, &["c5", "Nf3"]);
+
+		assert_eq!(sans(&tree, &tree.mainline()), vec!["e4", "e5", "Nf3"]);
+		assert_eq!(tree.node(main[0]).children(), &[main[1], sicilian[0]]);
+
+		// playing a move that's already in the tree follows it
+		tree.go_to(main[0]).unwrap();
+		assert_eq!(tree.play_san("c5").unwrap(), sicilian[0]);
+
+		tree.promote_to_mainline(sicilian[1]).unwrap();
+		assert_eq!(sans(&tree, &tree.mainline()), vec!["e4", "c5", "Nf3"]);
+
+		tree.promote(main[1]).unwrap();
+		assert_eq!(sans(&tree, &tree.mainline()), vec!["e4", "e5", "Nf3"]);
+
+		tree.go_to(sicilian[1]).unwrap();
+		tree.delete(sicilian[0]).unwrap();
+		assert_eq!(tree.current(), main[0]);
+		assert!(!tree.contains(sicilian[1]));
+		assert!(tree.go_to(sicilian[1]).is_err());
+		assert!(tree.delete(tree.root()).is_err());
+	}
+
+	#[test]
+	fn test_navigation() {
+		let mut tree = GameTree::new(Game::new());
+		let main = play(&mut tree, &["d4", "d5"]);
+
+		assert!(!tree.forward());
+		assert!(tree.back());
+		assert_eq!(tree.current(), main[0]);
+
+		tree.go_to_start();
+		assert!(!tree.back());
+
+		tree.go_to_end();
+		assert_eq!(tree.current(), main[1]);
+		assert_eq!(tree.game().fullmove_number(), 2);
+	}
+
+	#[test]
+	fn test_pgn() {
+		let mut tree = GameTree::new(Game::new());
+		tree.set_header("White", "Morphy");
+		tree.set_header("Result", "1-0");
+		tree.set_header("Opening", "Philidor");
+
+		let main = play(&mut tree, &["e4", "e5", "Nf3", "d6"]);
+		tree.set_comment(main[3], "Philidor's defence").unwrap();
+		tree.add_nag(main[2], 1).unwrap();
+
+		tree.go_to(main[1]).unwrap();
+		play(&mut tree, &["Bc4", "Nf6"]);
+
+		tree.go_to(main[0]).unwrap();
+		play(&mut tree, &["c5"]);
+
+		assert_eq!(
+			tree.to_pgn(),
+			"[Event \"?\"]\n[Site \"?\"]\n[Date \"????.??.??\"]\n[Round \"?\"]\n[White \"Morphy\"]\n[Black \"?\"]\n\
+			 [Result \"1-0\"]\n[Opening \"Philidor\"]\n\n\
+			 1. e4 e5 (1... c5) 2. Nf3 $1 (2. Bc4 Nf6) 2... d6 {Philidor's defence} 1-0\n"
+		);
+	}
+
+	#[test]
+	fn test_pgn_from_position() {
+		let game = Game::from_fen("4k3/8/8/8/8/8/8/4K2R b K - 0 10").unwrap();
+		let mut tree = GameTree::new(game);
+		play(&mut tree, &["Kd7", "O-O"]);
+
+		let pgn = tree.to_pgn();
+		assert!(pgn.contains("[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/8/4K2R b K - 0 10\"]\n"));
+		assert!(pgn.ends_with("\n10... Kd7 11. O-O *\n"));
+	}
+}
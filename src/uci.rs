@@ -0,0 +1,106 @@
+use crate::game::Game;
+use crate::moves::{Move, MoveKind};
+use crate::square::Square;
+
+impl Game {
+	// castling is accepted both as the king moving two squares
+	// and as the king moving onto its own rook (UCI_Chess960)
+	pub fn parse_uci(&self, uci: &str) -> Result<Move, String> {
+		let invalid = || format!("'{}' is not a move in UCI notation", uci);
+
+		// drops are written with the piece letter, e.g. N@f3
+		if let Some((letter, square)) = uci.split_once('@') {
+			let mut chars = letter.chars();
+			let name = match (chars.next(), chars.next()) {
+				(Some(c), None) => self.name_from_letter(c).ok_or_else(invalid)?,
+				_ => return Err(invalid()),
+			};
+			let to = Square::from_str(square).map_err(|_| invalid())?;
+
+			return self
+				.legal_moves()
+				.into_iter()
+				.find(|mv| mv.kind == MoveKind::Drop(name) && mv.to == to)
+				.ok_or(format!("{} is not a legal move", uci));
+		}
+
+		let (from, rest) = Square::split_prefix(uci).ok_or_else(invalid)?;
+		let (to, rest) = Square::split_prefix(rest).ok_or_else(invalid)?;
+
+		let mut chars = rest.chars();
+		let promotion = match (chars.next(), chars.next()) {
+			(None, _) => None,
+			(Some(c), None) => Some(self.name_from_letter(c).ok_or(format!("Invalid promotion in '{}'", uci))?),
+			_ => return Err(invalid()),
+		};
+
+		self.legal_moves()
+			.into_iter()
+			.filter(|mv| mv.from == from && mv.promotion == promotion)
+			.find(|mv| mv.to == to || (mv.is_castle() && self.king_destination(*mv) == to))
+			.ok_or(format!("{} is not a legal move", uci))
+	}
+
+	pub fn to_uci(&self, mv: Move) -> String {
+		if let MoveKind::Drop(name) = mv.kind {
+			return format!("{}@{}", char::from(name), mv.to);
+		}
+
+		let to = if mv.is_castle() && !self.chess960 {
+			self.king_destination(mv)
+		} else {
+			mv.to
+		};
+
+		match mv.promotion {
+			Some(name) => format!("{}{}{}", mv.from, to, char::from(name).to_ascii_lowercase()),
+			None => format!("{}{}", mv.from, to),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::name::Name;
+
+	#[test]
+	fn test_castling_notation() {
+		let mut game =
+			Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+
+		let short = game.parse_uci("e1g1").unwrap();
+		assert!(short.is_castle());
+		assert_eq!(game.parse_uci("e1h1").unwrap(), short);
+		assert_eq!(game.to_uci(short), "e1g1");
+
+		game.set_chess960(true);
+		assert_eq!(game.to_uci(short), "e1h1");
+	}
+
+	#[test]
+	fn test_promotion_notation() {
+		let game = Game::from_fen("8/4P3/8/8/8/8/k7/4K3 w - - 0 1").unwrap();
+
+		let mv = game.parse_uci("e7e8n").unwrap();
+		assert_eq!(mv.promotion(), Some(Name::Knight));
+		assert_eq!(game.to_uci(mv), "e7e8n");
+		assert!(game.parse_uci("e7e8").is_err());
+		assert!(game.parse_uci("e7e8k").is_err());
+		assert!(game.parse_uci("e7e8qq").is_err());
+	}
+
+	#[test]
+	fn test_large_board_notation() {
+		let game = Game::from_fen("k9/10/10/10/10/10/10/10/P9/K9 w - - 0 1").unwrap();
+
+		let mv = game.parse_uci("a2a4").unwrap();
+		assert_eq!(game.to_uci(mv), "a2a4");
+
+		let game = Game::from_fen("k9/10/P9/10/10/10/10/10/10/K9 w - - 0 1").unwrap();
+
+		let mv = game.parse_uci("a8a9").unwrap();
+		assert_eq!(game.to_uci(mv), "a8a9");
+		assert!(game.parse_uci("a9a10").is_err());
+	}
+}
@@ -0,0 +1,690 @@
+use crate::color::Color;
+use crate::engine::{Engine, Limits, Reply, Score};
+use crate::game::Game;
+use crate::moves::Move;
+
+use std::ffi::OsStr;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+// how long the engine gets to answer anything but a search
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+// how much longer than its time a search may take before it's stopped
+const GRACE: Duration = Duration::from_secs(1);
+
+// the words that start a field of an info line
+const INFO_FIELDS: [&str; 17] = [
+	"depth",
+	"seldepth",
+	"multipv",
+	"score",
+	"nodes",
+	"nps",
+	"time",
+	"pv",
+	"hashfull",
+	"tbhits",
+	"sbhits",
+	"cpuload",
+	"currmove",
+	"currmovenumber",
+	"currline",
+	"refutation",
+	"string",
+];
+
+// an option the engine offers, e.g. "option name Hash type spin default 16 min 1 max 1024"
+#[derive(Clone, PartialEq, Debug)]
+pub struct EngineOption {
+	name: String,
+	kind: String,
+	default: Option<String>,
+}
+
+impl EngineOption {
+	fn parse(line: &str) -> Option<EngineOption> {
+		let rest = line.strip_prefix("option name ")?;
+		let (name, rest) = rest.split_once(" type ")?;
+		let mut words = rest.split_whitespace();
+		let kind = words.next()?.to_string();
+
+		// the default can have spaces, e.g. a path
+		let default = rest.split_once(" default ").map(|(_, default)| {
+			let end = [" min ", " max ", " var "].iter().filter_map(|key| default.find(key)).min();
+			default[..end.unwrap_or(default.len())].trim().to_string()
+		});
+
+		Some(EngineOption {
+			name: name.trim().to_string(),
+			kind,
+			default: default.filter(|default| default != "<empty>"),
+		})
+	}
+
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	// check, spin, combo, button or string
+	pub fn kind(&self) -> &str {
+		&self.kind
+	}
+
+	pub fn default(&self) -> Option<&str> {
+		self.default.as_deref()
+	}
+}
+
+// a score that is only a bound, when the search failed high or low
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Bound {
+	Lower,
+	Upper,
+}
+
+// an info line of a search, the fields the engine left out are None
+#[derive(Clone, PartialEq, Default, Debug)]
+pub struct Info {
+	depth: Option<u32>,
+	seldepth: Option<u32>,
+	multipv: Option<u32>,
+	score: Option<Score>,
+	bound: Option<Bound>,
+	nodes: Option<u64>,
+	nps: Option<u64>,
+	time: Option<Duration>,
+	pv: Vec<Move>,
+	string: Option<String>,
+}
+
+fn number<T: std::str::FromStr>(word: Option<&str>, field: &str) -> Result<T, String> {
+	word.and_then(|word| word.parse().ok()).ok_or_else(|| format!("Invalid {} in the info line", field))
+}
+
+impl Info {
+	// the principal variation is read in `game`, the position that was
+	// searched, and is cut off at the first move that isn't legal
+	pub fn parse(line: &str, game: &Game) -> Result<Info, String> {
+		let mut words = line.split_whitespace().peekable();
+		if words.next() != Some("info") {
+			return Err(format!("'{}' isn't an info line", line));
+		}
+
+		let mut info = Info::default();
+		while let Some(word) = words.next() {
+			match word {
+				"depth" => info.depth = Some(number(words.next(), word)?),
+				"seldepth" => info.seldepth = Some(number(words.next(), word)?),
+				"multipv" => info.multipv = Some(number(words.next(), word)?),
+				"nodes" => info.nodes = Some(number(words.next(), word)?),
+				"nps" => info.nps = Some(number(words.next(), word)?),
+				"time" => info.time = Some(Duration::from_millis(number(words.next(), word)?)),
+				"score" => {
+					info.score = match words.next() {
+						Some("cp") => Some(Score::Centipawns(number(words.next(), word)?)),
+						Some("mate") => Some(Score::Mate(number(words.next(), word)?)),
+						_ => return Err("Invalid score in the info line".to_string()),
+					};
+
+					info.bound = match words.next_if(|word| *word == "lowerbound" || *word == "upperbound") {
+						Some("lowerbound") => Some(Bound::Lower),
+						Some(_) => Some(Bound::Upper),
+						None => None,
+					};
+				},
+				"pv" => {
+					let mut position = Some(game.clone());
+
+					while let Some(uci) = words.next_if(|word| !INFO_FIELDS.contains(word)) {
+						position = position.and_then(|mut position| {
+							let mv = position.parse_uci(uci).ok().filter(|mv| position.legal_moves().contains(mv))?;
+							position.make_move(mv).ok()?;
+							info.pv.push(mv);
+							Some(position)
+						});
+					}
+				},
+				// the rest of the line is for people to read
+				"string" => {
+					info.string = Some(words.by_ref().collect::<Vec<_>>().join(" "));
+				},
+				// the fields that aren't kept, with the words that belong to them
+				_ => {
+					while words.next_if(|word| !INFO_FIELDS.contains(word)).is_some() {}
+				},
+			}
+		}
+
+		Ok(info)
+	}
+
+	pub fn depth(&self) -> Option<u32> {
+		self.depth
+	}
+
+	pub fn seldepth(&self) -> Option<u32> {
+		self.seldepth
+	}
+
+	// the rank of the line when the engine searches several
+	pub fn multipv(&self) -> Option<u32> {
+		self.multipv
+	}
+
+	// from the point of view of the side to move
+	pub fn score(&self) -> Option<Score> {
+		self.score
+	}
+
+	pub fn bound(&self) -> Option<Bound> {
+		self.bound
+	}
+
+	pub fn nodes(&self) -> Option<u64> {
+		self.nodes
+	}
+
+	pub fn nps(&self) -> Option<u64> {
+		self.nps
+	}
+
+	pub fn time(&self) -> Option<Duration> {
+		self.time
+	}
+
+	pub fn pv(&self) -> &[Move] {
+		&self.pv
+	}
+
+	pub fn string(&self) -> Option<&str> {
+		self.string.as_deref()
+	}
+}
+
+// the end of a search
+#[derive(Clone, PartialEq, Debug)]
+pub struct BestMove {
+	mv: Move,
+	ponder: Option<Move>,
+	// the last info line with a score
+	info: Option<Info>,
+}
+
+impl BestMove {
+	pub fn mv(&self) -> Move {
+		self.mv
+	}
+
+	// the answer the engine expects
+	pub fn ponder(&self) -> Option<Move> {
+		self.ponder
+	}
+
+	pub fn info(&self) -> Option<&Info> {
+		self.info.as_ref()
+	}
+}
+
+// what the engine said at the end of a search, before it's checked
+struct Answer {
+	bestmove: String,
+	ponder: Option<String>,
+	info: Option<Info>,
+}
+
+// an engine speaking UCI in another process, it's told to quit when dropped
+pub struct UciEngine {
+	child: Child,
+	stdin: ChildStdin,
+	// the lines the engine writes, read on a thread of their own
+	lines: Receiver<String>,
+	name: String,
+	author: Option<String>,
+	options: Vec<EngineOption>,
+	// the variant and whether it's Chess960, as last told to the engine
+	variant: (String, bool),
+}
+
+impl UciEngine {
+	// starts the engine and waits for it to say it speaks UCI
+	pub fn spawn<P: AsRef<OsStr>>(program: P, args: &[&str]) -> Result<UciEngine, String> {
+		let mut child = Command::new(program.as_ref())
+			.args(args)
+			.stdin(Stdio::piped())
+			.stdout(Stdio::piped())
+			.stderr(Stdio::null())
+			.spawn()
+			.map_err(|error| format!("Couldn't start {}: {}", program.as_ref().to_string_lossy(), error))?;
+
+		let stdin = child.stdin.take().unwrap();
+		let stdout = child.stdout.take().unwrap();
+
+		let (sender, lines) = mpsc::channel();
+		thread::spawn(move || {
+			for line in BufReader::new(stdout).lines() {
+				let sent = line.ok().and_then(|line| sender.send(line).ok());
+				if sent.is_none() {
+					break;
+				}
+			}
+		});
+
+		let mut engine = UciEngine {
+			child,
+			stdin,
+			lines,
+			name: program.as_ref().to_string_lossy().into_owned(),
+			author: None,
+			options: Vec::new(),
+			variant: ("standard".to_string(), false),
+		};
+
+		engine.send("uci")?;
+		for line in engine.read_until("uciok", Some(TIMEOUT))? {
+			if let Some(name) = line.strip_prefix("id name ") {
+				engine.name = name.trim().to_string();
+			} else if let Some(author) = line.strip_prefix("id author ") {
+				engine.author = Some(author.trim().to_string());
+			} else if let Some(option) = EngineOption::parse(&line) {
+				engine.options.push(option);
+			}
+		}
+
+		engine.is_ready()?;
+		Ok(engine)
+	}
+
+	pub fn author(&self) -> Option<&str> {
+		self.author.as_deref()
+	}
+
+	pub fn options(&self) -> &[EngineOption] {
+		&self.options
+	}
+
+	pub fn option(&self, name: &str) -> Option<&EngineOption> {
+		self.options.iter().find(|option| option.name.eq_ignore_ascii_case(name))
+	}
+
+	// only the options the engine offers can be set, buttons are pressed with an empty value
+	pub fn set_option(&mut self, name: &str, value: &str) -> Result<(), String> {
+		let name = self.option(name).ok_or_else(|| format!("{} has no option {}", self.name, name))?.name.clone();
+
+		if value.is_empty() {
+			self.send(&format!("setoption name {}", name))?;
+		} else {
+			self.send(&format!("setoption name {} value {}", name, value))?;
+		}
+
+		self.is_ready()
+	}
+
+	// waits for the engine to be done with what it was told
+	pub fn is_ready(&mut self) -> Result<(), String> {
+		self.send("isready")?;
+		self.read_until("readyok", Some(TIMEOUT)).map(|_| ())
+	}
+
+	// the position is the one after `moves` are played from `start`, so the
+	// engine knows the positions that were repeated
+	pub fn set_position(&mut self, start: &Game, moves: &[Move]) -> Result<(), String> {
+		let variant = (start.variant().name().to_string(), start.is_chess960());
+
+		if variant != self.variant {
+			if variant.0 != "standard" {
+				self.set_option("UCI_Variant", &variant.0)
+					.map_err(|_| format!("{} doesn't play {}", self.name, variant.0))?;
+			} else if self.option("UCI_Variant").is_some() {
+				self.set_option("UCI_Variant", "chess")?;
+			}
+
+			if variant.1 || self.option("UCI_Chess960").is_some() {
+				self.set_option("UCI_Chess960", &variant.1.to_string())
+					.map_err(|_| format!("{} doesn't play Chess960", self.name))?;
+			}
+
+			self.variant = variant;
+		}
+
+		let mut command = format!("position fen {}", start.to_fen());
+		if !moves.is_empty() {
+			command.push_str(" moves");
+
+			let mut game = start.clone();
+			for mv in moves {
+				command.push(' ');
+				command.push_str(&game.to_uci(*mv));
+				game.make_move(*mv)?;
+			}
+		}
+
+		self.send(&command)
+	}
+
+	// searches the position after `moves` are played from `start`, every
+	// info line is handed to `on_info` while the engine is thinking
+	pub fn search<F: FnMut(&Info)>(
+		&mut self,
+		start: &Game,
+		moves: &[Move],
+		limits: &Limits,
+		on_info: F,
+	) -> Result<BestMove, String> {
+		let mut game = start.clone();
+		for mv in moves {
+			game.make_move(*mv)?;
+		}
+
+		let answer = self.run_search(&game, start, moves, limits, on_info)?;
+
+		let legal = |game: &Game, uci: &str| -> Result<Move, String> {
+			match game.parse_uci(uci) {
+				Ok(mv) if game.legal_moves().contains(&mv) => Ok(mv),
+				_ => Err(format!("{} answered with the illegal move {}", self.name, uci)),
+			}
+		};
+
+		let mv = legal(&game, &answer.bestmove)?;
+
+		// a ponder move that isn't legal is ignored, it's only a hint
+		let mut after = game.clone();
+		after.make_move(mv)?;
+		let ponder = answer.ponder.and_then(|uci| legal(&after, &uci).ok());
+
+		Ok(BestMove {
+			mv,
+			ponder,
+			info: answer.info,
+		})
+	}
+
+	fn run_search<F: FnMut(&Info)>(
+		&mut self,
+		game: &Game,
+		start: &Game,
+		moves: &[Move],
+		limits: &Limits,
+		mut on_info: F,
+	) -> Result<Answer, String> {
+		self.set_position(start, moves)?;
+		self.send(&UciEngine::go_command(limits)?)?;
+
+		let mut timeout = limits.deadline(game.turn()).map(|deadline| deadline + GRACE);
+		let mut stopped = false;
+		let mut last = None;
+
+		loop {
+			let line = match self.read_line(timeout) {
+				Ok(line) => line,
+				// the engine is told to stop once it's out of time, and has
+				// a little longer to answer
+				Err(RecvTimeoutError::Timeout) if !stopped => {
+					self.send("stop")?;
+					stopped = true;
+					timeout = Some(GRACE);
+					continue;
+				},
+				Err(RecvTimeoutError::Timeout) => return Err(format!("{} didn't stop searching", self.name)),
+				Err(RecvTimeoutError::Disconnected) => return Err(format!("{} quit", self.name)),
+			};
+
+			if line.starts_with("info") {
+				// engines write all kinds of info, a line that can't be read is skipped
+				if let Ok(info) = Info::parse(&line, game) {
+					on_info(&info);
+					if info.score.is_some() {
+						last = Some(info);
+					}
+				}
+			} else if let Some(rest) = line.strip_prefix("bestmove") {
+				let mut words = rest.split_whitespace();
+				let bestmove = match words.next() {
+					Some("(none)") | None => return Err(format!("{} has no move", self.name)),
+					Some(bestmove) => bestmove.to_string(),
+				};
+
+				let ponder = match (words.next(), words.next()) {
+					(Some("ponder"), Some(ponder)) => Some(ponder.to_string()),
+					_ => None,
+				};
+
+				return Ok(Answer {
+					bestmove,
+					ponder,
+					info: last,
+				});
+			}
+		}
+	}
+
+	fn go_command(limits: &Limits) -> Result<String, String> {
+		let mut command = "go".to_string();
+
+		for (color, time, increment) in [(Color::White, "wtime", "winc"), (Color::Black, "btime", "binc")] {
+			if let Some(remaining) = limits.remaining(color) {
+				command.push_str(&format!(" {} {}", time, remaining.as_millis()));
+				if limits.increment(color) > Duration::ZERO {
+					command.push_str(&format!(" {} {}", increment, limits.increment(color).as_millis()));
+				}
+			}
+		}
+
+		if let Some(moves) = limits.moves_to_go() {
+			command.push_str(&format!(" movestogo {}", moves));
+		}
+
+		if let Some(depth) = limits.max_depth() {
+			command.push_str(&format!(" depth {}", depth));
+		}
+
+		if let Some(nodes) = limits.max_nodes() {
+			command.push_str(&format!(" nodes {}", nodes));
+		}
+
+		if let Some(time) = limits.time_per_move() {
+			command.push_str(&format!(" movetime {}", time.as_millis()));
+		}
+
+		if command == "go" {
+			return Err("A search needs a limit".to_string());
+		}
+
+		Ok(command)
+	}
+
+	fn send(&mut self, command: &str) -> Result<(), String> {
+		writeln!(self.stdin, "{}", command)
+			.and_then(|_| self.stdin.flush())
+			.map_err(|error| format!("Couldn't write to {}: {}", self.name, error))
+	}
+
+	// waits forever without a timeout
+	fn read_line(&self, timeout: Option<Duration>) -> Result<String, RecvTimeoutError> {
+		match timeout {
+			Some(timeout) => self.lines.recv_timeout(timeout),
+			None => self.lines.recv().map_err(|_| RecvTimeoutError::Disconnected),
+		}
+	}
+
+	// the lines up to and including the one that is `token`
+	fn read_until(&mut self, token: &str, timeout: Option<Duration>) -> Result<Vec<String>, String> {
+		let mut lines = Vec::new();
+
+		loop {
+			let line = self.read_line(timeout).map_err(|error| match error {
+				RecvTimeoutError::Timeout => format!("{} didn't answer with {}", self.name, token),
+				RecvTimeoutError::Disconnected => format!("{} quit", self.name),
+			})?;
+
+			let done = line.trim() == token;
+			lines.push(line);
+
+			if done {
+				return Ok(lines);
+			}
+		}
+	}
+}
+
+impl Engine for UciEngine {
+	fn name(&self) -> String {
+		self.name.clone()
+	}
+
+	fn new_game(&mut self) -> Result<(), String> {
+		self.send("ucinewgame")?;
+		self.is_ready()
+	}
+
+	// the move isn't checked, that's up to the arbiter
+	fn go(&mut self, game: &Game, start: &Game, moves: &[Move], limits: &Limits) -> Result<Reply, String> {
+		let answer = self.run_search(game, start, moves, limits, |_| ())?;
+		Ok(Reply::new(&answer.bestmove, answer.info.and_then(|info| info.score)))
+	}
+}
+
+impl Drop for UciEngine {
+	fn drop(&mut self) {
+		let _ = self.send("quit");
+
+		for _ in 0..20 {
+			if let Ok(Some(_)) = self.child.try_wait() {
+				return;
+			}
+
+			thread::sleep(Duration::from_millis(10));
+		}
+
+		let _ = self.child.kill();
+		let _ = self.child.wait();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::arena::{Match, Termination};
+	use crate::clock::TimeControl;
+
+	// answers e2e4 to everything, which is only legal at the start
+	const STAND_IN: &str = r#"
+while read -r line; do
+	case "$line" in
+		uci)
+			echo "id name Stand-in"
+			echo "id author Nobody"
+			echo "option name Hash type spin default 16 min 1 max 1024"
+			echo "option name Clear Hash type button"
+			echo "option name SyzygyPath type string default <empty>"
+			echo "uciok" ;;
+		isready) echo "readyok" ;;
+		position*) position="$line" ;;
+		go*)
+			echo "info string $line"
+			echo "info depth 1 seldepth 2 multipv 1 score cp 18 nodes 20 nps 20000 time 1 pv e2e4"
+			echo "info depth 2 score mate -3 upperbound nodes 410 hashfull 5 nps 41000 time 10 pv e2e4 e7e5 e1e3"
+			case "$position" in
+				*moves*) echo "bestmove e2e4" ;;
+				*) echo "bestmove e2e4 ponder e7e5" ;;
+			esac ;;
+		quit) exit 0 ;;
+	esac
+done
+"#;
+
+	fn stand_in(name: &str) -> UciEngine {
+		let path = std::env::temp_dir().join(format!("rip-{}-{}.sh", name, std::process::id()));
+		std::fs::write(&path, STAND_IN).unwrap();
+
+		let engine = UciEngine::spawn("sh", &[path.to_str().unwrap()]).unwrap();
+		std::fs::remove_file(path).unwrap();
+		engine
+	}
+
+	#[test]
+	fn test_handshake() {
+		let mut engine = stand_in("handshake");
+
+		assert_eq!(engine.name(), "Stand-in");
+		assert_eq!(engine.author(), Some("Nobody"));
+		assert_eq!(engine.options().len(), 3);
+		assert_eq!(engine.option("hash").unwrap().default(), Some("16"));
+		assert_eq!(engine.option("Clear Hash").unwrap().kind(), "button");
+		assert_eq!(engine.option("SyzygyPath").unwrap().default(), None);
+
+		engine.set_option("Hash", "64").unwrap();
+		engine.set_option("Clear Hash", "").unwrap();
+		assert!(engine.set_option("Threads", "4").is_err());
+
+		assert!(UciEngine::spawn("/nonexistent/engine", &[]).is_err());
+	}
+
+	#[test]
+	fn test_search() {
+		let mut engine = stand_in("search");
+		let mut infos = Vec::new();
+
+		let best = engine.search(&Game::new(), &[], &Limits::depth(2), |info| infos.push(info.clone())).unwrap();
+
+		assert_eq!(infos.len(), 3);
+		assert_eq!(infos[0].string(), Some("go depth 2"));
+		assert_eq!(infos[1].seldepth(), Some(2));
+		assert_eq!(infos[1].nps(), Some(20_000));
+		assert_eq!(infos[1].pv().len(), 1);
+
+		// the pv is cut off at the illegal king move
+		let last = best.info().unwrap();
+		assert_eq!(last.depth(), Some(2));
+		assert_eq!(last.score(), Some(Score::Mate(-3)));
+		assert_eq!(last.bound(), Some(Bound::Upper));
+		assert_eq!(last.time(), Some(Duration::from_millis(10)));
+		assert_eq!(last.pv().len(), 2);
+
+		let game = Game::new();
+		assert_eq!(game.to_uci(best.mv()), "e2e4");
+
+		let mut after = game.clone();
+		after.make_move(best.mv()).unwrap();
+		assert_eq!(after.to_uci(best.ponder().unwrap()), "e7e5");
+
+		// the stand-in answers e2e4 for black too
+		let error = engine.search(&game, &[best.mv()], &Limits::nodes(1_000), |_| ()).err().unwrap();
+		assert_eq!(error, "Stand-in answered with the illegal move e2e4");
+
+		assert!(engine.search(&game, &[], &Limits::default(), |_| ()).is_err());
+	}
+
+	#[test]
+	fn test_limits() {
+		let limits = Limits::move_time(Duration::from_millis(250));
+		assert_eq!(UciEngine::go_command(&limits).unwrap(), "go movetime 250");
+
+		let mut engine = stand_in("limits");
+		let mut command = String::new();
+		engine
+			.search(&Game::new(), &[], &limits, |info| {
+				if let Some(string) = info.string() {
+					command = string.to_string();
+				}
+			})
+			.unwrap();
+
+		assert_eq!(command, "go movetime 250");
+	}
+
+	#[test]
+	fn test_match() {
+		let control = TimeControl::fischer(Duration::from_secs(60), Duration::from_secs(1));
+		let mut arena = Match::new(Box::new(stand_in("white")), Box::new(stand_in("black")), control).games(1);
+		arena.run(|_, _| ());
+
+		let game = &arena.played()[0];
+		assert_eq!(game.termination(), Termination::IllegalMove);
+		assert_eq!(game.tree().mainline().len(), 1);
+		assert!(arena.to_pgn().contains("{Black made the illegal move e2e4} 1-0"));
+	}
+}
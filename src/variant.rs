@@ -0,0 +1,431 @@
+use crate::board::Board;
+use crate::color::Color;
+use crate::fairy::PieceKind;
+use crate::fen::STARTING_POSITION;
+use crate::game::Game;
+use crate::moves::Move;
+use crate::name::Name;
+use crate::square::Square;
+
+use std::sync::Arc;
+
+pub use crate::antichess::Antichess;
+pub use crate::atomic::Atomic;
+pub use crate::crazyhouse::{Bughouse, Crazyhouse};
+pub use crate::horde::Horde;
+pub use crate::king_of_the_hill::KingOfTheHill;
+pub use crate::racing_kings::RacingKings;
+pub use crate::three_check::ThreeCheck;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Outcome {
+	Win(Color),
+	Draw,
+}
+
+// the rules of a variant, every hook does what standard chess does
+// so a variant only has to override the ones it changes
+pub trait Variant: Send + Sync {
+	fn name(&self) -> &str;
+
+	fn starting_fen(&self) -> &str {
+		STARTING_POSITION
+	}
+
+	// called when the variant is set on a game, to fix up the position
+	fn setup(&self, _game: &mut Game) {}
+
+	// the pieces a pawn can be promoted to
+	fn promotions(&self) -> Vec<Name> {
+		Name::promotions()
+	}
+
+	// the fairy pieces of the variant, which are known by their letters
+	// in FEN, SAN and UCI without registering them
+	fn pieces(&self) -> Vec<PieceKind> {
+		Vec::new()
+	}
+
+	// whether pieces can be dropped from a pocket
+	fn has_drops(&self) -> bool {
+		false
+	}
+
+	// whether the checks given are counted, and written to the FEN
+	fn counts_checks(&self) -> bool {
+		false
+	}
+
+	// the moves before the legality filter
+	fn pseudo_legal_moves(&self, game: &Game) -> Vec<Move> {
+		let mut moves = game.piece_moves();
+		moves.extend(game.castling_moves());
+		moves
+	}
+
+	// whether a piece of the other color attacks the square
+	fn is_attacked(&self, board: &Board, square: Square, color: Color) -> bool {
+		board.is_attacked(square, color)
+	}
+
+	fn is_in_check(&self, board: &Board, color: Color) -> bool {
+		match board.find_king(color) {
+			Some(king) => self.is_attacked(board, king, color),
+			None => false,
+		}
+	}
+
+	// `after` is the board once the move has been made
+	fn is_legal(&self, game: &Game, _mv: Move, after: &Board) -> bool {
+		!self.is_in_check(after, game.turn())
+	}
+
+	// whether a move is legal exactly when it doesn't leave the own king in check,
+	// which lets the pins decide most moves without trying them out. A variant
+	// has to opt in, otherwise its `is_legal` could be skipped for most moves
+	fn standard_legality(&self) -> bool {
+		false
+	}
+
+	// whether the rules stay the same when the board is turned or mirrored, so an
+	// endgame table only keeps one of those positions. How the pieces move is
+	// checked on its own, e.g. pawns only let the files be mirrored
+	fn is_symmetric(&self) -> bool {
+		false
+	}
+
+	// gets the legal moves last, e.g. to make capturing compulsory
+	fn filter_moves(&self, _game: &Game, moves: Vec<Move>) -> Vec<Move> {
+		moves
+	}
+
+	// called once the pieces have been moved, also when a move is only tried out
+	fn after_apply(&self, _board: &mut Board, _mv: Move, _color: Color, _capture: bool) {}
+
+	// called once a move has been played and the turn has passed,
+	// `captured` is the piece that was taken as it would go into a pocket
+	fn after_play(&self, _game: &mut Game, _mv: Move, _captured: Option<Name>) {}
+
+	// the game ending by the rules of the variant, no moves are generated once it has
+	fn outcome(&self, _game: &Game) -> Option<Outcome> {
+		None
+	}
+
+	// whether the side could still win with any series of legal moves,
+	// a side that can't doesn't win when the other side runs out of time
+	fn has_mating_material(&self, game: &Game, color: Color) -> bool {
+		game.has_standard_mating_material(color)
+	}
+
+	// the result when the side to move has no legal moves
+	fn no_moves_outcome(&self, game: &Game) -> Outcome {
+		if game.is_check() {
+			Outcome::Win(game.turn().opposite())
+		} else {
+			Outcome::Draw
+		}
+	}
+}
+
+pub struct Standard;
+
+impl Variant for Standard {
+	fn name(&self) -> &str {
+		"standard"
+	}
+
+	fn standard_legality(&self) -> bool {
+		true
+	}
+
+	fn is_symmetric(&self) -> bool {
+		true
+	}
+}
+
+// 10x8 with the archbishop (A) between the knight and the bishop
+// and the chancellor (C) between the bishop and the knight
+pub struct Capablanca;
+
+impl Variant for Capablanca {
+	fn name(&self) -> &str {
+		"capablanca"
+	}
+
+	fn standard_legality(&self) -> bool {
+		true
+	}
+
+	fn is_symmetric(&self) -> bool {
+		true
+	}
+
+	fn starting_fen(&self) -> &str {
+		"rnabqkbcnr/pppppppppp/10/10/10/10/PPPPPPPPPP/RNABQKBCNR w KQkq - 0 1"
+	}
+
+	fn pieces(&self) -> Vec<PieceKind> {
+		ten_file_pieces()
+	}
+
+	fn promotions(&self) -> Vec<Name> {
+		ten_file_promotions()
+	}
+}
+
+// 10x8 with the chancellor next to the queen and the archbishop next to the king
+pub struct Gothic;
+
+impl Variant for Gothic {
+	fn name(&self) -> &str {
+		"gothic"
+	}
+
+	fn standard_legality(&self) -> bool {
+		true
+	}
+
+	fn is_symmetric(&self) -> bool {
+		true
+	}
+
+	fn starting_fen(&self) -> &str {
+		"rnbqckabnr/pppppppppp/10/10/10/10/PPPPPPPPPP/RNBQCKABNR w KQkq - 0 1"
+	}
+
+	fn pieces(&self) -> Vec<PieceKind> {
+		ten_file_pieces()
+	}
+
+	fn promotions(&self) -> Vec<Name> {
+		ten_file_promotions()
+	}
+}
+
+fn ten_file_pieces() -> Vec<PieceKind> {
+	vec![PieceKind::archbishop(), PieceKind::chancellor()]
+}
+
+fn ten_file_promotions() -> Vec<Name> {
+	let mut names = Name::promotions();
+	names.extend(ten_file_pieces().into_iter().map(Name::Custom));
+	names
+}
+
+// looks up one of the variants that come with the crate
+pub fn from_name(name: &str) -> Option<Arc<dyn Variant>> {
+	let variant: Arc<dyn Variant> = match name.to_lowercase().as_str() {
+		"standard" | "chess" => Arc::new(Standard),
+		"capablanca" => Arc::new(Capablanca),
+		"gothic" => Arc::new(Gothic),
+		"crazyhouse" => Arc::new(Crazyhouse),
+		"bughouse" => Arc::new(Bughouse),
+		"atomic" => Arc::new(Atomic),
+		"threecheck" | "3check" => Arc::new(ThreeCheck),
+		"kingofthehill" => Arc::new(KingOfTheHill),
+		"racingkings" => Arc::new(RacingKings),
+		"antichess" => Arc::new(Antichess),
+		"horde" => Arc::new(Horde),
+		_ => return None,
+	};
+
+	Some(variant)
+}
+
+impl Game {
+	pub fn with_variant<V: Variant + 'static>(variant: V) -> Game {
+		let fen = variant.starting_fen().to_string();
+		Game::from_variant_fen(variant, &fen).unwrap()
+	}
+
+	pub fn from_variant_fen<V: Variant + 'static>(variant: V, fen: &str) -> Result<Game, String> {
+		Game::from_fen_with_variant(fen, Arc::new(variant))
+	}
+
+	// the same for a variant that is only known at runtime, e.g. from `from_name`
+	pub fn from_fen_with_variant(fen: &str, variant: Arc<dyn Variant>) -> Result<Game, String> {
+		let mut game = Game::read_fen(fen, &variant.pieces())?;
+		game.set_variant(variant);
+
+		Ok(game)
+	}
+
+	pub fn set_variant(&mut self, variant: Arc<dyn Variant>) {
+		self.forget_moves();
+		self.variant = variant.clone();
+		variant.setup(self);
+	}
+
+	// a letter in the notation of this game, which can be one of the variant's pieces
+	pub(crate) fn name_from_letter(&self, letter: char) -> Option<Name> {
+		Name::from_letter(letter, &self.variant.pieces())
+	}
+
+	pub fn variant(&self) -> &dyn Variant {
+		&*self.variant
+	}
+
+	pub fn remaining_checks(&self, color: Color) -> usize {
+		self.remaining_checks[color.index()]
+	}
+
+	// whether the game has been ended by the rules of the variant,
+	// there are no legal moves left when it has
+	pub fn is_variant_end(&self) -> bool {
+		self.variant.outcome(self).is_some()
+	}
+
+	pub fn outcome(&self) -> Option<Outcome> {
+		if let Some(outcome) = self.variant.outcome(self) {
+			return Some(outcome);
+		}
+
+		if !self.legal_moves().is_empty() {
+			return None;
+		}
+
+		Some(self.variant.no_moves_outcome(self))
+	}
+
+	pub fn winner(&self) -> Option<Color> {
+		match self.outcome() {
+			Some(Outcome::Win(color)) => Some(color),
+			_ => None,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_perft_capablanca() {
+		let game = Game::with_variant(Capablanca);
+
+		assert_eq!(game.to_fen(), Capablanca.starting_fen());
+		assert_eq!(game.perft(1), 28);
+		assert_eq!(game.perft(2), 784);
+	}
+
+	#[test]
+	fn test_perft_gothic() {
+		let game = Game::with_variant(Gothic);
+
+		assert_eq!(game.to_fen(), Gothic.starting_fen());
+		assert_eq!(game.perft(1), 28);
+		assert_eq!(game.perft(2), 784);
+	}
+
+	#[test]
+	fn test_castling_on_ten_files() {
+		let mut game =
+			Game::from_variant_fen(Capablanca, "r4k3r/10/10/10/10/10/10/R4K3R w KQkq - 0 1").unwrap();
+
+		let short = game.parse_uci("f1i1").unwrap();
+		let long = game.parse_uci("f1c1").unwrap();
+		assert!(short.is_castle() && long.is_castle());
+		assert_eq!(game.to_san(short), "O-O");
+
+		game.make_move(short).unwrap();
+		assert_eq!(game.board().placement(), "r4k3r/10/10/10/10/10/10/R6RK1");
+		assert!(!game.is_chess960());
+	}
+
+	#[test]
+	fn test_promotion_to_new_pieces() {
+		let game = Game::from_variant_fen(Capablanca, "5k4/P9/10/10/10/10/10/5K4 w - - 0 1").unwrap();
+
+		// queen, rook, bishop, knight, archbishop and chancellor
+		assert_eq!(game.legal_moves().len(), 5 + 6);
+
+		let mv = game.parse_san("a8=C").unwrap();
+		assert_eq!(mv.promotion(), Some(Name::Custom(PieceKind::chancellor())));
+		assert_eq!(game.to_uci(mv), "a7a8c");
+
+		let mut game = game;
+		game.make_move(mv).unwrap();
+		assert_eq!(game.to_fen(), "C4k4/10/10/10/10/10/10/5K4 b - - 0 1");
+		assert_eq!(game.turn(), Color::Black);
+	}
+
+	#[test]
+	fn test_san_for_new_pieces() {
+		let game = Game::with_variant(Capablanca);
+
+		let mv = game.parse_san("Ad3").unwrap();
+		assert_eq!(mv.from(), Square::from_str("c1").unwrap());
+		assert_eq!(game.to_san(mv), "Ad3");
+		assert_eq!(game.to_san(game.parse_uci("h1i3").unwrap()), "Ci3");
+	}
+
+	#[test]
+	fn test_variant_pieces() {
+		let game = Game::from_fen_with_variant(Gothic.starting_fen(), from_name("gothic").unwrap()).unwrap();
+
+		let piece = |square: &str| game.board().get(Square::from_str(square).unwrap()).piece.unwrap().name;
+		assert_eq!(piece("e1"), Name::Custom(PieceKind::chancellor()));
+		assert_eq!(piece("g8"), Name::Custom(PieceKind::archbishop()));
+		assert_eq!(game.to_fen(), Gothic.starting_fen());
+	}
+
+	#[test]
+	fn test_from_name() {
+		let mut game = Game::from_fen("4k3/8/2pnp3/3r4/8/8/8/3RK3 w - - 0 1").unwrap();
+		assert_eq!(game.variant().name(), "standard");
+
+		game.set_variant(from_name("Atomic").unwrap());
+		assert_eq!(game.variant().name(), "atomic");
+		game.make_move(game.parse_uci("d1d5").unwrap()).unwrap();
+		assert_eq!(game.to_fen(), "4k3/8/2p1p3/8/8/8/8/4K3 b - - 0 1");
+
+		assert!(from_name("chaturanga").is_none());
+	}
+
+	// a house variant only overrides the hooks it changes
+	struct NoCastling;
+
+	impl Variant for NoCastling {
+		fn name(&self) -> &str {
+			"nocastling"
+		}
+
+		fn pseudo_legal_moves(&self, game: &Game) -> Vec<Move> {
+			game.piece_moves()
+		}
+	}
+
+	#[test]
+	fn test_house_variant() {
+		let fen = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+
+		assert_eq!(Game::from_fen(fen).unwrap().perft(1), 26);
+		assert_eq!(Game::from_variant_fen(NoCastling, fen).unwrap().perft(1), 24);
+	}
+
+	// only the legality changes, giving check isn't allowed
+	struct NoChecks;
+
+	impl Variant for NoChecks {
+		fn name(&self) -> &str {
+			"nochecks"
+		}
+
+		fn is_legal(&self, game: &Game, _mv: Move, after: &Board) -> bool {
+			!self.is_in_check(after, game.turn()) && !self.is_in_check(after, game.turn().opposite())
+		}
+	}
+
+	#[test]
+	fn test_custom_legality() {
+		let mut game = Game::from_variant_fen(NoChecks, "4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+		let check = Move::new(Square::from_str("a1").unwrap(), Square::from_str("a8").unwrap());
+
+		assert!(!game.legal_moves().contains(&check));
+		assert!(game.parse_uci("a1a8").is_err());
+		assert!(game.make_move(check).is_err());
+		assert!(game.parse_uci("a1a7").is_ok());
+	}
+}
@@ -0,0 +1,404 @@
+#!/usr/bin/env python3
+# Writes KRvK.rtbw and KRvK.rtbz in the Syzygy format, for the tests of
+# src/syzygy.rs. The endgame is solved here by retrograde analysis and the
+# values are compressed the way the real tables are: pairs of symbols are
+# merged (RE-PAIR) and the symbols get a canonical Huffman code.
+
+import heapq
+import os
+import struct
+
+WDL_MAGIC = bytes([0x71, 0xE8, 0x23, 0x5D])
+DTZ_MAGIC = bytes([0xD7, 0x66, 0x0C, 0xA5])
+SINGLE_VALUE = 0x80
+
+# piece codes of the format, white king, white rook and black king
+PIECES = [6, 4, 14]
+
+
+def rank(square):
+	return square >> 3
+
+
+def file(square):
+	return square & 7
+
+
+def off_diagonal(square):
+	return rank(square) - file(square)
+
+
+def flip_diagonal(square):
+	return ((square >> 3) | (square << 3)) & 63
+
+
+TRIANGLE = {1: 0, 2: 1, 3: 2, 10: 3, 11: 4, 19: 5, 0: 6, 9: 7, 18: 8, 27: 9}
+LOWER = {}
+for square in range(64):
+	if off_diagonal(square) < 0:
+		LOWER[square] = len(LOWER)
+
+SIZE = 6 * 63 * 62 + 4 * 28 * 62 + 4 * 7 * 28 + 4 * 7 * 6
+
+
+# the index of three unique pieces, as in the probing code of the tables
+def encode(squares):
+	pos = list(squares)
+	if pos[0] & 0x04:
+		pos = [square ^ 0x07 for square in pos]
+	if pos[0] & 0x20:
+		pos = [square ^ 0x38 for square in pos]
+
+	for i in range(3):
+		if off_diagonal(pos[i]):
+			if off_diagonal(pos[i]) > 0:
+				for j in range(i, 3):
+					pos[j] = flip_diagonal(pos[j])
+			break
+
+	i = int(pos[1] > pos[0])
+	j = int(pos[2] > pos[0]) + int(pos[2] > pos[1])
+
+	if off_diagonal(pos[0]):
+		return TRIANGLE[pos[0]] * 63 * 62 + (pos[1] - i) * 62 + (pos[2] - j)
+	if off_diagonal(pos[1]):
+		return 6 * 63 * 62 + rank(pos[0]) * 28 * 62 + LOWER[pos[1]] * 62 + pos[2] - j
+	if off_diagonal(pos[2]):
+		return 6 * 63 * 62 + 4 * 28 * 62 + rank(pos[0]) * 7 * 28 + (rank(pos[1]) - i) * 28 + LOWER[pos[2]]
+	return 6 * 63 * 62 + 4 * 28 * 62 + 4 * 7 * 28 + rank(pos[0]) * 7 * 6 + (rank(pos[1]) - i) * 6 + rank(pos[2]) - j
+
+
+def adjacent(a, b):
+	return max(abs(rank(a) - rank(b)), abs(file(a) - file(b))) <= 1
+
+
+def king_steps(square):
+	for dr in (-1, 0, 1):
+		for df in (-1, 0, 1):
+			r, f = rank(square) + dr, file(square) + df
+			if (dr or df) and 0 <= r < 8 and 0 <= f < 8:
+				yield r * 8 + f
+
+
+# the squares a rook on `square` reaches, up to and with the first blocker
+def rook_rays(square, blockers):
+	for dr, df in ((1, 0), (-1, 0), (0, 1), (0, -1)):
+		r, f = rank(square) + dr, file(square) + df
+		while 0 <= r < 8 and 0 <= f < 8:
+			yield r * 8 + f
+			if r * 8 + f in blockers:
+				break
+			r, f = r + dr, f + df
+
+
+def rook_attacks(rook, king, target):
+	return target in set(rook_rays(rook, {king}))
+
+
+def solve():
+	# one position per index, the values are the same for all of its mirrors
+	positions = {}
+	for wk in range(64):
+		for wr in range(64):
+			for bk in range(64):
+				if len({wk, wr, bk}) < 3 or adjacent(wk, bk):
+					continue
+				positions.setdefault(encode([wk, wr, bk]), (wk, wr, bk))
+
+	white_moves = {}
+	black_moves = {}
+	mated = []
+	escapes = set()
+
+	for index, (wk, wr, bk) in positions.items():
+		# white to move, the black king can't be in check
+		if not rook_attacks(wr, wk, bk):
+			moves = set()
+			for to in king_steps(wk):
+				if to != wr and not adjacent(to, bk):
+					moves.add(encode([to, wr, bk]))
+			for to in rook_rays(wr, {wk, bk}):
+				if to not in (wk, bk):
+					moves.add(encode([wk, to, bk]))
+			white_moves[index] = moves
+
+		# black to move
+		moves = set()
+		escape = False
+		attacked = set(rook_rays(wr, {wk}))
+		for to in king_steps(bk):
+			if adjacent(to, wk):
+				continue
+			if to == wr:
+				escape = True
+			elif to not in attacked:
+				moves.add(encode([wk, wr, to]))
+		if escape:
+			escapes.add(index)
+		elif not moves and bk in attacked:
+			mated.append(index)
+		black_moves[index] = moves
+
+	wins = {}
+	losses = {index: 0 for index in mated}
+	ply = 0
+	while True:
+		ply += 1
+		found = False
+		if ply % 2 == 1:
+			for index, moves in white_moves.items():
+				if index not in wins and any(losses.get(m) == ply - 1 for m in moves):
+					wins[index] = ply
+					found = True
+		else:
+			for index, moves in black_moves.items():
+				if index in losses or index in escapes or not moves:
+					continue
+				if all(m in wins for m in moves):
+					losses[index] = ply
+					found = True
+		if not found and ply % 2 == 0 and ply > 2:
+			# a ply without news on both sides ends the search
+			if not any(v == ply - 1 for v in wins.values()):
+				break
+
+	return white_moves, black_moves, wins, losses
+
+
+class Symbols:
+	def __init__(self):
+		self.tree = []
+		self.lengths = []
+
+	def leaf(self, value):
+		self.tree.append((value, 0xFFF))
+		self.lengths.append(1)
+		return len(self.tree) - 1
+
+	def pair(self, left, right):
+		self.tree.append((left, right))
+		self.lengths.append(self.lengths[left] + self.lengths[right])
+		return len(self.tree) - 1
+
+
+def re_pair(values, symbols):
+	leaves = {}
+	sequence = []
+	for value in values:
+		if value not in leaves:
+			leaves[value] = symbols.leaf(value)
+		sequence.append(leaves[value])
+
+	while len(symbols.tree) < 400:
+		counts = {}
+		i = 0
+		while i + 1 < len(sequence):
+			pair = (sequence[i], sequence[i + 1])
+			counts[pair] = counts.get(pair, 0) + 1
+			# a run like a a a counts its pair once
+			i += 2 if pair[0] == pair[1] and i + 2 < len(sequence) and sequence[i + 2] == pair[0] else 1
+
+		candidates = [(count, pair) for pair, count in counts.items()
+			if symbols.lengths[pair[0]] + symbols.lengths[pair[1]] <= 256]
+		if not candidates:
+			break
+		count, best = max(candidates)
+		if count < 4:
+			break
+
+		new = symbols.pair(*best)
+		merged = []
+		i = 0
+		while i < len(sequence):
+			if i + 1 < len(sequence) and (sequence[i], sequence[i + 1]) == best:
+				merged.append(new)
+				i += 2
+			else:
+				merged.append(sequence[i])
+				i += 1
+		sequence = merged
+
+	return sequence
+
+
+def huffman_lengths(frequencies):
+	heap = [(frequency, symbol, [symbol]) for symbol, frequency in frequencies.items()]
+	heapq.heapify(heap)
+	lengths = {symbol: 0 for symbol in frequencies}
+	while len(heap) > 1:
+		f1, s1, a = heapq.heappop(heap)
+		f2, s2, b = heapq.heappop(heap)
+		for symbol in a + b:
+			lengths[symbol] += 1
+		heapq.heappush(heap, (f1 + f2, min(s1, s2), a + b))
+	return lengths
+
+
+def compress(values, block_log, span_log):
+	symbols = Symbols()
+	sequence = re_pair(values, symbols)
+
+	frequencies = {}
+	for symbol in sequence:
+		frequencies[symbol] = frequencies.get(symbol, 0) + 1
+	assert len(frequencies) >= 2
+	code_lengths = huffman_lengths(frequencies)
+	min_length = min(code_lengths.values())
+	max_length = max(code_lengths.values())
+	assert max_length <= 32
+
+	# the longest codes get the lowest symbols, the symbols without a code come last
+	coded = sorted(frequencies, key=lambda symbol: (-code_lengths[symbol], symbol))
+	uncoded = [symbol for symbol in range(len(symbols.tree)) if symbol not in frequencies]
+	order = coded + uncoded
+	renumber = {old: new for new, old in enumerate(order)}
+
+	counts = {length: 0 for length in range(min_length, max_length + 1)}
+	for symbol in coded:
+		counts[code_lengths[symbol]] += 1
+
+	lowest = {max_length: 0}
+	base = {max_length: 0}
+	for length in range(max_length - 1, min_length - 1, -1):
+		lowest[length] = lowest[length + 1] + counts[length + 1]
+		assert (base[length + 1] + counts[length + 1]) % 2 == 0
+		base[length] = (base[length + 1] + counts[length + 1]) // 2
+	assert base[min_length] + counts[min_length] == 1 << min_length
+
+	def code(symbol):
+		length = code_lengths[symbol]
+		return length, base[length] + renumber[symbol] - lowest[length]
+
+	# whole symbols go into blocks, which hold at most 65536 values
+	block_size = 1 << block_log
+	blocks = []
+	bits = []
+	block_values = 0
+	for symbol in sequence:
+		length, value = code(symbol)
+		if len(bits) + length > block_size * 8 or block_values + symbols.lengths[symbol] > 65536:
+			blocks.append((bits, block_values))
+			bits, block_values = [], 0
+		bits.extend((value >> (length - 1 - i)) & 1 for i in range(length))
+		block_values += symbols.lengths[symbol]
+	blocks.append((bits, block_values))
+
+	data = bytearray()
+	for bits, _ in blocks:
+		bits = bits + [0] * (block_size * 8 - len(bits))
+		for i in range(0, len(bits), 8):
+			data.append(int("".join(map(str, bits[i:i + 8])), 2))
+
+	starts = []
+	start = 0
+	for _, count in blocks:
+		starts.append(start)
+		start += count
+	assert start == len(values)
+
+	span = 1 << span_log
+	sparse = bytearray()
+	for k in range((len(values) + span - 1) // span):
+		position = k * span + span // 2
+		block = len(blocks) - 1
+		while block > 0 and starts[block] > position:
+			block -= 1
+		sparse += struct.pack("<IH", block, position - starts[block])
+
+	sizes = bytearray()
+	for _, count in blocks:
+		sizes += struct.pack("<H", count - 1)
+
+	header = bytearray([0, block_log, span_log, 0])
+	header += struct.pack("<I", len(blocks))
+	header += bytes([max_length, min_length])
+	for length in range(min_length, max_length + 1):
+		header += struct.pack("<H", lowest[length])
+	header += struct.pack("<H", len(order))
+	for old in order:
+		left, right = symbols.tree[old]
+		if right != 0xFFF:
+			left, right = renumber[left], renumber[right]
+		header += bytes([left & 0xFF, (left >> 8) | ((right & 0xF) << 4), right >> 4])
+	if len(order) % 2 == 1:
+		header.append(0)
+
+	return header, sparse, sizes, data
+
+
+# unknown values (positions that can't happen) copy the one before them
+def fill(values):
+	last = next(value for value in values if value is not None)
+	filled = []
+	for value in values:
+		last = last if value is None else value
+		filled.append(last)
+	return filled
+
+
+def align(out, alignment):
+	while len(out) % alignment:
+		out.append(0)
+
+
+def write(path, magic, tables, dtz_flags=0):
+	out = bytearray(magic)
+	out.append(1 if len(tables) == 2 else 0)
+	out.append(0)
+	out += bytes(code | code << 4 for code in PIECES)
+	align(out, 2)
+
+	parts = []
+	for table in tables:
+		if isinstance(table, int):
+			out += bytes([SINGLE_VALUE, table])
+			parts.append(None)
+		else:
+			header, sparse, sizes, data = table
+			header[0] |= dtz_flags
+			out += header
+			parts.append((sparse, sizes, data))
+
+	align(out, 2)
+	for part in parts:
+		if part:
+			out += part[0]
+	for part in parts:
+		if part:
+			out += part[1]
+	for part in parts:
+		if part:
+			align(out, 64)
+			out += part[2]
+
+	# room for the decoder to read ahead at the end of the last block
+	out += bytes(8)
+
+	with open(path, "wb") as f:
+		f.write(out)
+
+
+def main():
+	white_moves, black_moves, wins, losses = solve()
+	assert max(wins.values()) == 31 and max(losses.values()) == 32
+
+	white = [None] * SIZE
+	black = [None] * SIZE
+	dtz = [None] * SIZE
+	for index in white_moves:
+		white[index] = 4 if index in wins else 2
+		if index in wins:
+			dtz[index] = (wins[index] - 1) // 2
+	for index in black_moves:
+		black[index] = 0 if index in losses else 2
+
+	directory = os.path.dirname(os.path.abspath(__file__))
+
+	# white to move always wins, as the real tables store it
+	assert set(value for value in white if value is not None) == {4}
+	write(os.path.join(directory, "KRvK.rtbw"), WDL_MAGIC, [4, compress(fill(black), 6, 10)])
+	write(os.path.join(directory, "KRvK.rtbz"), DTZ_MAGIC, [compress(fill(dtz), 6, 9)])
+
+
+if __name__ == "__main__":
+	main()